@@ -0,0 +1,59 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use roc_ast::builtin_aliases::SolvedType;
+use roc_module::ident::Lowercase;
+use roc_module::symbol::Symbol;
+use roc_types::types::RecordField;
+
+const FIELDS: usize = 1_000;
+const DEPTH: usize = 20;
+
+// List (List (... Str))
+fn nested_list(depth: usize) -> SolvedType {
+    (0..depth).fold(SolvedType::Apply(Symbol::STR_STR, Vec::new()), |typ, _| {
+        SolvedType::Apply(Symbol::LIST_LIST, vec![typ])
+    })
+}
+
+// A record whose fields all have the same deep type, and only the name of the last one differs
+fn big_record(last_label: &str) -> SolvedType {
+    let mut fields: Vec<_> = (0..FIELDS - 1)
+        .map(|i| {
+            (
+                Lowercase::from(format!("field{}", i)),
+                RecordField::Required(nested_list(DEPTH)),
+            )
+        })
+        .collect();
+
+    fields.push((
+        Lowercase::from(last_label),
+        RecordField::Required(nested_list(DEPTH)),
+    ));
+
+    SolvedType::Record {
+        fields,
+        ext: Box::new(SolvedType::EmptyRecord),
+    }
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let record = big_record("last");
+    let renamed = big_record("renamed");
+
+    // The shallow hashes differ, so fast_eq never gets to the deep compare
+    assert_ne!(record.shallow_hash(), renamed.shallow_hash());
+    assert!(record != renamed);
+    assert!(!record.fast_eq(&renamed));
+    assert!(record.fast_eq(&record.clone()));
+
+    c.bench_function("== on records that differ in their last field", |b| {
+        b.iter(|| black_box(black_box(&record) == black_box(&renamed)))
+    });
+
+    c.bench_function("fast_eq on records that differ in their last field", |b| {
+        b.iter(|| black_box(black_box(&record).fast_eq(black_box(&renamed))))
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -1,20 +1,25 @@
 use roc_collections::all::{default_hasher, ImMap, MutMap};
-use roc_module::ident::{Lowercase, TagName};
-use roc_module::symbol::Symbol;
+use roc_module::ident::{sort_by_name, Lowercase, TagName};
+use roc_module::symbol::{Interns, Symbol};
 use roc_region::all::{Loc, Region};
 use roc_types::subs::{VarId, Variable};
 use roc_types::types::{AliasKind, Problem, RecordField};
+use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SolvedLambdaSet(pub SolvedType);
 
 /// This is a fully solved type, with no Variables remaining in it.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SolvedType {
-    /// A function. The types of its arguments, then the type of its return value.
+    /// A function. The types of its arguments, its closure, then the type of its return value.
+    /// The last field has the name of each argument where it is known, e.g. for signature help,
+    /// and is always as long as the arguments. Use `SolvedType::func` when there are no names.
     #[allow(unused)]
-    Func(Vec<SolvedType>, Box<SolvedType>, Box<SolvedType>),
+    Func(Vec<SolvedType>, Box<SolvedType>, Box<SolvedType>, ArgNames),
     /// Applying a type to some arguments (e.g. Map.Map String Int)
     #[allow(unused)]
     Apply(Symbol, Vec<SolvedType>),
@@ -24,7 +29,7 @@ pub enum SolvedType {
     Flex(VarId),
     #[allow(unused)]
     Wildcard,
-    /// Inline type alias, e.g. `as List a` in `[Cons a (List a), Nil] as List a`
+    /// A record, e.g. `{ name: Str }`
     #[allow(unused)]
     Record {
         fields: Vec<(Lowercase, RecordField<SolvedType>)>,
@@ -46,6 +51,10 @@ pub enum SolvedType {
     #[allow(unused)]
     Erroneous(Problem),
 
+    /// A named alias, and also an inline `as` annotation on a non-recursive type:
+    /// `{ x : I64 } as Point` is `Alias(Point, [], [], { x : I64 }, Structural)`.
+    /// Inline `as` on a recursive tag union, e.g. `[Cons a (List a), Nil] as List a`,
+    /// becomes a RecursiveTagUnion instead.
     Alias(
         Symbol,
         Vec<SolvedType>,
@@ -68,6 +77,912 @@ pub enum SolvedType {
     Error,
 }
 
+/// The names of a function's arguments, where they are known.
+/// They are documentation, not part of the type: two functions that only differ in the names
+/// of their arguments are equal, so renaming an argument doesn't change a module's exports.
+#[derive(Debug, Clone, Default)]
+pub struct ArgNames(pub Vec<Option<Lowercase>>);
+
+impl PartialEq for ArgNames {
+    fn eq(&self, _other: &ArgNames) -> bool {
+        true
+    }
+}
+
+impl Hash for ArgNames {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl std::ops::Deref for ArgNames {
+    type Target = [Option<Lowercase>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl SolvedType {
+    /// A function whose arguments have no names
+    pub fn func(args: Vec<SolvedType>, closure: SolvedType, ret: SolvedType) -> SolvedType {
+        let arg_names = ArgNames(vec![None; args.len()]);
+
+        SolvedType::Func(args, Box::new(closure), Box::new(ret), arg_names)
+    }
+
+    /// A deterministic form of this type: record fields are sorted by name and tags by tag name,
+    /// recursively. Types that only differ in the order of their fields or tags become equal.
+    /// Argument names are kept, they don't take part in equality anyway.
+    pub fn canonicalize(self) -> SolvedType {
+        use SolvedType::*;
+
+        match self {
+            Func(args, closure, ret, arg_names) => Func(
+                canonicalize_all(args),
+                Box::new(closure.canonicalize()),
+                Box::new(ret.canonicalize()),
+                arg_names,
+            ),
+            Apply(symbol, args) => Apply(symbol, canonicalize_all(args)),
+            Record { fields, ext } => {
+                let mut fields: Vec<(Lowercase, RecordField<SolvedType>)> = fields
+                    .into_iter()
+                    .map(|(label, field)| (label, canonicalize_field(field)))
+                    .collect();
+
+                sort_by_name(&mut fields);
+
+                Record {
+                    fields,
+                    ext: Box::new(ext.canonicalize()),
+                }
+            }
+            TagUnion(tags, ext) => TagUnion(canonicalize_tags(tags), Box::new(ext.canonicalize())),
+            LambdaTag(symbol, args) => LambdaTag(symbol, canonicalize_all(args)),
+            FunctionOrTagUnion(tag_name, symbol, ext) => {
+                FunctionOrTagUnion(tag_name, symbol, Box::new(ext.canonicalize()))
+            }
+            RecursiveTagUnion(rec_var, tags, ext) => RecursiveTagUnion(
+                rec_var,
+                canonicalize_tags(tags),
+                Box::new(ext.canonicalize()),
+            ),
+            Alias(symbol, args, lambda_sets, actual, kind) => Alias(
+                symbol,
+                canonicalize_all(args),
+                canonicalize_lambda_sets(lambda_sets),
+                Box::new(actual.canonicalize()),
+                kind,
+            ),
+            HostExposedAlias {
+                name,
+                arguments,
+                lambda_set_variables,
+                actual_var,
+                actual,
+            } => HostExposedAlias {
+                name,
+                arguments: canonicalize_all(arguments),
+                lambda_set_variables: canonicalize_lambda_sets(lambda_set_variables),
+                actual_var,
+                actual: Box::new(actual.canonicalize()),
+            },
+            other @ (Rigid(_) | Flex(_) | Wildcard | EmptyRecord | EmptyTagUnion | Erroneous(_)
+            | Error) => other,
+        }
+    }
+}
+
+impl SolvedType {
+    /// This type with every alias kept opaque, like `Result ok err` in a hover tooltip.
+    /// Each alias becomes an `Apply` of its name to its arguments, without its actual type.
+    /// Use `expand_alias` to materialize one level on demand.
+    pub fn collapse_aliases(self) -> SolvedType {
+        self.transform(&mut |typ| match typ {
+            SolvedType::Alias(symbol, args, _, _, _) => SolvedType::Apply(symbol, args),
+            SolvedType::HostExposedAlias {
+                name, arguments, ..
+            } => SolvedType::Apply(name, arguments),
+            other => other,
+        })
+    }
+
+    /// Expands a collapsed alias one level, using its definition in `aliases`.
+    /// The aliases inside its actual type stay collapsed.
+    /// Returns None if this is not an `Apply` of one of the `aliases`.
+    pub fn expand_alias(&self, aliases: &MutMap<Symbol, BuiltinAlias>) -> Option<SolvedType> {
+        let (symbol, args) = match self {
+            SolvedType::Apply(symbol, args) => (symbol, args),
+            _ => return None,
+        };
+        let alias = aliases.get(symbol)?;
+
+        // the free variables of a builtin alias, in order, line up with its type arguments
+        let mut free_vars = FreeVarIds(Vec::new());
+        alias.typ.walk(&mut free_vars);
+        free_vars.0.sort();
+        free_vars.0.dedup();
+
+        if free_vars.0.len() != args.len() {
+            return None;
+        }
+
+        let actual = alias
+            .typ
+            .clone()
+            .collapse_aliases()
+            .transform(&mut |typ| match typ {
+                SolvedType::Flex(var) => match free_vars.0.iter().position(|v| *v == var) {
+                    Some(index) => args[index].clone(),
+                    None => SolvedType::Flex(var),
+                },
+                other => other,
+            });
+
+        Some(SolvedType::Alias(
+            *symbol,
+            args.clone(),
+            Vec::new(),
+            Box::new(actual),
+            alias.kind,
+        ))
+    }
+
+    /// Rebuilds this type bottom-up, applying `f` to every node after its children
+    fn transform<F: FnMut(SolvedType) -> SolvedType>(self, f: &mut F) -> SolvedType {
+        use SolvedType::*;
+
+        let transform_all = |types: Vec<SolvedType>, f: &mut F| -> Vec<SolvedType> {
+            types.into_iter().map(|typ| typ.transform(f)).collect()
+        };
+        let transform_tags =
+            |tags: Vec<(TagName, Vec<SolvedType>)>, f: &mut F| -> Vec<(TagName, Vec<SolvedType>)> {
+                tags.into_iter()
+                    .map(|(tag_name, args)| (tag_name, transform_all(args, f)))
+                    .collect()
+            };
+        let transform_lambda_sets =
+            |lambda_sets: Vec<SolvedLambdaSet>, f: &mut F| -> Vec<SolvedLambdaSet> {
+                lambda_sets
+                    .into_iter()
+                    .map(|SolvedLambdaSet(typ)| SolvedLambdaSet(typ.transform(f)))
+                    .collect()
+            };
+
+        let typ = match self {
+            Func(args, closure, ret, arg_names) => Func(
+                transform_all(args, f),
+                Box::new(closure.transform(f)),
+                Box::new(ret.transform(f)),
+                arg_names,
+            ),
+            Apply(symbol, args) => Apply(symbol, transform_all(args, f)),
+            Record { fields, ext } => Record {
+                fields: fields
+                    .into_iter()
+                    .map(|(label, field)| {
+                        let field = match field {
+                            RecordField::Optional(typ) => RecordField::Optional(typ.transform(f)),
+                            RecordField::Required(typ) => RecordField::Required(typ.transform(f)),
+                            RecordField::Demanded(typ) => RecordField::Demanded(typ.transform(f)),
+                            RecordField::RigidOptional(typ) => {
+                                RecordField::RigidOptional(typ.transform(f))
+                            }
+                        };
+
+                        (label, field)
+                    })
+                    .collect(),
+                ext: Box::new(ext.transform(f)),
+            },
+            TagUnion(tags, ext) => TagUnion(transform_tags(tags, f), Box::new(ext.transform(f))),
+            LambdaTag(symbol, args) => LambdaTag(symbol, transform_all(args, f)),
+            FunctionOrTagUnion(tag_name, symbol, ext) => {
+                FunctionOrTagUnion(tag_name, symbol, Box::new(ext.transform(f)))
+            }
+            RecursiveTagUnion(rec_var, tags, ext) => {
+                RecursiveTagUnion(rec_var, transform_tags(tags, f), Box::new(ext.transform(f)))
+            }
+            Alias(symbol, args, lambda_sets, actual, kind) => Alias(
+                symbol,
+                transform_all(args, f),
+                transform_lambda_sets(lambda_sets, f),
+                Box::new(actual.transform(f)),
+                kind,
+            ),
+            HostExposedAlias {
+                name,
+                arguments,
+                lambda_set_variables,
+                actual_var,
+                actual,
+            } => HostExposedAlias {
+                name,
+                arguments: transform_all(arguments, f),
+                lambda_set_variables: transform_lambda_sets(lambda_set_variables, f),
+                actual_var,
+                actual: Box::new(actual.transform(f)),
+            },
+            leaf @ (Rigid(_) | Flex(_) | Wildcard | EmptyRecord | EmptyTagUnion | Erroneous(_)
+            | Error) => leaf,
+        };
+
+        f(typ)
+    }
+}
+
+/// Collects the flex variables in a type
+struct FreeVarIds(Vec<VarId>);
+
+impl SolvedTypeVisitor for FreeVarIds {
+    fn visit_flex(&mut self, var: VarId) {
+        self.0.push(var);
+    }
+}
+
+impl SolvedType {
+    /// Visit this type and everything inside it, see SolvedTypeVisitor
+    pub fn walk<V: SolvedTypeVisitor>(&self, visitor: &mut V) {
+        visitor.visit(self);
+    }
+
+    /// Does the flex variable `var` appear anywhere in this type?
+    pub fn occurs(&self, var: VarId) -> bool {
+        struct Occurs {
+            var: VarId,
+            found: bool,
+        }
+
+        impl SolvedTypeVisitor for Occurs {
+            fn visit(&mut self, typ: &SolvedType) {
+                // no need to look any further once we've found it
+                if !self.found {
+                    walk_solved_type(self, typ);
+                }
+            }
+
+            fn visit_flex(&mut self, var: VarId) {
+                self.found |= var == self.var;
+            }
+        }
+
+        let mut visitor = Occurs { var, found: false };
+        self.walk(&mut visitor);
+        visitor.found
+    }
+
+    /// Does this type refer to itself, other than through the recursion variable of a
+    /// RecursiveTagUnion? The recursion variable may only appear in the tags of its own union,
+    /// and the variable of a HostExposedAlias may not appear in its actual type.
+    pub fn has_infinite_type(&self) -> bool {
+        use SolvedType::*;
+
+        match self {
+            RecursiveTagUnion(rec_var, tags, ext) => {
+                ext.occurs(*rec_var)
+                    || tags
+                        .iter()
+                        .any(|(_, args)| args.iter().any(SolvedType::has_infinite_type))
+                    || ext.has_infinite_type()
+            }
+            HostExposedAlias {
+                arguments,
+                lambda_set_variables,
+                actual_var,
+                actual,
+                ..
+            } => {
+                actual.occurs(*actual_var)
+                    || arguments.iter().any(SolvedType::has_infinite_type)
+                    || lambda_set_variables
+                        .iter()
+                        .any(|SolvedLambdaSet(set)| set.has_infinite_type())
+                    || actual.has_infinite_type()
+            }
+            Func(args, closure, ret, _) => {
+                args.iter().any(SolvedType::has_infinite_type)
+                    || closure.has_infinite_type()
+                    || ret.has_infinite_type()
+            }
+            Apply(_, args) | LambdaTag(_, args) => args.iter().any(SolvedType::has_infinite_type),
+            Record { fields, ext } => {
+                fields
+                    .iter()
+                    .any(|(_, field)| field.as_inner().has_infinite_type())
+                    || ext.has_infinite_type()
+            }
+            TagUnion(tags, ext) => {
+                tags.iter()
+                    .any(|(_, args)| args.iter().any(SolvedType::has_infinite_type))
+                    || ext.has_infinite_type()
+            }
+            FunctionOrTagUnion(_, _, ext) => ext.has_infinite_type(),
+            Alias(_, args, lambda_sets, actual, _) => {
+                args.iter().any(SolvedType::has_infinite_type)
+                    || lambda_sets
+                        .iter()
+                        .any(|SolvedLambdaSet(set)| set.has_infinite_type())
+                    || actual.has_infinite_type()
+            }
+            Flex(_) | Rigid(_) | Wildcard | EmptyRecord | EmptyTagUnion | Erroneous(_) | Error => {
+                false
+            }
+        }
+    }
+
+    /// A hash of the outermost layer of this type: its kind, names and arities, but not the
+    /// types inside it. Equal types always have equal shallow hashes, so different hashes
+    /// prove that two types differ without walking them.
+    pub fn shallow_hash(&self) -> u64 {
+        use SolvedType::*;
+
+        let mut state = default_hasher().build_hasher();
+        std::mem::discriminant(self).hash(&mut state);
+
+        match self {
+            Func(args, ..) => args.len().hash(&mut state),
+            Apply(symbol, args) | LambdaTag(symbol, args) => {
+                symbol.hash(&mut state);
+                args.len().hash(&mut state);
+            }
+            Rigid(name) => name.hash(&mut state),
+            Flex(var) => var.hash(&mut state),
+            Record { fields, .. } => {
+                for (label, _) in fields {
+                    label.hash(&mut state);
+                }
+            }
+            TagUnion(tags, _) => hash_tag_names(tags, &mut state),
+            RecursiveTagUnion(rec_var, tags, _) => {
+                rec_var.hash(&mut state);
+                hash_tag_names(tags, &mut state);
+            }
+            FunctionOrTagUnion(tag_name, symbol, _) => {
+                tag_name.hash(&mut state);
+                symbol.hash(&mut state);
+            }
+            Alias(symbol, args, _, _, _) => {
+                symbol.hash(&mut state);
+                args.len().hash(&mut state);
+            }
+            HostExposedAlias {
+                name,
+                arguments,
+                actual_var,
+                ..
+            } => {
+                name.hash(&mut state);
+                arguments.len().hash(&mut state);
+                actual_var.hash(&mut state);
+            }
+            Wildcard | EmptyRecord | EmptyTagUnion | Erroneous(_) | Error => {}
+        }
+
+        state.finish()
+    }
+
+    /// Same result as `==`, but skips the deep comparison when the shallow hashes differ
+    pub fn fast_eq(&self, other: &SolvedType) -> bool {
+        self.shallow_hash() == other.shallow_hash() && self == other
+    }
+}
+
+impl SolvedType {
+    /// Roc syntax for this type, for use in error messages. Type variables get generated names,
+    /// so a recursive tag union is printed once with an `as` binder, e.g. `[ Cons a b, Nil ] as b`.
+    /// Type names are looked up in `interns`, and printed without their module, like `List Str`.
+    pub fn display<'b>(&'b self, interns: &'b Interns) -> SolvedTypeDisplay<'b> {
+        SolvedTypeDisplay { typ: self, interns }
+    }
+}
+
+/// A SolvedType together with the names it needs for printing. See `SolvedType::display`.
+pub struct SolvedTypeDisplay<'b> {
+    typ: &'b SolvedType,
+    interns: &'b Interns,
+}
+
+impl fmt::Display for SolvedTypeDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use SolvedType::*;
+
+        let interns = self.interns;
+
+        match self.typ {
+            Func(args, _closure, ret, arg_names) => {
+                // named arguments are printed like `(x : I64, y : Str) -> Bool`
+                let has_names = arg_names.iter().any(Option::is_some);
+                if has_names {
+                    write!(f, "(")?;
+                }
+                for (index, arg) in args.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    if let Some(Some(name)) = arg_names.get(index) {
+                        write!(f, "{} : ", name)?;
+                    }
+                    match arg {
+                        Func(..) => write!(f, "({})", arg.display(interns))?,
+                        _ => write!(f, "{}", arg.display(interns))?,
+                    }
+                }
+                if has_names {
+                    write!(f, ")")?;
+                }
+                write!(f, " -> {}", ret.display(interns))
+            }
+            Apply(symbol, args) | LambdaTag(symbol, args) => fmt_applied(f, interns, *symbol, args),
+            Alias(symbol, args, _, _, _) => fmt_applied(f, interns, *symbol, args),
+            HostExposedAlias {
+                name, arguments, ..
+            } => fmt_applied(f, interns, *name, arguments),
+            Rigid(name) => write!(f, "{}", name),
+            Flex(var) => write!(f, "{}", var_name(*var)),
+            Wildcard => write!(f, "*"),
+            Record { fields, ext } => {
+                if fields.is_empty() {
+                    write!(f, "{{}}")?;
+                } else {
+                    write!(f, "{{ ")?;
+                    for (index, (label, field)) in fields.iter().enumerate() {
+                        if index > 0 {
+                            write!(f, ", ")?;
+                        }
+                        match field {
+                            RecordField::Optional(typ) | RecordField::RigidOptional(typ) => {
+                                write!(f, "{} ? {}", label, typ.display(interns))?
+                            }
+                            RecordField::Required(typ) | RecordField::Demanded(typ) => {
+                                write!(f, "{} : {}", label, typ.display(interns))?
+                            }
+                        }
+                    }
+                    write!(f, " }}")?;
+                }
+                fmt_ext(f, interns, ext)
+            }
+            EmptyRecord => write!(f, "{{}}"),
+            TagUnion(tags, ext) => {
+                fmt_tags(f, interns, tags)?;
+                fmt_ext(f, interns, ext)
+            }
+            FunctionOrTagUnion(tag_name, _, ext) => {
+                write!(f, "[ {} ]", tag_name.0)?;
+                fmt_ext(f, interns, ext)
+            }
+            RecursiveTagUnion(rec_var, tags, ext) => {
+                // Inside the tags, the recursion variable is printed by name like any other Flex,
+                // so the body is only printed once and the binder ties the knot.
+                fmt_tags(f, interns, tags)?;
+                fmt_ext(f, interns, ext)?;
+                write!(f, " as {}", var_name(*rec_var))
+            }
+            EmptyTagUnion => write!(f, "[]"),
+            Erroneous(_) | Error => write!(f, "<type error>"),
+        }
+    }
+}
+
+impl SolvedType {
+    /// A JSON representation of this type for tools outside of the compiler, like editor plugins.
+    /// Every node is an object with a `kind`, e.g. `{"kind":"func","args":[...],"ret":...}`.
+    /// Symbols are fully qualified, like `Str.Str`. Inside a recursive tag union, its recursion
+    /// variable is a `{"kind":"recursion","var":...}` reference instead of a flex var.
+    pub fn to_json(&self, interns: &Interns) -> Value {
+        to_json_help(self, interns, &mut Vec::new())
+    }
+}
+
+fn to_json_help(typ: &SolvedType, interns: &Interns, rec_vars: &mut Vec<VarId>) -> Value {
+    use SolvedType::*;
+
+    match typ {
+        Func(args, _closure, ret, arg_names) => json!({
+            "kind": "func",
+            "args": all_to_json(args, interns, rec_vars),
+            "argNames": arg_names
+                .iter()
+                .map(|name| name.as_ref().map(Lowercase::as_str))
+                .collect::<Vec<_>>(),
+            "ret": to_json_help(ret, interns, rec_vars),
+        }),
+        Apply(symbol, args) => json!({
+            "kind": "apply",
+            "name": symbol_to_json(*symbol, interns),
+            "args": all_to_json(args, interns, rec_vars),
+        }),
+        Rigid(name) => json!({ "kind": "rigid", "name": name.as_str() }),
+        Flex(var) if rec_vars.contains(var) => {
+            json!({ "kind": "recursion", "var": var_name(*var) })
+        }
+        Flex(var) => json!({ "kind": "flex", "var": var_name(*var) }),
+        Wildcard => json!({ "kind": "wildcard" }),
+        Record { fields, ext } => {
+            let fields: Vec<Value> = fields
+                .iter()
+                .map(|(label, field)| {
+                    let (typ, optional) = match field {
+                        RecordField::Optional(typ) | RecordField::RigidOptional(typ) => (typ, true),
+                        RecordField::Required(typ) | RecordField::Demanded(typ) => (typ, false),
+                    };
+
+                    json!({
+                        "name": label.as_str(),
+                        "type": to_json_help(typ, interns, rec_vars),
+                        "optional": optional,
+                    })
+                })
+                .collect();
+
+            json!({
+                "kind": "record",
+                "fields": fields,
+                "ext": ext_to_json(ext, interns, rec_vars),
+            })
+        }
+        EmptyRecord => json!({ "kind": "record", "fields": [], "ext": null }),
+        TagUnion(tags, ext) => json!({
+            "kind": "tagUnion",
+            "tags": tags_to_json(tags, interns, rec_vars),
+            "ext": ext_to_json(ext, interns, rec_vars),
+        }),
+        FunctionOrTagUnion(tag_name, _, ext) => json!({
+            "kind": "tagUnion",
+            "tags": [{ "name": tag_name.0.as_str(), "args": [] }],
+            "ext": ext_to_json(ext, interns, rec_vars),
+        }),
+        RecursiveTagUnion(rec_var, tags, ext) => {
+            rec_vars.push(*rec_var);
+            let tags = tags_to_json(tags, interns, rec_vars);
+            rec_vars.pop();
+
+            json!({
+                "kind": "recursiveTagUnion",
+                "recVar": var_name(*rec_var),
+                "tags": tags,
+                "ext": ext_to_json(ext, interns, rec_vars),
+            })
+        }
+        EmptyTagUnion => json!({ "kind": "tagUnion", "tags": [], "ext": null }),
+        LambdaTag(symbol, args) => json!({
+            "kind": "lambdaTag",
+            "name": symbol_to_json(*symbol, interns),
+            "args": all_to_json(args, interns, rec_vars),
+        }),
+        Alias(symbol, args, _, actual, _) => json!({
+            "kind": "alias",
+            "name": symbol_to_json(*symbol, interns),
+            "args": all_to_json(args, interns, rec_vars),
+            "actual": to_json_help(actual, interns, rec_vars),
+        }),
+        HostExposedAlias {
+            name,
+            arguments,
+            actual,
+            ..
+        } => json!({
+            "kind": "alias",
+            "name": symbol_to_json(*name, interns),
+            "args": all_to_json(arguments, interns, rec_vars),
+            "actual": to_json_help(actual, interns, rec_vars),
+        }),
+        Erroneous(_) | Error => json!({ "kind": "error" }),
+    }
+}
+
+fn all_to_json(types: &[SolvedType], interns: &Interns, rec_vars: &mut Vec<VarId>) -> Vec<Value> {
+    types
+        .iter()
+        .map(|typ| to_json_help(typ, interns, rec_vars))
+        .collect()
+}
+
+fn tags_to_json(
+    tags: &[(TagName, Vec<SolvedType>)],
+    interns: &Interns,
+    rec_vars: &mut Vec<VarId>,
+) -> Vec<Value> {
+    tags.iter()
+        .map(|(tag_name, args)| {
+            json!({
+                "name": tag_name.0.as_str(),
+                "args": all_to_json(args, interns, rec_vars),
+            })
+        })
+        .collect()
+}
+
+/// Closed records and tag unions have a null extension
+fn ext_to_json(ext: &SolvedType, interns: &Interns, rec_vars: &mut Vec<VarId>) -> Value {
+    match ext {
+        SolvedType::EmptyRecord | SolvedType::EmptyTagUnion => Value::Null,
+        _ => to_json_help(ext, interns, rec_vars),
+    }
+}
+
+fn symbol_to_json(symbol: Symbol, interns: &Interns) -> Value {
+    Value::String(format!(
+        "{}.{}",
+        symbol.module_string(interns).as_str(),
+        symbol.as_str(interns)
+    ))
+}
+
+fn hash_tag_names<H: Hasher>(tags: &[(TagName, Vec<SolvedType>)], state: &mut H) {
+    for (tag_name, args) in tags {
+        tag_name.hash(state);
+        args.len().hash(state);
+    }
+}
+
+/// A generated name for a type variable: a, b, ..., z, a1, b1, ...
+fn var_name(var: VarId) -> String {
+    let n = var.to_u32();
+    let letter = (b'a' + (n % 26) as u8) as char;
+
+    match n / 26 {
+        0 => letter.to_string(),
+        suffix => format!("{}{}", letter, suffix),
+    }
+}
+
+fn fmt_applied(
+    f: &mut fmt::Formatter,
+    interns: &Interns,
+    symbol: Symbol,
+    args: &[SolvedType],
+) -> fmt::Result {
+    write!(f, "{}", symbol.as_str(interns))?;
+    for arg in args {
+        write!(f, " ")?;
+        fmt_arg(f, interns, arg)?;
+    }
+    Ok(())
+}
+
+/// Arguments of an applied type or a tag need parentheses if they have spaces in them
+fn fmt_arg(f: &mut fmt::Formatter, interns: &Interns, arg: &SolvedType) -> fmt::Result {
+    use SolvedType::*;
+
+    let needs_parens = match arg {
+        Func(..) | RecursiveTagUnion(..) => true,
+        Apply(_, args) | LambdaTag(_, args) | Alias(_, args, _, _, _) => !args.is_empty(),
+        HostExposedAlias { arguments, .. } => !arguments.is_empty(),
+        _ => false,
+    };
+
+    if needs_parens {
+        write!(f, "({})", arg.display(interns))
+    } else {
+        write!(f, "{}", arg.display(interns))
+    }
+}
+
+fn fmt_tags(
+    f: &mut fmt::Formatter,
+    interns: &Interns,
+    tags: &[(TagName, Vec<SolvedType>)],
+) -> fmt::Result {
+    if tags.is_empty() {
+        return write!(f, "[]");
+    }
+
+    write!(f, "[ ")?;
+    for (index, (tag_name, args)) in tags.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", tag_name.0)?;
+        for arg in args {
+            write!(f, " ")?;
+            fmt_arg(f, interns, arg)?;
+        }
+    }
+    write!(f, " ]")
+}
+
+/// Closed records and tag unions have no extension, open ones print it right after the brace
+fn fmt_ext(f: &mut fmt::Formatter, interns: &Interns, ext: &SolvedType) -> fmt::Result {
+    match ext {
+        SolvedType::EmptyRecord | SolvedType::EmptyTagUnion => Ok(()),
+        _ => fmt_arg(f, interns, ext),
+    }
+}
+
+/// A traversal of a SolvedType, so that each new analysis doesn't need its own recursion.
+///
+/// `visit` is called on every node. By default it calls the method for that kind of node,
+/// which by default visits the node's children. Override only the methods you need,
+/// and call the matching `walk_*` function to keep going into the children.
+pub trait SolvedTypeVisitor: Sized {
+    fn visit(&mut self, typ: &SolvedType) {
+        walk_solved_type(self, typ);
+    }
+
+    fn visit_func(&mut self, args: &[SolvedType], closure: &SolvedType, ret: &SolvedType) {
+        walk_all(self, args);
+        self.visit(closure);
+        self.visit(ret);
+    }
+
+    fn visit_apply(&mut self, _symbol: Symbol, args: &[SolvedType]) {
+        walk_all(self, args);
+    }
+
+    fn visit_rigid(&mut self, _name: &Lowercase) {}
+
+    fn visit_flex(&mut self, _var: VarId) {}
+
+    fn visit_record(&mut self, fields: &[(Lowercase, RecordField<SolvedType>)], ext: &SolvedType) {
+        walk_record(self, fields, ext);
+    }
+
+    fn visit_tag_union(&mut self, tags: &[(TagName, Vec<SolvedType>)], ext: &SolvedType) {
+        walk_tags(self, tags, ext);
+    }
+
+    fn visit_lambda_tag(&mut self, _symbol: Symbol, args: &[SolvedType]) {
+        walk_all(self, args);
+    }
+
+    fn visit_function_or_tag_union(
+        &mut self,
+        _tag_name: &TagName,
+        _symbol: Symbol,
+        ext: &SolvedType,
+    ) {
+        self.visit(ext);
+    }
+
+    /// The recursion variable is not visited as a Flex, only where it is used inside the tags
+    fn visit_recursive_tag_union(
+        &mut self,
+        _rec_var: VarId,
+        tags: &[(TagName, Vec<SolvedType>)],
+        ext: &SolvedType,
+    ) {
+        walk_tags(self, tags, ext);
+    }
+
+    fn visit_alias(
+        &mut self,
+        _symbol: Symbol,
+        args: &[SolvedType],
+        lambda_sets: &[SolvedLambdaSet],
+        actual: &SolvedType,
+        _kind: AliasKind,
+    ) {
+        walk_alias(self, args, lambda_sets, actual);
+    }
+
+    /// `actual_var` is not visited as a Flex
+    fn visit_host_exposed_alias(
+        &mut self,
+        _name: Symbol,
+        arguments: &[SolvedType],
+        lambda_set_variables: &[SolvedLambdaSet],
+        _actual_var: VarId,
+        actual: &SolvedType,
+    ) {
+        walk_alias(self, arguments, lambda_set_variables, actual);
+    }
+
+    /// Wildcard, EmptyRecord, EmptyTagUnion, Erroneous and Error have no children
+    fn visit_leaf(&mut self, _typ: &SolvedType) {}
+}
+
+/// Call the SolvedTypeVisitor method for this kind of node
+pub fn walk_solved_type<V: SolvedTypeVisitor>(visitor: &mut V, typ: &SolvedType) {
+    use SolvedType::*;
+
+    match typ {
+        Func(args, closure, ret, _) => visitor.visit_func(args, closure, ret),
+        Apply(symbol, args) => visitor.visit_apply(*symbol, args),
+        Rigid(name) => visitor.visit_rigid(name),
+        Flex(var) => visitor.visit_flex(*var),
+        Record { fields, ext } => visitor.visit_record(fields, ext),
+        TagUnion(tags, ext) => visitor.visit_tag_union(tags, ext),
+        LambdaTag(symbol, args) => visitor.visit_lambda_tag(*symbol, args),
+        FunctionOrTagUnion(tag_name, symbol, ext) => {
+            visitor.visit_function_or_tag_union(tag_name, *symbol, ext)
+        }
+        RecursiveTagUnion(rec_var, tags, ext) => {
+            visitor.visit_recursive_tag_union(*rec_var, tags, ext)
+        }
+        Alias(symbol, args, lambda_sets, actual, kind) => {
+            visitor.visit_alias(*symbol, args, lambda_sets, actual, *kind)
+        }
+        HostExposedAlias {
+            name,
+            arguments,
+            lambda_set_variables,
+            actual_var,
+            actual,
+        } => visitor.visit_host_exposed_alias(
+            *name,
+            arguments,
+            lambda_set_variables,
+            *actual_var,
+            actual,
+        ),
+        Wildcard | EmptyRecord | EmptyTagUnion | Erroneous(_) | Error => visitor.visit_leaf(typ),
+    }
+}
+
+pub fn walk_all<V: SolvedTypeVisitor>(visitor: &mut V, types: &[SolvedType]) {
+    for typ in types {
+        visitor.visit(typ);
+    }
+}
+
+pub fn walk_record<V: SolvedTypeVisitor>(
+    visitor: &mut V,
+    fields: &[(Lowercase, RecordField<SolvedType>)],
+    ext: &SolvedType,
+) {
+    for (_, field) in fields {
+        visitor.visit(field.as_inner());
+    }
+    visitor.visit(ext);
+}
+
+pub fn walk_tags<V: SolvedTypeVisitor>(
+    visitor: &mut V,
+    tags: &[(TagName, Vec<SolvedType>)],
+    ext: &SolvedType,
+) {
+    for (_, args) in tags {
+        walk_all(visitor, args);
+    }
+    visitor.visit(ext);
+}
+
+pub fn walk_alias<V: SolvedTypeVisitor>(
+    visitor: &mut V,
+    args: &[SolvedType],
+    lambda_sets: &[SolvedLambdaSet],
+    actual: &SolvedType,
+) {
+    walk_all(visitor, args);
+    for SolvedLambdaSet(lambda_set) in lambda_sets {
+        visitor.visit(lambda_set);
+    }
+    visitor.visit(actual);
+}
+
+fn canonicalize_all(types: Vec<SolvedType>) -> Vec<SolvedType> {
+    types.into_iter().map(SolvedType::canonicalize).collect()
+}
+
+fn canonicalize_lambda_sets(lambda_sets: Vec<SolvedLambdaSet>) -> Vec<SolvedLambdaSet> {
+    lambda_sets
+        .into_iter()
+        .map(|SolvedLambdaSet(lambda_set)| SolvedLambdaSet(lambda_set.canonicalize()))
+        .collect()
+}
+
+fn canonicalize_field(field: RecordField<SolvedType>) -> RecordField<SolvedType> {
+    use RecordField::*;
+
+    match field {
+        Optional(typ) => Optional(typ.canonicalize()),
+        Required(typ) => Required(typ.canonicalize()),
+        Demanded(typ) => Demanded(typ.canonicalize()),
+        RigidOptional(typ) => RigidOptional(typ.canonicalize()),
+    }
+}
+
+fn canonicalize_tags(tags: Vec<(TagName, Vec<SolvedType>)>) -> Vec<(TagName, Vec<SolvedType>)> {
+    let mut tags: Vec<(TagName, Vec<SolvedType>)> = tags
+        .into_iter()
+        .map(|(tag_name, args)| (tag_name, canonicalize_all(args)))
+        .collect();
+
+    sort_by_name(&mut tags);
+
+    tags
+}
+
 #[derive(Clone, Debug)]
 pub struct BuiltinAlias {
     pub region: Region,
@@ -839,3 +1754,456 @@ pub fn str_utf8_byte_problem_alias_content() -> SolvedType {
         Box::new(SolvedType::EmptyTagUnion),
     )
 }
+
+#[cfg(test)]
+mod test_solved_type {
+    use super::{
+        aliases, flex, num_type, signed64_type, walk_solved_type, ArgNames, SolvedType,
+        SolvedTypeVisitor,
+    };
+    use roc_module::ident::{Lowercase, TagName};
+    use roc_module::symbol::{IdentIds, Interns, ModuleIds, Symbol};
+    use roc_types::subs::VarId;
+    use roc_types::types::RecordField;
+    use serde_json::json;
+
+    fn record(fields: Vec<(&str, SolvedType)>) -> SolvedType {
+        SolvedType::Record {
+            fields: fields
+                .into_iter()
+                .map(|(label, typ)| (Lowercase::from(label), RecordField::Required(typ)))
+                .collect(),
+            ext: Box::new(SolvedType::EmptyRecord),
+        }
+    }
+
+    fn tag_union(tags: Vec<(&str, Vec<SolvedType>)>) -> SolvedType {
+        SolvedType::TagUnion(
+            tags.into_iter()
+                .map(|(tag_name, args)| (TagName(tag_name.into()), args))
+                .collect(),
+            Box::new(SolvedType::EmptyTagUnion),
+        )
+    }
+
+    fn str_type() -> SolvedType {
+        SolvedType::Apply(Symbol::STR_STR, Vec::new())
+    }
+
+    // [Cons a rec, Nil] as rec
+    fn linked_list(elem: SolvedType, rec_var: VarId) -> SolvedType {
+        SolvedType::RecursiveTagUnion(
+            rec_var,
+            vec![
+                (TagName("Cons".into()), vec![elem, flex(rec_var)]),
+                (TagName("Nil".into()), vec![]),
+            ],
+            Box::new(SolvedType::EmptyTagUnion),
+        )
+    }
+
+    #[test]
+    fn occurs() {
+        let var1 = VarId::from_u32(1);
+        let var2 = VarId::from_u32(2);
+        let typ = record(vec![
+            ("a", str_type()),
+            ("b", linked_list(flex(var1), var2)),
+        ]);
+
+        assert!(typ.occurs(var1));
+        assert!(typ.occurs(var2));
+        assert!(!typ.occurs(VarId::from_u32(3)));
+        assert!(!str_type().occurs(var1));
+    }
+
+    #[test]
+    fn recursive_list_is_not_infinite() {
+        let rec_var = VarId::from_u32(1);
+        let list = linked_list(str_type(), rec_var);
+
+        assert!(!list.has_infinite_type());
+
+        let func = SolvedType::func(vec![list.clone()], SolvedType::EmptyTagUnion, list);
+
+        assert!(!func.has_infinite_type());
+    }
+
+    #[test]
+    fn self_reference_is_infinite() {
+        let var = VarId::from_u32(1);
+
+        // the variable of the alias appears in its own actual type
+        let cyclic_alias = SolvedType::HostExposedAlias {
+            name: Symbol::STR_STR,
+            arguments: vec![],
+            lambda_set_variables: vec![],
+            actual_var: var,
+            actual: Box::new(record(vec![("self", flex(var))])),
+        };
+
+        assert!(cyclic_alias.has_infinite_type());
+
+        // the recursion variable appears outside of the tags of its union
+        let escaping_rec_var = SolvedType::RecursiveTagUnion(
+            var,
+            vec![(TagName("Nil".into()), vec![])],
+            Box::new(flex(var)),
+        );
+
+        assert!(escaping_rec_var.has_infinite_type());
+
+        // nested inside a well formed type
+        let nested = record(vec![("a", str_type()), ("b", cyclic_alias)]);
+
+        assert!(nested.has_infinite_type());
+    }
+
+    #[test]
+    fn canonicalize_record_field_order() {
+        let record1 = record(vec![
+            ("name", str_type()),
+            ("age", flex(VarId::from_u32(1))),
+        ]);
+        let record2 = record(vec![
+            ("age", flex(VarId::from_u32(1))),
+            ("name", str_type()),
+        ]);
+
+        assert_ne!(record1, record2);
+        assert_eq!(record1.canonicalize(), record2.canonicalize());
+    }
+
+    #[test]
+    fn canonical_order_is_string_order() {
+        let labels = ["zeta", "alpha", "a_b", "aB", "beta"];
+        let typ = record(labels.iter().map(|label| (*label, str_type())).collect());
+
+        let mut expected: Vec<&str> = labels.to_vec();
+        expected.sort_unstable();
+
+        match typ.canonicalize() {
+            SolvedType::Record { fields, .. } => {
+                let actual: Vec<&str> = fields.iter().map(|(label, _)| label.as_str()).collect();
+                assert_eq!(actual, expected);
+            }
+            other => panic!("expected a record, got {:?}", other),
+        }
+
+        let tag_names = ["Ok", "Err", "Nil", "Cons"];
+        let typ = tag_union(tag_names.iter().map(|name| (*name, vec![])).collect());
+
+        let mut expected: Vec<&str> = tag_names.to_vec();
+        expected.sort_unstable();
+
+        match typ.canonicalize() {
+            SolvedType::TagUnion(tags, _) => {
+                let actual: Vec<&str> = tags.iter().map(|(name, _)| name.0.as_str()).collect();
+                assert_eq!(actual, expected);
+            }
+            other => panic!("expected a tag union, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn canonicalize_nested() {
+        let typ1 = SolvedType::func(
+            vec![tag_union(vec![
+                (
+                    "Ok",
+                    vec![record(vec![("b", str_type()), ("a", str_type())])],
+                ),
+                ("Err", vec![]),
+            ])],
+            SolvedType::EmptyTagUnion,
+            str_type(),
+        );
+        let typ2 = SolvedType::func(
+            vec![tag_union(vec![
+                ("Err", vec![]),
+                (
+                    "Ok",
+                    vec![record(vec![("a", str_type()), ("b", str_type())])],
+                ),
+            ])],
+            SolvedType::EmptyTagUnion,
+            str_type(),
+        );
+
+        assert_ne!(typ1, typ2);
+        assert_eq!(typ1.clone().canonicalize(), typ2.canonicalize());
+        // canonicalizing twice changes nothing
+        assert_eq!(
+            typ1.clone().canonicalize(),
+            typ1.canonicalize().canonicalize()
+        );
+    }
+
+    #[test]
+    fn count_nodes_with_visitor() {
+        struct CountNodes(usize);
+
+        impl SolvedTypeVisitor for CountNodes {
+            fn visit(&mut self, typ: &SolvedType) {
+                self.0 += 1;
+                walk_solved_type(self, typ);
+            }
+        }
+
+        let mut counter = CountNodes(0);
+        str_type().walk(&mut counter);
+        assert_eq!(counter.0, 1);
+
+        // record, Str, union, Flex, Flex, empty union, empty record
+        let typ = record(vec![
+            ("a", str_type()),
+            (
+                "b",
+                linked_list(flex(VarId::from_u32(1)), VarId::from_u32(2)),
+            ),
+        ]);
+        let mut counter = CountNodes(0);
+        typ.walk(&mut counter);
+        assert_eq!(counter.0, 7);
+    }
+
+    #[test]
+    fn display_recursive_tag_union() {
+        let elem = SolvedType::Rigid("a".into());
+        let list = linked_list(elem, VarId::from_u32(1));
+
+        let interns = builtin_interns();
+        assert_eq!(list.display(&interns).to_string(), "[ Cons a b, Nil ] as b");
+
+        // A recursive union nested in another type is parenthesized, and still printed only once
+        let nested = tag_union(vec![("Wrap", vec![list])]);
+        assert_eq!(
+            nested.display(&interns).to_string(),
+            "[ Wrap ([ Cons a b, Nil ] as b) ]"
+        );
+    }
+
+    #[test]
+    fn display_applied_type_names() {
+        let interns = builtin_interns();
+        let list_of_str = SolvedType::Apply(Symbol::LIST_LIST, vec![str_type()]);
+        assert_eq!(list_of_str.display(&interns).to_string(), "List Str");
+
+        let nested = SolvedType::Apply(Symbol::LIST_LIST, vec![list_of_str]);
+        assert_eq!(nested.display(&interns).to_string(), "List (List Str)");
+    }
+
+    #[test]
+    fn display_error() {
+        let interns = builtin_interns();
+        assert_eq!(
+            SolvedType::Error.display(&interns).to_string(),
+            "<type error>"
+        );
+
+        let list_of_error = SolvedType::Apply(Symbol::LIST_LIST, vec![SolvedType::Error]);
+        assert_eq!(
+            list_of_error.display(&interns).to_string(),
+            "List <type error>"
+        );
+
+        let func = SolvedType::func(
+            vec![list_of_error],
+            SolvedType::EmptyTagUnion,
+            SolvedType::Error,
+        );
+        assert_eq!(
+            func.display(&interns).to_string(),
+            "List <type error> -> <type error>"
+        );
+    }
+
+    #[test]
+    fn display_named_arguments() {
+        let bool_type = SolvedType::Apply(Symbol::BOOL_BOOL, Vec::new());
+        let func = SolvedType::Func(
+            vec![SolvedType::Rigid("a".into()), str_type()],
+            Box::new(SolvedType::EmptyTagUnion),
+            Box::new(bool_type.clone()),
+            ArgNames(vec![Some("x".into()), Some("y".into())]),
+        );
+
+        let interns = builtin_interns();
+        assert_eq!(
+            func.display(&interns).to_string(),
+            "(x : a, y : Str) -> Bool"
+        );
+
+        // canonicalizing drops the names, and the type prints as before
+        let unnamed = SolvedType::func(
+            vec![SolvedType::Rigid("a".into()), str_type()],
+            SolvedType::EmptyTagUnion,
+            bool_type,
+        );
+
+        assert_eq!(unnamed.display(&interns).to_string(), "a, Str -> Bool");
+        // the names are not part of the type
+        assert_eq!(func, unnamed);
+        assert!(func.fast_eq(&unnamed));
+        assert_eq!(func.shallow_hash(), unnamed.shallow_hash());
+        // ArgNames always compares equal, so check the names themselves
+        assert_eq!(arg_names(&func), &[Some("x".into()), Some("y".into())]);
+        assert_eq!(arg_names(&unnamed), &[None, None]);
+
+        let canonical = func.canonicalize();
+        assert_eq!(arg_names(&canonical), &[Some("x".into()), Some("y".into())]);
+        assert_eq!(canonical.display(&interns).to_string(), "a, Str -> Bool");
+    }
+
+    fn arg_names(typ: &SolvedType) -> &[Option<Lowercase>] {
+        match typ {
+            SolvedType::Func(_, _, _, arg_names) => arg_names,
+            other => panic!("expected a function type, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn shallow_hash_short_circuits() {
+        let list = linked_list(str_type(), VarId::from_u32(1));
+        let person = record(vec![("name", str_type())]);
+
+        // Equal types must never be told apart by their shallow hash
+        assert_eq!(list.shallow_hash(), list.clone().shallow_hash());
+        assert!(list.fast_eq(&list.clone()));
+
+        // Obviously different types are rejected by the hash alone
+        assert_ne!(list.shallow_hash(), person.shallow_hash());
+        assert!(!list.fast_eq(&person));
+
+        // Types that only differ deep inside need the full comparison
+        let other_list = linked_list(flex(VarId::from_u32(2)), VarId::from_u32(1));
+        assert_eq!(list.shallow_hash(), other_list.shallow_hash());
+        assert!(!list.fast_eq(&other_list));
+    }
+
+    fn builtin_interns() -> Interns {
+        Interns {
+            module_ids: ModuleIds::default(),
+            all_ident_ids: IdentIds::exposed_builtins(0),
+        }
+    }
+
+    #[test]
+    fn function_to_json() {
+        let func = SolvedType::Func(
+            vec![SolvedType::Rigid("a".into()), str_type()],
+            Box::new(SolvedType::EmptyTagUnion),
+            Box::new(SolvedType::Apply(Symbol::BOOL_BOOL, Vec::new())),
+            ArgNames(vec![Some("x".into()), None]),
+        );
+
+        assert_eq!(
+            func.to_json(&builtin_interns()),
+            json!({
+                "kind": "func",
+                "args": [
+                    { "kind": "rigid", "name": "a" },
+                    { "kind": "apply", "name": "Str.Str", "args": [] },
+                ],
+                "argNames": ["x", null],
+                "ret": { "kind": "apply", "name": "Bool.Bool", "args": [] },
+            })
+        );
+    }
+
+    #[test]
+    fn record_to_json() {
+        let person = SolvedType::Record {
+            fields: vec![
+                ("name".into(), RecordField::Required(str_type())),
+                (
+                    "nickname".into(),
+                    RecordField::Optional(flex(VarId::from_u32(0))),
+                ),
+            ],
+            ext: Box::new(SolvedType::EmptyRecord),
+        };
+
+        assert_eq!(
+            person.to_json(&builtin_interns()),
+            json!({
+                "kind": "record",
+                "fields": [
+                    {
+                        "name": "name",
+                        "type": { "kind": "apply", "name": "Str.Str", "args": [] },
+                        "optional": false,
+                    },
+                    {
+                        "name": "nickname",
+                        "type": { "kind": "flex", "var": "a" },
+                        "optional": true,
+                    },
+                ],
+                "ext": null,
+            })
+        );
+    }
+
+    #[test]
+    fn recursive_tag_union_to_json() {
+        let list = linked_list(str_type(), VarId::from_u32(1));
+
+        assert_eq!(
+            list.to_json(&builtin_interns()),
+            json!({
+                "kind": "recursiveTagUnion",
+                "recVar": "b",
+                "tags": [
+                    {
+                        "name": "Cons",
+                        "args": [
+                            { "kind": "apply", "name": "Str.Str", "args": [] },
+                            { "kind": "recursion", "var": "b" },
+                        ],
+                    },
+                    { "name": "Nil", "args": [] },
+                ],
+                "ext": null,
+            })
+        );
+    }
+
+    #[test]
+    fn collapsed_aliases_are_not_expanded() {
+        // Num Signed64, with both aliases fully expanded
+        let typ = SolvedType::func(
+            vec![num_type(signed64_type())],
+            SolvedType::EmptyTagUnion,
+            str_type(),
+        );
+
+        let signed64 = SolvedType::Apply(Symbol::NUM_SIGNED64, vec![]);
+        let collapsed_num = SolvedType::Apply(Symbol::NUM_NUM, vec![signed64.clone()]);
+
+        assert_eq!(
+            typ.collapse_aliases(),
+            SolvedType::func(
+                vec![collapsed_num.clone()],
+                SolvedType::EmptyTagUnion,
+                str_type()
+            )
+        );
+
+        // expanding materializes one level, the argument stays collapsed
+        let aliases = aliases();
+        let expanded_num = collapsed_num.expand_alias(&aliases).unwrap();
+
+        assert_eq!(expanded_num, num_type(signed64.clone()));
+        match signed64.expand_alias(&aliases) {
+            Some(SolvedType::Alias(Symbol::NUM_SIGNED64, args, _, actual, _)) => {
+                assert!(args.is_empty());
+                assert_eq!(*actual, SolvedType::EmptyTagUnion);
+            }
+            other => panic!("expected the Signed64 alias, got {:?}", other),
+        }
+
+        // Str is not an alias
+        assert_eq!(str_type().expand_alias(&aliases), None);
+    }
+}
@@ -0,0 +1,775 @@
+//! On-disk format for the solved types that a module exposes, so they can be cached.
+//!
+//! The file starts with a header: the magic bytes, a format version and the ModuleId.
+//! It is followed by a table of the exposed Symbols and their SolvedTypes.
+//! Numbers are written in native endianness, like Subs::serialize, so these files
+//! should only be read on the machine that wrote them.
+
+use crate::builtin_aliases::{ArgNames, SolvedLambdaSet, SolvedType};
+use roc_module::ident::{Lowercase, TagName};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_types::subs::VarId;
+use roc_types::types::{AliasKind, RecordField};
+use snafu::{ResultExt, Snafu};
+use std::io::{Read, Write};
+
+pub const INTERFACE_MAGIC: [u8; 4] = *b"ROCI";
+
+/// Increment this whenever the encoding of SolvedType changes
+pub const INTERFACE_VERSION: u8 = 2;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum InterfaceError {
+    #[snafu(display("IoError: {}", source))]
+    Io { source: std::io::Error },
+
+    #[snafu(display(
+        "BadMagic: this is not a Roc interface file, it starts with {:?}.",
+        found
+    ))]
+    BadMagic { found: [u8; 4] },
+
+    #[snafu(display(
+        "VersionMismatch: I can only read interface format version {}, but this file has version {}.",
+        expected,
+        found
+    ))]
+    VersionMismatch { expected: u8, found: u8 },
+
+    #[snafu(display("Malformed: {}", msg))]
+    Malformed { msg: String },
+
+    #[snafu(display("Unsupported: {}", msg))]
+    Unsupported { msg: String },
+}
+
+pub type InterfaceResult<T, E = InterfaceError> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceFile {
+    pub module_id: ModuleId,
+    pub exports: Vec<(Symbol, SolvedType)>,
+}
+
+impl InterfaceFile {
+    /// Whether another version of this interface exposes the same symbols with the same types,
+    /// e.g. to decide if dependent modules need to be checked again.
+    /// The order of record fields and tags doesn't matter.
+    pub fn same_exports(&self, other: &InterfaceFile) -> bool {
+        self.module_id == other.module_id
+            && self.exports.len() == other.exports.len()
+            && self
+                .exports
+                .iter()
+                .zip(other.exports.iter())
+                .all(|((sym1, typ1), (sym2, typ2))| {
+                    sym1 == sym2
+                        && typ1
+                            .clone()
+                            .canonicalize()
+                            .fast_eq(&typ2.clone().canonicalize())
+                })
+    }
+}
+
+/// The exports are written in their canonical form,
+/// so the same exports always give the same bytes, whatever the order of their fields and tags.
+pub fn write_interface(
+    module_id: ModuleId,
+    exports: &[(Symbol, SolvedType)],
+    w: &mut impl Write,
+) -> InterfaceResult<()> {
+    w.write_all(&INTERFACE_MAGIC).context(IoSnafu)?;
+    w.write_all(&[INTERFACE_VERSION]).context(IoSnafu)?;
+    write_module_id(module_id, w)?;
+
+    write_len(exports.len(), w)?;
+    for (symbol, solved_type) in exports {
+        write_symbol(*symbol, w)?;
+        write_solved_type(&solved_type.clone().canonicalize(), w)?;
+    }
+
+    Ok(())
+}
+
+pub fn read_interface(r: &mut impl Read) -> InterfaceResult<InterfaceFile> {
+    // read the whole file up front, so lengths can be checked against the bytes that are left
+    let mut bytes = Vec::new();
+    r.read_to_end(&mut bytes).context(IoSnafu)?;
+    let r = &mut bytes.as_slice();
+
+    let mut magic = [0; 4];
+    r.read_exact(&mut magic).context(IoSnafu)?;
+    if magic != INTERFACE_MAGIC {
+        return BadMagicSnafu { found: magic }.fail();
+    }
+
+    let version = read_u8(r)?;
+    if version != INTERFACE_VERSION {
+        return VersionMismatchSnafu {
+            expected: INTERFACE_VERSION,
+            found: version,
+        }
+        .fail();
+    }
+
+    let module_id = read_module_id(r)?;
+
+    let len = read_len(r)?;
+    let mut exports = Vec::with_capacity(len);
+    for _ in 0..len {
+        let symbol = read_symbol(r)?;
+        let solved_type = read_solved_type(r)?;
+        exports.push((symbol, solved_type));
+    }
+
+    Ok(InterfaceFile { module_id, exports })
+}
+
+// Tags for the SolvedType variants
+const FUNC: u8 = 0;
+const APPLY: u8 = 1;
+const RIGID: u8 = 2;
+const FLEX: u8 = 3;
+const WILDCARD: u8 = 4;
+const RECORD: u8 = 5;
+const EMPTY_RECORD: u8 = 6;
+const TAG_UNION: u8 = 7;
+const LAMBDA_TAG: u8 = 8;
+const FUNCTION_OR_TAG_UNION: u8 = 9;
+const RECURSIVE_TAG_UNION: u8 = 10;
+const EMPTY_TAG_UNION: u8 = 11;
+const ALIAS: u8 = 12;
+const HOST_EXPOSED_ALIAS: u8 = 13;
+const ERROR: u8 = 14;
+
+fn write_solved_type(solved_type: &SolvedType, w: &mut impl Write) -> InterfaceResult<()> {
+    use SolvedType::*;
+
+    match solved_type {
+        Func(args, closure, ret, arg_names) => {
+            write_u8(FUNC, w)?;
+            write_solved_types(args, w)?;
+            write_solved_type(closure, w)?;
+            write_solved_type(ret, w)?;
+            write_arg_names(arg_names, w)
+        }
+        Apply(symbol, args) => {
+            write_u8(APPLY, w)?;
+            write_symbol(*symbol, w)?;
+            write_solved_types(args, w)
+        }
+        Rigid(name) => {
+            write_u8(RIGID, w)?;
+            write_str(name.as_str(), w)
+        }
+        Flex(var) => {
+            write_u8(FLEX, w)?;
+            write_u32(var.to_u32(), w)
+        }
+        Wildcard => write_u8(WILDCARD, w),
+        Record { fields, ext } => {
+            write_u8(RECORD, w)?;
+            write_len(fields.len(), w)?;
+            for (label, field) in fields {
+                write_str(label.as_str(), w)?;
+                write_record_field(field, w)?;
+            }
+            write_solved_type(ext, w)
+        }
+        EmptyRecord => write_u8(EMPTY_RECORD, w),
+        TagUnion(tags, ext) => {
+            write_u8(TAG_UNION, w)?;
+            write_tags(tags, w)?;
+            write_solved_type(ext, w)
+        }
+        LambdaTag(symbol, args) => {
+            write_u8(LAMBDA_TAG, w)?;
+            write_symbol(*symbol, w)?;
+            write_solved_types(args, w)
+        }
+        FunctionOrTagUnion(TagName(tag_name), symbol, ext) => {
+            write_u8(FUNCTION_OR_TAG_UNION, w)?;
+            write_str(tag_name.as_str(), w)?;
+            write_symbol(*symbol, w)?;
+            write_solved_type(ext, w)
+        }
+        RecursiveTagUnion(rec_var, tags, ext) => {
+            write_u8(RECURSIVE_TAG_UNION, w)?;
+            write_u32(rec_var.to_u32(), w)?;
+            write_tags(tags, w)?;
+            write_solved_type(ext, w)
+        }
+        EmptyTagUnion => write_u8(EMPTY_TAG_UNION, w),
+        Erroneous(problem) => UnsupportedSnafu {
+            msg: format!(
+                "I can't write an erroneous type to an interface file: {:?}",
+                problem
+            ),
+        }
+        .fail(),
+        Alias(symbol, args, lambda_sets, actual, kind) => {
+            write_u8(ALIAS, w)?;
+            write_symbol(*symbol, w)?;
+            write_solved_types(args, w)?;
+            write_lambda_sets(lambda_sets, w)?;
+            write_solved_type(actual, w)?;
+            write_u8(
+                match kind {
+                    AliasKind::Structural => 0,
+                    AliasKind::Opaque => 1,
+                },
+                w,
+            )
+        }
+        HostExposedAlias {
+            name,
+            arguments,
+            lambda_set_variables,
+            actual_var,
+            actual,
+        } => {
+            write_u8(HOST_EXPOSED_ALIAS, w)?;
+            write_symbol(*name, w)?;
+            write_solved_types(arguments, w)?;
+            write_lambda_sets(lambda_set_variables, w)?;
+            write_u32(actual_var.to_u32(), w)?;
+            write_solved_type(actual, w)
+        }
+        Error => write_u8(ERROR, w),
+    }
+}
+
+fn read_solved_type(r: &mut &[u8]) -> InterfaceResult<SolvedType> {
+    use SolvedType::*;
+
+    let solved_type = match read_u8(r)? {
+        FUNC => {
+            let args = read_solved_types(r)?;
+            let closure = read_solved_type(r)?;
+            let ret = read_solved_type(r)?;
+            let arg_names = read_arg_names(args.len(), r)?;
+            Func(args, Box::new(closure), Box::new(ret), ArgNames(arg_names))
+        }
+        APPLY => {
+            let symbol = read_symbol(r)?;
+            Apply(symbol, read_solved_types(r)?)
+        }
+        RIGID => Rigid(Lowercase::from(read_string(r)?.as_str())),
+        FLEX => Flex(VarId::from_u32(read_u32(r)?)),
+        WILDCARD => Wildcard,
+        RECORD => {
+            let len = read_len(r)?;
+            let mut fields = Vec::with_capacity(len);
+            for _ in 0..len {
+                let label = Lowercase::from(read_string(r)?.as_str());
+                fields.push((label, read_record_field(r)?));
+            }
+            let ext = read_solved_type(r)?;
+            Record {
+                fields,
+                ext: Box::new(ext),
+            }
+        }
+        EMPTY_RECORD => EmptyRecord,
+        TAG_UNION => {
+            let tags = read_tags(r)?;
+            TagUnion(tags, Box::new(read_solved_type(r)?))
+        }
+        LAMBDA_TAG => {
+            let symbol = read_symbol(r)?;
+            LambdaTag(symbol, read_solved_types(r)?)
+        }
+        FUNCTION_OR_TAG_UNION => {
+            let tag_name = TagName(read_string(r)?.as_str().into());
+            let symbol = read_symbol(r)?;
+            FunctionOrTagUnion(tag_name, symbol, Box::new(read_solved_type(r)?))
+        }
+        RECURSIVE_TAG_UNION => {
+            let rec_var = VarId::from_u32(read_u32(r)?);
+            let tags = read_tags(r)?;
+            RecursiveTagUnion(rec_var, tags, Box::new(read_solved_type(r)?))
+        }
+        EMPTY_TAG_UNION => EmptyTagUnion,
+        ALIAS => {
+            let symbol = read_symbol(r)?;
+            let args = read_solved_types(r)?;
+            let lambda_sets = read_lambda_sets(r)?;
+            let actual = read_solved_type(r)?;
+            let kind = match read_u8(r)? {
+                0 => AliasKind::Structural,
+                1 => AliasKind::Opaque,
+                other => {
+                    return MalformedSnafu {
+                        msg: format!("{} is not a valid alias kind.", other),
+                    }
+                    .fail()
+                }
+            };
+            Alias(symbol, args, lambda_sets, Box::new(actual), kind)
+        }
+        HOST_EXPOSED_ALIAS => {
+            let name = read_symbol(r)?;
+            let arguments = read_solved_types(r)?;
+            let lambda_set_variables = read_lambda_sets(r)?;
+            let actual_var = VarId::from_u32(read_u32(r)?);
+            let actual = Box::new(read_solved_type(r)?);
+            HostExposedAlias {
+                name,
+                arguments,
+                lambda_set_variables,
+                actual_var,
+                actual,
+            }
+        }
+        ERROR => Error,
+        other => {
+            return MalformedSnafu {
+                msg: format!("{} is not a valid SolvedType tag.", other),
+            }
+            .fail()
+        }
+    };
+
+    Ok(solved_type)
+}
+
+fn write_solved_types(solved_types: &[SolvedType], w: &mut impl Write) -> InterfaceResult<()> {
+    write_len(solved_types.len(), w)?;
+    for solved_type in solved_types {
+        write_solved_type(solved_type, w)?;
+    }
+    Ok(())
+}
+
+fn read_solved_types(r: &mut &[u8]) -> InterfaceResult<Vec<SolvedType>> {
+    let len = read_len(r)?;
+    let mut solved_types = Vec::with_capacity(len);
+    for _ in 0..len {
+        solved_types.push(read_solved_type(r)?);
+    }
+    Ok(solved_types)
+}
+
+/// One entry per argument, so there is no length
+fn write_arg_names(arg_names: &[Option<Lowercase>], w: &mut impl Write) -> InterfaceResult<()> {
+    for arg_name in arg_names {
+        match arg_name {
+            Some(name) => {
+                write_u8(1, w)?;
+                write_str(name.as_str(), w)?;
+            }
+            None => write_u8(0, w)?,
+        }
+    }
+    Ok(())
+}
+
+fn read_arg_names(n_args: usize, r: &mut &[u8]) -> InterfaceResult<Vec<Option<Lowercase>>> {
+    let mut arg_names = Vec::with_capacity(n_args);
+    for _ in 0..n_args {
+        let arg_name = match read_u8(r)? {
+            0 => None,
+            1 => Some(Lowercase::from(read_string(r)?.as_str())),
+            other => {
+                return MalformedSnafu {
+                    msg: format!("{} is not a valid argument name tag.", other),
+                }
+                .fail()
+            }
+        };
+        arg_names.push(arg_name);
+    }
+    Ok(arg_names)
+}
+
+fn write_lambda_sets(lambda_sets: &[SolvedLambdaSet], w: &mut impl Write) -> InterfaceResult<()> {
+    write_len(lambda_sets.len(), w)?;
+    for SolvedLambdaSet(lambda_set) in lambda_sets {
+        write_solved_type(lambda_set, w)?;
+    }
+    Ok(())
+}
+
+fn read_lambda_sets(r: &mut &[u8]) -> InterfaceResult<Vec<SolvedLambdaSet>> {
+    Ok(read_solved_types(r)?
+        .into_iter()
+        .map(SolvedLambdaSet)
+        .collect())
+}
+
+fn write_tags(tags: &[(TagName, Vec<SolvedType>)], w: &mut impl Write) -> InterfaceResult<()> {
+    write_len(tags.len(), w)?;
+    for (TagName(tag_name), args) in tags {
+        write_str(tag_name.as_str(), w)?;
+        write_solved_types(args, w)?;
+    }
+    Ok(())
+}
+
+fn read_tags(r: &mut &[u8]) -> InterfaceResult<Vec<(TagName, Vec<SolvedType>)>> {
+    let len = read_len(r)?;
+    let mut tags = Vec::with_capacity(len);
+    for _ in 0..len {
+        let tag_name = TagName(read_string(r)?.as_str().into());
+        tags.push((tag_name, read_solved_types(r)?));
+    }
+    Ok(tags)
+}
+
+fn write_record_field(field: &RecordField<SolvedType>, w: &mut impl Write) -> InterfaceResult<()> {
+    use RecordField::*;
+
+    let (kind, solved_type) = match field {
+        Demanded(typ) => (0, typ),
+        Required(typ) => (1, typ),
+        Optional(typ) => (2, typ),
+        RigidOptional(typ) => (3, typ),
+    };
+
+    write_u8(kind, w)?;
+    write_solved_type(solved_type, w)
+}
+
+fn read_record_field(r: &mut &[u8]) -> InterfaceResult<RecordField<SolvedType>> {
+    use RecordField::*;
+
+    let kind = read_u8(r)?;
+    let solved_type = read_solved_type(r)?;
+
+    match kind {
+        0 => Ok(Demanded(solved_type)),
+        1 => Ok(Required(solved_type)),
+        2 => Ok(Optional(solved_type)),
+        3 => Ok(RigidOptional(solved_type)),
+        other => MalformedSnafu {
+            msg: format!("{} is not a valid record field kind.", other),
+        }
+        .fail(),
+    }
+}
+
+fn write_symbol(symbol: Symbol, w: &mut impl Write) -> InterfaceResult<()> {
+    w.write_all(&symbol.to_ne_bytes()).context(IoSnafu)
+}
+
+fn read_symbol(r: &mut &[u8]) -> InterfaceResult<Symbol> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes).context(IoSnafu)?;
+
+    // The ModuleId is stored in the last 4 bytes of a Symbol, and can't be zero
+    if bytes[4..] == [0; 4] {
+        return MalformedSnafu {
+            msg: format!("{:?} is not a valid Symbol.", bytes),
+        }
+        .fail();
+    }
+
+    // Safety: Symbol is repr(packed(4)) with an ident_id and a non-zero module_id,
+    // so any 8 bytes with non-zero module_id bytes are a valid Symbol.
+    Ok(unsafe { std::mem::transmute::<[u8; 8], Symbol>(bytes) })
+}
+
+/// ModuleId has no public conversion to or from integers,
+/// so write it as the module_id bytes of a Symbol in that module.
+fn write_module_id(module_id: ModuleId, w: &mut impl Write) -> InterfaceResult<()> {
+    let bytes = Interns::from_index(module_id, 0).to_ne_bytes();
+    w.write_all(&bytes[4..]).context(IoSnafu)
+}
+
+fn read_module_id(r: &mut &[u8]) -> InterfaceResult<ModuleId> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes[4..]).context(IoSnafu)?;
+
+    let symbol = read_symbol(&mut &bytes[..])?;
+    Ok(symbol.module_id())
+}
+
+fn write_str(string: &str, w: &mut impl Write) -> InterfaceResult<()> {
+    write_len(string.len(), w)?;
+    w.write_all(string.as_bytes()).context(IoSnafu)
+}
+
+fn read_string(r: &mut &[u8]) -> InterfaceResult<String> {
+    let len = read_len(r)?;
+    let mut bytes = vec![0; len];
+    r.read_exact(&mut bytes).context(IoSnafu)?;
+
+    String::from_utf8(bytes).map_err(|err| InterfaceError::Malformed {
+        msg: format!("A name is not valid UTF-8: {}", err),
+    })
+}
+
+fn write_len(len: usize, w: &mut impl Write) -> InterfaceResult<()> {
+    write_u32(len as u32, w)
+}
+
+/// Every string byte or list element takes at least one byte,
+/// so a length that is larger than the bytes left can only come from a malformed file.
+fn read_len(r: &mut &[u8]) -> InterfaceResult<usize> {
+    let len = read_u32(r)? as usize;
+    if len > r.len() {
+        return MalformedSnafu {
+            msg: format!(
+                "A length of {} is larger than the {} bytes left.",
+                len,
+                r.len()
+            ),
+        }
+        .fail();
+    }
+    Ok(len)
+}
+
+fn write_u32(value: u32, w: &mut impl Write) -> InterfaceResult<()> {
+    w.write_all(&value.to_ne_bytes()).context(IoSnafu)
+}
+
+fn read_u32(r: &mut &[u8]) -> InterfaceResult<u32> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes).context(IoSnafu)?;
+    Ok(u32::from_ne_bytes(bytes))
+}
+
+fn write_u8(value: u8, w: &mut impl Write) -> InterfaceResult<()> {
+    w.write_all(&[value]).context(IoSnafu)
+}
+
+fn read_u8(r: &mut &[u8]) -> InterfaceResult<u8> {
+    let mut bytes = [0; 1];
+    r.read_exact(&mut bytes).context(IoSnafu)?;
+    Ok(bytes[0])
+}
+
+#[cfg(test)]
+mod test_interface_file {
+    use super::{
+        read_interface, write_interface, InterfaceError, InterfaceFile, INTERFACE_VERSION,
+    };
+    use crate::builtin_aliases::{flex, ArgNames, SolvedLambdaSet, SolvedType};
+    use roc_module::ident::{Lowercase, TagName};
+    use roc_module::symbol::{ModuleId, Symbol};
+    use roc_types::subs::VarId;
+    use roc_types::types::{AliasKind, RecordField};
+
+    fn exports() -> Vec<(Symbol, SolvedType)> {
+        let str_type = SolvedType::Apply(Symbol::STR_STR, Vec::new());
+
+        let record = SolvedType::Record {
+            fields: vec![
+                (
+                    Lowercase::from("name"),
+                    RecordField::Required(str_type.clone()),
+                ),
+                (
+                    Lowercase::from("age"),
+                    RecordField::Optional(flex(VarId::from_u32(1))),
+                ),
+            ],
+            ext: Box::new(SolvedType::EmptyRecord),
+        };
+        let not_found = SolvedType::TagUnion(
+            vec![(TagName("NotFound".into()), vec![])],
+            Box::new(SolvedType::Wildcard),
+        );
+        let result = SolvedType::Alias(
+            Symbol::RESULT_RESULT,
+            vec![record.clone(), not_found],
+            vec![SolvedLambdaSet(flex(VarId::from_u32(2)))],
+            Box::new(SolvedType::Error),
+            AliasKind::Opaque,
+        );
+        let func = SolvedType::Func(
+            vec![str_type, SolvedType::Rigid(Lowercase::from("a"))],
+            Box::new(flex(VarId::from_u32(3))),
+            Box::new(result),
+            ArgNames(vec![Some(Lowercase::from("key")), None]),
+        );
+
+        let rec_var = VarId::from_u32(4);
+        let linked_list = SolvedType::RecursiveTagUnion(
+            rec_var,
+            vec![
+                (TagName("Cons".into()), vec![record, flex(rec_var)]),
+                (TagName("Nil".into()), vec![]),
+            ],
+            Box::new(SolvedType::EmptyTagUnion),
+        );
+
+        vec![(Symbol::STR_CONCAT, func), (Symbol::LIST_LIST, linked_list)]
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut bytes = Vec::new();
+        write_interface(ModuleId::STR, &exports(), &mut bytes).unwrap();
+
+        let interface = read_interface(&mut bytes.as_slice()).unwrap();
+
+        // the exports are written in their canonical form
+        let canonical_exports = exports()
+            .into_iter()
+            .map(|(symbol, typ)| (symbol, typ.canonicalize()))
+            .collect();
+
+        assert_eq!(
+            interface,
+            InterfaceFile {
+                module_id: ModuleId::STR,
+                exports: canonical_exports,
+            }
+        );
+
+        // argument names don't take part in `==`, so check them separately
+        match &interface.exports[0].1 {
+            SolvedType::Func(_, _, _, arg_names) => {
+                assert_eq!(arg_names.0, vec![Some(Lowercase::from("key")), None]);
+            }
+            other => panic!("Expected a function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn inline_alias_round_trip() {
+        use roc_module::symbol::IdentIds;
+
+        // `{ x : I64 } as Point`
+        let point = Symbol::new(ModuleId::STR, IdentIds::default().add_str("Point"));
+        let point_alias = SolvedType::Alias(
+            point,
+            vec![],
+            vec![],
+            Box::new(SolvedType::Record {
+                fields: vec![(
+                    Lowercase::from("x"),
+                    RecordField::Required(SolvedType::Apply(Symbol::NUM_I64, vec![])),
+                )],
+                ext: Box::new(SolvedType::EmptyRecord),
+            }),
+            AliasKind::Structural,
+        );
+        let exports = vec![(Symbol::STR_CONCAT, point_alias)];
+
+        let mut bytes = Vec::new();
+        write_interface(ModuleId::STR, &exports, &mut bytes).unwrap();
+
+        let interface = read_interface(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(interface.exports, exports);
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let mut bytes = Vec::new();
+        write_interface(ModuleId::STR, &exports(), &mut bytes).unwrap();
+
+        // the version comes right after the magic bytes
+        bytes[4] = INTERFACE_VERSION + 1;
+
+        match read_interface(&mut bytes.as_slice()) {
+            Err(InterfaceError::VersionMismatch { expected, found }) => {
+                assert_eq!(expected, INTERFACE_VERSION);
+                assert_eq!(found, INTERFACE_VERSION + 1);
+            }
+            other => panic!("Expected a version mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bad_magic() {
+        let mut bytes = Vec::new();
+        write_interface(ModuleId::STR, &[], &mut bytes).unwrap();
+        bytes[0] = b'X';
+
+        assert!(matches!(
+            read_interface(&mut bytes.as_slice()),
+            Err(InterfaceError::BadMagic { .. })
+        ));
+    }
+
+    #[test]
+    fn truncated() {
+        let mut bytes = Vec::new();
+        write_interface(ModuleId::STR, &exports(), &mut bytes).unwrap();
+        bytes.truncate(bytes.len() - 3);
+
+        assert!(read_interface(&mut bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn oversized_len() {
+        let mut bytes = Vec::new();
+        write_interface(ModuleId::STR, &exports(), &mut bytes).unwrap();
+
+        // the number of exports comes right after the magic bytes, the version and the ModuleId
+        bytes[9..13].copy_from_slice(&u32::MAX.to_ne_bytes());
+
+        assert!(matches!(
+            read_interface(&mut bytes.as_slice()),
+            Err(InterfaceError::Malformed { .. })
+        ));
+    }
+
+    fn reordered_record() -> (SolvedType, SolvedType) {
+        let field = |name: &str| {
+            (
+                Lowercase::from(name),
+                RecordField::Required(SolvedType::EmptyRecord),
+            )
+        };
+        let record = |fields| SolvedType::Record {
+            fields,
+            ext: Box::new(SolvedType::EmptyRecord),
+        };
+
+        (
+            record(vec![field("a"), field("b")]),
+            record(vec![field("b"), field("a")]),
+        )
+    }
+
+    #[test]
+    fn field_order_doesnt_change_bytes() {
+        let (record1, record2) = reordered_record();
+
+        let mut bytes1 = Vec::new();
+        write_interface(ModuleId::STR, &[(Symbol::STR_CONCAT, record1)], &mut bytes1).unwrap();
+        let mut bytes2 = Vec::new();
+        write_interface(ModuleId::STR, &[(Symbol::STR_CONCAT, record2)], &mut bytes2).unwrap();
+
+        assert_eq!(bytes1, bytes2);
+    }
+
+    #[test]
+    fn same_exports() {
+        let interface = InterfaceFile {
+            module_id: ModuleId::STR,
+            exports: exports(),
+        };
+        assert!(interface.same_exports(&interface.clone()));
+
+        let mut changed = interface.clone();
+        changed.exports[0].1 = SolvedType::EmptyRecord;
+        assert!(!interface.same_exports(&changed));
+
+        // renaming an argument doesn't change the exports
+        let mut renamed = interface.clone();
+        if let SolvedType::Func(_, _, _, arg_names) = &mut renamed.exports[0].1 {
+            arg_names.0[0] = Some(Lowercase::from("name"));
+        }
+        assert!(interface.same_exports(&renamed));
+
+        // neither does the order of record fields
+        let (record1, record2) = reordered_record();
+        let interface1 = InterfaceFile {
+            module_id: ModuleId::STR,
+            exports: vec![(Symbol::STR_CONCAT, record1)],
+        };
+        let interface2 = InterfaceFile {
+            module_id: ModuleId::STR,
+            exports: vec![(Symbol::STR_CONCAT, record2)],
+        };
+        assert!(interface1.same_exports(&interface2));
+    }
+}
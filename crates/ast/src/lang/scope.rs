@@ -20,7 +20,7 @@ use roc_problem::can::RuntimeError;
 use roc_region::all::{Loc, Region};
 use roc_types::subs::{VarId, VarStore, Variable};
 
-use super::core::types::{Alias, Type2, TypeId};
+use super::core::types::{Alias, Problem2, Type2, TypeId};
 use super::env::Env;
 
 fn solved_type_to_type_id(
@@ -91,6 +91,9 @@ fn to_type2(
             Type2::Variable(var_id_to_flex_var(*var_id, free_vars, var_store))
         }
         SolvedType::EmptyTagUnion => Type2::EmptyTagUnion,
+        // never a valid type, so solving reports it instead of unifying with it
+        SolvedType::Erroneous(_) => Type2::Erroneous(Problem2::InvalidModule),
+        SolvedType::Error => Type2::Erroneous(Problem2::SolvedTypeError),
         rest => todo!("{:?}", rest),
     }
 }
@@ -348,3 +351,29 @@ impl ShallowClone for Scope {
         }
     }
 }
+
+#[cfg(test)]
+mod test_scope {
+    use roc_types::subs::VarStore;
+
+    use super::to_type2;
+    use crate::builtin_aliases::{FreeVars, SolvedType};
+    use crate::lang::core::types::{Problem2, Type2};
+    use crate::mem_pool::pool::Pool;
+
+    #[test]
+    fn error_becomes_erroneous_type() {
+        let mut pool = Pool::with_capacity(4);
+        let mut free_vars = FreeVars::default();
+        let mut var_store = VarStore::default();
+
+        let typ2 = to_type2(
+            &mut pool,
+            &SolvedType::Error,
+            &mut free_vars,
+            &mut var_store,
+        );
+
+        assert!(matches!(typ2, Type2::Erroneous(Problem2::SolvedTypeError)));
+    }
+}
@@ -1,7 +1,8 @@
 pub mod ast_error;
-mod builtin_aliases;
+pub mod builtin_aliases;
 mod canonicalization;
 pub mod constrain;
+pub mod interface_file;
 pub mod lang;
 pub mod mem_pool;
 pub mod module;
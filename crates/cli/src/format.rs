@@ -4,10 +4,8 @@ use std::path::{Path, PathBuf};
 use crate::FormatMode;
 use bumpalo::Bump;
 use roc_error_macros::{internal_error, user_error};
-use roc_fmt::def::fmt_defs;
-use roc_fmt::module::fmt_module;
 use roc_fmt::spaces::RemoveSpaces;
-use roc_fmt::{Ast, Buf};
+use roc_fmt::{Ast, Formatter};
 use roc_parse::{
     module::{self, module_defs},
     parser::{Parser, SyntaxError},
@@ -58,6 +56,28 @@ fn is_roc_file(path: &Path) -> bool {
     matches!(path.extension().and_then(OsStr::to_str), Some("roc"))
 }
 
+#[derive(Debug)]
+pub enum FmtError {
+    /// The source code didn't parse
+    ParseError(String),
+    /// Formatting bug: the formatted code didn't parse
+    InvalidFormattedCode {
+        formatted: String,
+        parse_error: String,
+    },
+    /// Formatting bug: the formatted code parsed to a different tree
+    TreeChanged {
+        formatted: String,
+        tree_before: String,
+        tree_after: String,
+    },
+    /// Formatting bug: formatting the formatted code changed it again
+    Unstable {
+        formatted: String,
+        formatted_twice: String,
+    },
+}
+
 pub fn format(files: std::vec::Vec<PathBuf>, mode: FormatMode) -> Result<(), String> {
     let files = flatten_directories(files);
 
@@ -66,86 +86,83 @@ pub fn format(files: std::vec::Vec<PathBuf>, mode: FormatMode) -> Result<(), Str
 
         let src = std::fs::read_to_string(&file).unwrap();
 
-        let ast = arena.alloc(parse_all(&arena, &src).unwrap_or_else(|e| {
-            user_error!("Unexpected parse failure when parsing this formatting:\n\n{:?}\n\nParse error was:\n\n{:?}\n\n", src, e)
-        }));
-        let mut buf = Buf::new_in(&arena);
-        fmt_all(&mut buf, ast);
-
-        let reparsed_ast = arena.alloc(parse_all(&arena, buf.as_str()).unwrap_or_else(|e| {
-            let mut fail_file = file.clone();
-            fail_file.set_extension("roc-format-failed");
-            std::fs::write(&fail_file, buf.as_str()).unwrap();
-            internal_error!(
-                "Formatting bug; formatted code isn't valid\n\n\
-                I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
-                Parse error was: {:?}\n\n",
-                fail_file.display(),
-                e
-            );
-        }));
-
-        let ast_normalized = ast.remove_spaces(&arena);
-        let reparsed_ast_normalized = reparsed_ast.remove_spaces(&arena);
-
-        // HACK!
-        // We compare the debug format strings of the ASTs, because I'm finding in practice that _somewhere_ deep inside the ast,
-        // the PartialEq implementation is returning `false` even when the Debug-formatted impl is exactly the same.
-        // I don't have the patience to debug this right now, so let's leave it for another day...
-        // TODO: fix PartialEq impl on ast types
-        if format!("{:?}", ast_normalized) != format!("{:?}", reparsed_ast_normalized) {
-            let mut fail_file = file.clone();
-            fail_file.set_extension("roc-format-failed");
-            std::fs::write(&fail_file, buf.as_str()).unwrap();
-
-            let mut before_file = file.clone();
-            before_file.set_extension("roc-format-failed-ast-before");
-            std::fs::write(&before_file, &format!("{:#?}\n", ast_normalized)).unwrap();
-
-            let mut after_file = file.clone();
-            after_file.set_extension("roc-format-failed-ast-after");
-            std::fs::write(&after_file, &format!("{:#?}\n", reparsed_ast_normalized)).unwrap();
-
-            internal_error!(
-                "Formatting bug; formatting didn't reparse as the same tree\n\n\
-                I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
-                I wrote the tree before and after formatting to these files for debugging purposes:\n{}\n{}\n\n",
-                fail_file.display(),
-                before_file.display(),
-                after_file.display());
-        }
-
-        // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
-        let mut reformatted_buf = Buf::new_in(&arena);
-        fmt_all(&mut reformatted_buf, reparsed_ast);
-        if buf.as_str() != reformatted_buf.as_str() {
-            let mut unstable_1_file = file.clone();
-            unstable_1_file.set_extension("roc-format-unstable-1");
-            std::fs::write(&unstable_1_file, buf.as_str()).unwrap();
-
-            let mut unstable_2_file = file.clone();
-            unstable_2_file.set_extension("roc-format-unstable-2");
-            std::fs::write(&unstable_2_file, reformatted_buf.as_str()).unwrap();
-
-            internal_error!(
-                "Formatting bug; formatting is not stable. Reformatting the formatted file changed it again.\n\n\
-                I wrote the result of formatting to this file for debugging purposes:\n{}\n\n\
-                I wrote the result of double-formatting here:\n{}\n\n",
-                unstable_1_file.display(),
-                unstable_2_file.display());
-        }
+        let formatted = match format_src(&arena, &src) {
+            Ok(formatted) => formatted,
+            Err(FmtError::ParseError(e)) => {
+                user_error!("Unexpected parse failure when parsing this formatting:\n\n{:?}\n\nParse error was:\n\n{}\n\n", src, e)
+            }
+            Err(FmtError::InvalidFormattedCode {
+                formatted,
+                parse_error,
+            }) => {
+                let mut fail_file = file.clone();
+                fail_file.set_extension("roc-format-failed");
+                std::fs::write(&fail_file, formatted).unwrap();
+                internal_error!(
+                    "Formatting bug; formatted code isn't valid\n\n\
+                    I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
+                    Parse error was: {}\n\n",
+                    fail_file.display(),
+                    parse_error
+                );
+            }
+            Err(FmtError::TreeChanged {
+                formatted,
+                tree_before,
+                tree_after,
+            }) => {
+                let mut fail_file = file.clone();
+                fail_file.set_extension("roc-format-failed");
+                std::fs::write(&fail_file, formatted).unwrap();
+
+                let mut before_file = file.clone();
+                before_file.set_extension("roc-format-failed-ast-before");
+                std::fs::write(&before_file, tree_before).unwrap();
+
+                let mut after_file = file.clone();
+                after_file.set_extension("roc-format-failed-ast-after");
+                std::fs::write(&after_file, tree_after).unwrap();
+
+                internal_error!(
+                    "Formatting bug; formatting didn't reparse as the same tree\n\n\
+                    I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
+                    I wrote the tree before and after formatting to these files for debugging purposes:\n{}\n{}\n\n",
+                    fail_file.display(),
+                    before_file.display(),
+                    after_file.display());
+            }
+            Err(FmtError::Unstable {
+                formatted,
+                formatted_twice,
+            }) => {
+                let mut unstable_1_file = file.clone();
+                unstable_1_file.set_extension("roc-format-unstable-1");
+                std::fs::write(&unstable_1_file, formatted).unwrap();
+
+                let mut unstable_2_file = file.clone();
+                unstable_2_file.set_extension("roc-format-unstable-2");
+                std::fs::write(&unstable_2_file, formatted_twice).unwrap();
+
+                internal_error!(
+                    "Formatting bug; formatting is not stable. Reformatting the formatted file changed it again.\n\n\
+                    I wrote the result of formatting to this file for debugging purposes:\n{}\n\n\
+                    I wrote the result of double-formatting here:\n{}\n\n",
+                    unstable_1_file.display(),
+                    unstable_2_file.display());
+            }
+        };
 
         match mode {
             FormatMode::CheckOnly => {
                 // If we notice that this file needs to be formatted, return early
-                if buf.as_str() != src {
+                if formatted != src {
                     return Err("One or more files need to be reformatted.".to_string());
                 }
             }
 
             FormatMode::Format => {
                 // If all the checks above passed, actually write out the new file.
-                std::fs::write(&file, buf.as_str()).unwrap();
+                std::fs::write(&file, formatted).unwrap();
             }
         }
     }
@@ -153,6 +170,60 @@ pub fn format(files: std::vec::Vec<PathBuf>, mode: FormatMode) -> Result<(), Str
     Ok(())
 }
 
+/// Is this source code exactly what the formatter would produce?
+/// Formatting bugs are reported as errors rather than as "not formatted",
+/// so that `roc format --check` can't pass or fail because of them.
+pub fn is_formatted(src: &str) -> Result<bool, FmtError> {
+    let arena = Bump::new();
+
+    let formatted = format_src(&arena, src)?;
+
+    Ok(formatted == src)
+}
+
+/// Format the source code, and check that the result means the same thing,
+/// and that formatting it again doesn't change it.
+fn format_src<'a>(arena: &'a Bump, src: &'a str) -> Result<&'a str, FmtError> {
+    let ast =
+        arena.alloc(parse_all(arena, src).map_err(|e| FmtError::ParseError(format!("{:?}", e)))?);
+    let formatted = fmt_all(arena, ast);
+
+    let reparsed_ast =
+        arena.alloc(
+            parse_all(arena, formatted).map_err(|e| FmtError::InvalidFormattedCode {
+                formatted: formatted.to_string(),
+                parse_error: format!("{:?}", e),
+            })?,
+        );
+
+    let ast_normalized = ast.remove_spaces(arena);
+    let reparsed_ast_normalized = reparsed_ast.remove_spaces(arena);
+
+    // HACK!
+    // We compare the debug format strings of the ASTs, because I'm finding in practice that _somewhere_ deep inside the ast,
+    // the PartialEq implementation is returning `false` even when the Debug-formatted impl is exactly the same.
+    // I don't have the patience to debug this right now, so let's leave it for another day...
+    // TODO: fix PartialEq impl on ast types
+    if format!("{:?}", ast_normalized) != format!("{:?}", reparsed_ast_normalized) {
+        return Err(FmtError::TreeChanged {
+            formatted: formatted.to_string(),
+            tree_before: format!("{:#?}\n", ast_normalized),
+            tree_after: format!("{:#?}\n", reparsed_ast_normalized),
+        });
+    }
+
+    // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
+    let formatted_twice = fmt_all(arena, reparsed_ast);
+    if formatted != formatted_twice {
+        return Err(FmtError::Unstable {
+            formatted: formatted.to_string(),
+            formatted_twice: formatted_twice.to_string(),
+        });
+    }
+
+    Ok(formatted)
+}
+
 fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<Ast<'a>, SyntaxError<'a>> {
     let (module, state) = module::parse_header(arena, State::new(src.as_bytes()))
         .map_err(|e| SyntaxError::Header(e.problem))?;
@@ -162,10 +233,9 @@ fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<Ast<'a>, SyntaxError<'
     Ok(Ast { module, defs })
 }
 
-fn fmt_all<'a>(buf: &mut Buf<'a>, ast: &'a Ast) {
-    fmt_module(buf, &ast.module);
-
-    fmt_defs(buf, &ast.defs, 0);
+fn fmt_all<'a>(arena: &'a Bump, ast: &'a Ast) -> &'a str {
+    let mut formatter = Formatter::new_in(arena);
+    formatter.format_module(ast);
 
-    buf.fmt_end_of_file();
+    formatter.into_bump_str()
 }
@@ -26,7 +26,7 @@ use tempfile::TempDir;
 
 pub mod build;
 mod format;
-pub use format::format;
+pub use format::{format, is_formatted, FmtError};
 
 use crate::build::{BuildFileError, BuildOrdering};
 
@@ -1137,6 +1137,24 @@ mod cli_run {
         check_format_check_as_expected(&fixture_file("format", "NotFormatted.roc"), false);
     }
 
+    #[test]
+    fn is_formatted() {
+        let formatted = std::fs::read_to_string(fixture_file("format", "Formatted.roc")).unwrap();
+        assert!(roc_cli::is_formatted(&formatted).unwrap());
+
+        let extra_spaces = formatted.replace("main = ", "main =  ");
+        assert!(!roc_cli::is_formatted(&extra_spaces).unwrap());
+
+        let not_formatted =
+            std::fs::read_to_string(fixture_file("format", "NotFormatted.roc")).unwrap();
+        assert!(!roc_cli::is_formatted(&not_formatted).unwrap());
+
+        assert!(matches!(
+            roc_cli::is_formatted("main = "),
+            Err(roc_cli::FmtError::ParseError(_))
+        ));
+    }
+
     #[test]
     fn format_check_folders() {
         // This fails, because "NotFormatted.roc" is present in this folder
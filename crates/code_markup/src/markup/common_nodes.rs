@@ -13,6 +13,22 @@ pub fn new_equals_mn() -> MarkupNode {
     common_text_node(nodes::EQUALS.to_owned(), HighlightStyle::Operator, 0)
 }
 
+// for a def whose body starts on the next line, no trailing space before the line break
+pub fn new_equals_mn_w_nl() -> MarkupNode {
+    common_text_node(
+        nodes::EQUALS.trim_end().to_owned(),
+        HighlightStyle::Operator,
+        1,
+    )
+}
+
+pub fn new_indent_mn(indent_level: usize) -> MarkupNode {
+    MarkupNode::Indent {
+        indent_level,
+        parent_id_opt: None,
+    }
+}
+
 pub fn new_comma_mn() -> MarkupNode {
     common_text_node(nodes::COMMA.to_owned(), HighlightStyle::Operator, 0)
 }
@@ -65,6 +81,14 @@ pub fn new_right_square_mn() -> MarkupNode {
     )
 }
 
+pub fn new_left_paren_mn() -> MarkupNode {
+    common_text_node(nodes::LEFT_PAREN.to_owned(), HighlightStyle::Bracket, 0)
+}
+
+pub fn new_right_paren_mn() -> MarkupNode {
+    common_text_node(nodes::RIGHT_PAREN.to_owned(), HighlightStyle::Bracket, 0)
+}
+
 pub fn new_func_name_mn(content: String) -> MarkupNode {
     common_text_node(content, HighlightStyle::FunctionName, 0)
 }
@@ -112,6 +136,24 @@ pub fn new_assign_mn(
     )
 }
 
+// like new_assign_mn, for a body on its own indented line
+pub fn new_indented_assign_mn(
+    val_name_mn_id: MarkNodeId,
+    equals_mn_id: MarkNodeId,
+    indent_mn_id: MarkNodeId,
+    expr_mark_node_id: MarkNodeId,
+) -> MarkupNode {
+    make_nested_mn(
+        vec![
+            val_name_mn_id,
+            equals_mn_id,
+            indent_mn_id,
+            expr_mark_node_id,
+        ],
+        NEW_LINES_AFTER_DEF,
+    )
+}
+
 pub fn new_module_name_mn_id(mn_ids: Vec<MarkNodeId>, mark_node_pool: &mut SlowPool) -> MarkNodeId {
     if mn_ids.len() == 1 {
         *mn_ids.first().unwrap() // safe because we checked the length before
@@ -121,6 +163,24 @@ pub fn new_module_name_mn_id(mn_ids: Vec<MarkNodeId>, mark_node_pool: &mut SlowP
     }
 }
 
+pub fn new_parens_around_mn(
+    left_paren_mn_id: MarkNodeId,
+    expr_mn_id: MarkNodeId,
+    right_paren_mn_id: MarkNodeId,
+) -> MarkupNode {
+    make_nested_mn(vec![left_paren_mn_id, expr_mn_id, right_paren_mn_id], 0)
+}
+
+// `a + b * c` becomes a flat nested node of operands and operators, a single operand is returned as is
+pub fn new_bin_op_expr_mn_id(mn_ids: Vec<MarkNodeId>, mark_node_pool: &mut SlowPool) -> MarkNodeId {
+    if mn_ids.len() == 1 {
+        *mn_ids.first().unwrap() // safe because we checked the length before
+    } else {
+        let nested_node = make_nested_mn(mn_ids, 0);
+        mark_node_pool.add(nested_node)
+    }
+}
+
 pub fn new_module_var_mn(
     module_name_id: MarkNodeId,
     dot_id: MarkNodeId,
@@ -267,6 +267,8 @@ pub const LEFT_ACCOLADE: &str = "{ ";
 pub const RIGHT_ACCOLADE: &str = " }";
 pub const LEFT_SQUARE_BR: &str = "[ ";
 pub const RIGHT_SQUARE_BR: &str = " ]";
+pub const LEFT_PAREN: &str = "( ";
+pub const RIGHT_PAREN: &str = " )";
 pub const COLON: &str = ": ";
 pub const COMMA: &str = ", ";
 pub const DOT: &str = ".";
@@ -23,6 +23,7 @@ pub enum HighlightStyle {
     UppercaseIdent,
     LowercaseIdent, // TODO we probably don't want all lowercase identifiers to have the same color?
     Keyword,        // if, else, when...
+    Builtin,        // references to builtin functions like List.map
 }
 
 pub fn default_highlight_map() -> HashMap<HighlightStyle, RgbaTup> {
@@ -50,6 +51,7 @@ pub fn default_highlight_map() -> HashMap<HighlightStyle, RgbaTup> {
         (UppercaseIdent, almost_white),
         (LowercaseIdent, from_hsb(225, 50, 100)),
         (Keyword, almost_white),
+        (Builtin, from_hsb(185, 50, 75)),
     ]
     .iter()
     .for_each(|tup| {
@@ -1,5 +1,5 @@
 pub use roc_gen_llvm::llvm::build::FunctionIterator;
-use roc_gen_llvm::llvm::build::{module_from_builtins, LlvmBackendMode};
+use roc_gen_llvm::llvm::build::{module_from_builtins, verify_module, LlvmBackendMode};
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
 use roc_load::{EntryPoint, LoadedModule, MonomorphizedModule};
 use roc_module::symbol::{Interns, ModuleId};
@@ -250,6 +250,7 @@ pub fn gen_from_mono_module_llvm(
     }
 
     let builder = context.create_builder();
+    let alloca_builder = context.create_builder();
     let (dibuilder, compile_unit) = roc_gen_llvm::llvm::build::Env::new_debug_info(module);
     let (mpm, _fpm) = roc_gen_llvm::llvm::build::construct_optimization_passes(module, opt_level);
 
@@ -258,6 +259,7 @@ pub fn gen_from_mono_module_llvm(
         arena,
         layout_interner: &loaded.layout_interner,
         builder: &builder,
+        alloca_builder: &alloca_builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
         context: &context,
@@ -266,6 +268,7 @@ pub fn gen_from_mono_module_llvm(
         target_info,
         mode: LlvmBackendMode::Binary,
         exposed_to_host: loaded.exposed_to_host.values.keys().copied().collect(),
+        global_constants: Default::default(),
     };
 
     // does not add any externs for this mode (we have a host) but cleans up some functions around
@@ -298,14 +301,13 @@ pub fn gen_from_mono_module_llvm(
     mpm.run_on(module);
 
     // Verify the module
-    if let Err(errors) = env.module.verify() {
+    if let Err(errors) = verify_module(env.module) {
         // write the ll code to a file, so we can modify it
         env.module.print_to_file(&app_ll_file).unwrap();
 
         panic!(
             "😱 LLVM errors when defining module; I wrote the full LLVM IR to {:?}\n\n {}",
-            app_ll_file,
-            errors.to_string(),
+            app_ll_file, errors,
         );
     }
 
@@ -9,8 +9,19 @@ pub mod module;
 pub mod pattern;
 pub mod spaces;
 
-use bumpalo::{collections::String, Bump};
-use roc_parse::ast::Module;
+use annotation::Formattable;
+use bumpalo::{
+    collections::{String, Vec},
+    Bump,
+};
+use roc_parse::ast::{
+    AssignedField, Collection, CommentOrNewline, Expr, Module, TypeDef, ValueDef,
+};
+use roc_parse::module::{module_defs, parse_header};
+use roc_parse::parser::Parser;
+use roc_parse::state::State;
+use roc_region::all::Loc;
+use std::ops::Range;
 
 #[derive(Debug)]
 pub struct Ast<'a> {
@@ -23,6 +34,9 @@ pub struct Buf<'a> {
     text: String<'a>,
     spaces_to_flush: usize,
     beginning_of_line: bool,
+    /// Spaces pushed by `indent` on the current line, with nothing after them yet.
+    /// `newline` removes them, so an empty line never ends up with trailing whitespace.
+    trailing_indent: usize,
 }
 
 impl<'a> Buf<'a> {
@@ -31,6 +45,7 @@ impl<'a> Buf<'a> {
             text: String::new_in(arena),
             spaces_to_flush: 0,
             beginning_of_line: true,
+            trailing_indent: 0,
         }
     }
 
@@ -47,6 +62,7 @@ impl<'a> Buf<'a> {
             for _ in 0..indent {
                 self.text.push(' ');
             }
+            self.trailing_indent = indent as usize;
         }
         self.beginning_of_line = false;
     }
@@ -65,6 +81,7 @@ impl<'a> Buf<'a> {
         self.flush_spaces();
 
         self.text.push(ch);
+        self.trailing_indent = 0;
     }
 
     pub fn push_str_allow_spaces(&mut self, s: &str) {
@@ -73,6 +90,7 @@ impl<'a> Buf<'a> {
         self.flush_spaces();
 
         self.text.push_str(s);
+        self.trailing_indent = 0;
     }
 
     pub fn push_str(&mut self, s: &str) {
@@ -81,6 +99,7 @@ impl<'a> Buf<'a> {
 
         if !s.is_empty() {
             self.flush_spaces();
+            self.trailing_indent = 0;
         }
 
         self.text.push_str(s);
@@ -92,6 +111,8 @@ impl<'a> Buf<'a> {
 
     pub fn newline(&mut self) {
         self.spaces_to_flush = 0;
+        self.text
+            .truncate(self.text.len() - std::mem::take(&mut self.trailing_indent));
         self.text.push('\n');
         self.beginning_of_line = true;
     }
@@ -126,10 +147,13 @@ impl<'a> Buf<'a> {
                 self.text.push(' ');
             }
             self.spaces_to_flush = 0;
+            self.trailing_indent = 0;
         }
     }
 
     /// Ensures the text ends in a newline with no whitespace preceding it.
+    /// This is the last step of formatting a file: blank lines and spaces at the end are
+    /// trimmed down to a single `\n`.
     pub fn fmt_end_of_file(&mut self) {
         fmt_text_eof(&mut self.text)
     }
@@ -145,6 +169,204 @@ impl<'a> Buf<'a> {
     fn is_empty(&self) -> bool {
         self.spaces_to_flush == 0 && self.text.is_empty()
     }
+
+    /// Empties the buffer, but keeps its allocation so it can be reused
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.spaces_to_flush = 0;
+        self.beginning_of_line = true;
+        self.trailing_indent = 0;
+    }
+}
+
+/// Lines longer than this make the Formatter break a def's list or record body
+pub const DEFAULT_MAX_WIDTH: usize = 100;
+
+/// Formats whole modules for `roc format`, or defs one at a time for callers like an editor
+/// that format over and over, and shouldn't allocate a new buffer each time.
+#[derive(Debug)]
+pub struct Formatter<'a> {
+    arena: &'a Bump,
+    buf: Buf<'a>,
+    max_width: usize,
+}
+
+impl<'a> Formatter<'a> {
+    pub fn new_in(arena: &'a Bump) -> Formatter<'a> {
+        Formatter {
+            arena,
+            buf: Buf::new_in(arena),
+            max_width: DEFAULT_MAX_WIDTH,
+        }
+    }
+
+    pub fn set_max_width(&mut self, max_width: usize) {
+        self.max_width = max_width;
+    }
+
+    /// Appends the formatted def to the buffer, and returns just the text of this def.
+    /// A def whose body is a single-line list or record that ends up wider than `max_width`
+    /// gets its body broken up with one item per line.
+    pub fn format_def(&mut self, def: Result<&TypeDef<'_>, &ValueDef<'_>>) -> &str {
+        let start = self.buf.text.len();
+
+        self.push_def(def);
+
+        &self.buf.text[start..]
+    }
+
+    /// Appends the whole formatted module to the buffer, breaking its top-level defs
+    /// the same way `format_def` does, and returns just the text of this module.
+    pub fn format_module(&mut self, ast: &Ast<'_>) -> &str {
+        let start = self.buf.text.len();
+
+        module::fmt_module(&mut self.buf, &ast.module);
+
+        let defs = &ast.defs;
+        for (index, def) in defs.defs().enumerate() {
+            let spaces_before = &defs.spaces[defs.space_before[index].indices()];
+            let spaces_after = &defs.spaces[defs.space_after[index].indices()];
+
+            spaces::fmt_spaces(&mut self.buf, spaces_before.iter(), 0);
+            self.push_def(def);
+            spaces::fmt_spaces(&mut self.buf, spaces_after.iter(), 0);
+        }
+
+        self.buf.fmt_end_of_file();
+
+        &self.buf.text[start..]
+    }
+
+    fn push_def(&mut self, def: Result<&TypeDef<'_>, &ValueDef<'_>>) {
+        let start = self.buf.text.len();
+        let (spaces_to_flush, beginning_of_line, trailing_indent) = (
+            self.buf.spaces_to_flush,
+            self.buf.beginning_of_line,
+            self.buf.trailing_indent,
+        );
+
+        match def {
+            Ok(type_def) => type_def.format(&mut self.buf, 0),
+            Err(value_def) => value_def.format(&mut self.buf, 0),
+        }
+
+        let too_wide = self.buf.text[start..]
+            .lines()
+            .any(|line| line.chars().count() > self.max_width);
+
+        if let (true, Err(ValueDef::Body(loc_pattern, loc_expr))) = (too_wide, def) {
+            let arena = self.arena;
+
+            if let Some(broken_body) = break_collection(arena, &loc_expr.value) {
+                self.buf.text.truncate(start);
+                self.buf.spaces_to_flush = spaces_to_flush;
+                self.buf.beginning_of_line = beginning_of_line;
+                self.buf.trailing_indent = trailing_indent;
+
+                def::fmt_body(
+                    &mut self.buf,
+                    &loc_pattern.value,
+                    arena.alloc(broken_body),
+                    0,
+                );
+            }
+        }
+    }
+
+    /// Forgets everything formatted so far. Call this between formats,
+    /// so that the buffer doesn't keep growing.
+    pub fn reset(&mut self) {
+        self.buf.clear();
+    }
+
+    pub fn into_bump_str(self) -> &'a str {
+        self.buf.into_bump_str()
+    }
+}
+
+const NEWLINE: &[CommentOrNewline<'static>] = &[CommentOrNewline::Newline];
+
+/// The same list or record with a newline before every item, so it is formatted one item per line.
+/// Returns None for other exprs, and for collections that are already multiline or empty.
+fn break_collection<'b>(arena: &'b Bump, expr: &'b Expr<'b>) -> Option<Expr<'b>> {
+    if expr.is_multiline() {
+        return None;
+    }
+
+    match expr {
+        Expr::List(items) if !items.is_empty() => {
+            let broken = Vec::from_iter_in(
+                items.iter().map(|item| {
+                    &*arena.alloc(Loc::at(
+                        item.region,
+                        Expr::SpaceBefore(&item.value, NEWLINE),
+                    ))
+                }),
+                arena,
+            );
+
+            Some(Expr::List(Collection::with_items(broken.into_bump_slice())))
+        }
+        Expr::Record(fields) if !fields.is_empty() => {
+            let broken = Vec::from_iter_in(
+                fields.iter().map(|field| {
+                    Loc::at(
+                        field.region,
+                        AssignedField::SpaceBefore(&field.value, NEWLINE),
+                    )
+                }),
+                arena,
+            );
+
+            Some(Expr::Record(Collection::with_items(
+                broken.into_bump_slice(),
+            )))
+        }
+        _ => None,
+    }
+}
+
+/// Formats only the top-level defs that overlap the byte range, for "format selection".
+/// A range that ends in the middle of a def formats that whole def, and an empty range
+/// formats the def it's in. Returns the whole source with those defs replaced.
+/// If the source doesn't parse, it is returned unchanged.
+pub fn format_range(src: &str, range: Range<usize>) -> std::string::String {
+    let arena = Bump::new();
+
+    let defs = match parse_header(&arena, State::new(src.as_bytes())) {
+        Ok((_, state)) => match module_defs().parse(&arena, state) {
+            Ok((_, defs, _)) => defs,
+            Err(_) => return src.to_string(),
+        },
+        Err(_) => return src.to_string(),
+    };
+
+    let mut formatter = Formatter::new_in(&arena);
+    let mut output = std::string::String::with_capacity(src.len());
+    let mut copied_up_to = 0;
+
+    for (def, region) in defs.defs().zip(defs.regions.iter()) {
+        let def_start = region.start().offset as usize;
+        let def_end = region.end().offset as usize;
+
+        let overlaps = if range.is_empty() {
+            def_start <= range.start && range.start <= def_end
+        } else {
+            def_start < range.end && range.start < def_end
+        };
+
+        if overlaps {
+            formatter.reset();
+
+            output.push_str(&src[copied_up_to..def_start]);
+            output.push_str(formatter.format_def(def).trim_end());
+            copied_up_to = def_end;
+        }
+    }
+
+    output.push_str(&src[copied_up_to..]);
+
+    output
 }
 
 /// Ensures the text ends in a newline with no whitespace preceding it.
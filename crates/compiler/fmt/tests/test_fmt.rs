@@ -9,7 +9,7 @@ mod test_fmt {
     use roc_fmt::annotation::{Formattable, Newlines, Parens};
     use roc_fmt::def::fmt_defs;
     use roc_fmt::module::fmt_module;
-    use roc_fmt::Buf;
+    use roc_fmt::{format_range, Ast, Buf, Formatter};
     use roc_parse::ast::Module;
     use roc_parse::module::{self, module_defs};
     use roc_parse::parser::Parser;
@@ -176,6 +176,26 @@ mod test_fmt {
         module_formats_to(input, input);
     }
 
+    /// Formats a whole file, including the end-of-file normalization, without trimming the output
+    fn format_file(src: &str) -> std::string::String {
+        let arena = Bump::new();
+        let (module, state) = module::parse_header(&arena, State::new(src.as_bytes())).unwrap();
+        let mut buf = Buf::new_in(&arena);
+
+        fmt_module_and_defs(&arena, src, &module, state, &mut buf);
+        buf.fmt_end_of_file();
+
+        buf.as_str().to_string()
+    }
+
+    fn assert_no_trailing_whitespace(output: &str) {
+        assert!(output.ends_with('\n') && !output.ends_with("\n\n"));
+
+        for line in output.lines() {
+            assert_eq!(line, line.trim_end(), "line has trailing whitespace");
+        }
+    }
+
     // STRING LITERALS
 
     #[test]
@@ -1243,6 +1263,41 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn def_with_hex_literal() {
+        expr_formats_same(indoc!(
+            r#"
+            x = 0xFF
+
+            x
+            "#
+        ));
+    }
+
+    #[test]
+    fn def_with_int_with_underscores() {
+        expr_formats_same(indoc!(
+            r#"
+            x = 1_000_000
+
+            x
+            "#
+        ));
+    }
+
+    #[test]
+    fn def_with_binary_and_negative_octal_literals() {
+        expr_formats_same(indoc!(
+            r#"
+            x = 0b1010_0101
+
+            y = -0o17
+
+            x
+            "#
+        ));
+    }
+
     #[test]
     fn multi_arg_closure() {
         expr_formats_same(indoc!(
@@ -4602,6 +4657,42 @@ mod test_fmt {
         );
     }
 
+    #[test]
+    fn file_with_extra_trailing_newlines() {
+        let output = format_file(indoc!(
+            r#"
+            interface Foo exposes [] imports []
+            a = 42
+
+
+
+            "#
+        ));
+
+        assert_no_trailing_whitespace(&output);
+        assert_multiline_str_eq!(output.as_str(), format_file(output.trim_end()).as_str());
+    }
+
+    #[test]
+    fn file_with_trailing_spaces() {
+        let output = format_file(&format!(
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []{space}
+                a ={space}
+                    b = 1{space}
+
+                    b{space}{space}
+                {space}{space}{space}{space}
+                "#
+            ),
+            space = " ",
+        ));
+
+        assert_no_trailing_whitespace(&output);
+        assert!(output.contains("b = 1\n\n"));
+    }
+
     #[test]
     fn multiline_interface() {
         module_formats_same(indoc!(
@@ -5567,6 +5658,138 @@ mod test_fmt {
         );
     }
 
+    #[test]
+    fn reuse_formatter() {
+        let arena = Bump::new();
+        let src = indoc!(
+            r#"
+            x   =  5
+
+            Pair a b :   [  Pair a b ]
+
+            f =   \a ,b -> Pair a b
+            "#
+        );
+        let defs = roc_parse::test_helpers::parse_defs_with(&arena, src).unwrap();
+
+        let mut formatter = Formatter::new_in(&arena);
+        let mut formatted = Vec::new();
+        for def in defs.defs() {
+            formatter.reset();
+            formatted.push(formatter.format_def(def).trim().to_owned());
+        }
+
+        assert_eq!(
+            formatted,
+            vec!["x = 5", "Pair a b : [Pair a b]", "f = \\a, b -> Pair a b"]
+        );
+    }
+
+    fn format_defs_with_formatter(formatter: &mut Formatter, src: &str) -> Vec<String> {
+        let arena = Bump::new();
+        let defs = roc_parse::test_helpers::parse_defs_with(&arena, src).unwrap();
+
+        let mut formatted = Vec::new();
+        for def in defs.defs() {
+            formatter.reset();
+            formatted.push(formatter.format_def(def).trim().to_owned());
+        }
+
+        formatted
+    }
+
+    #[test]
+    fn long_list_wraps_at_max_width() {
+        let arena = Bump::new();
+        let mut formatter = Formatter::new_in(&arena);
+
+        let items: Vec<String> = (0..10).map(|i| format!("\"list item {}\"", i)).collect();
+        let src = format!("long = [{}]\n\nshort = [1, 2, 3]\n", items.join(", "));
+
+        let mut expected_long = "long = [\n".to_string();
+        for item in items.iter() {
+            expected_long.push_str(&format!("    {},\n", item));
+        }
+        expected_long.push(']');
+
+        assert_eq!(
+            format_defs_with_formatter(&mut formatter, &src),
+            vec![expected_long, "short = [1, 2, 3]".to_string()]
+        );
+    }
+
+    #[test]
+    fn record_wraps_at_configured_max_width() {
+        let arena = Bump::new();
+        let mut formatter = Formatter::new_in(&arena);
+        formatter.set_max_width(20);
+
+        let src = "point = { x: 1, y: 2, z: 3 }\n\np = { x: 1 }\n";
+
+        assert_eq!(
+            format_defs_with_formatter(&mut formatter, src),
+            vec![
+                "point = {\n    x: 1,\n    y: 2,\n    z: 3,\n}".to_string(),
+                "p = { x: 1 }".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn module_wraps_at_configured_max_width() {
+        let src =
+            "interface Foo exposes [] imports []\n\npoint = { x: 1, y: 2, z: 3 }\n\np = { x: 1 }\n";
+
+        let arena = Bump::new();
+        let (module, state) = module::parse_header(&arena, State::new(src.as_bytes())).unwrap();
+        let (_, defs, _) = module_defs().parse(&arena, state).unwrap();
+        let ast = Ast { module, defs };
+
+        let mut formatter = Formatter::new_in(&arena);
+        formatter.set_max_width(20);
+
+        let expected = format_file(src).replace(
+            "point = { x: 1, y: 2, z: 3 }",
+            "point = {\n    x: 1,\n    y: 2,\n    z: 3,\n}",
+        );
+
+        assert_multiline_str_eq!(expected.as_str(), formatter.format_module(&ast));
+    }
+
+    #[test]
+    fn format_range_of_defs() {
+        let src = indoc!(
+            r#"
+            interface Foo exposes [] imports []
+
+            x   =  5
+
+            y   =  6
+
+            z   =  7
+            "#
+        );
+        let y_start = src.find("y").unwrap();
+
+        // only the def in the range is formatted
+        let expected = src.replace("y   =  6", "y = 6");
+        assert_eq!(format_range(src, y_start..y_start + 8), expected);
+
+        // a range that only covers part of the def formats all of it
+        assert_eq!(format_range(src, y_start + 4..y_start + 6), expected);
+
+        // an empty range formats the def it's in
+        assert_eq!(format_range(src, y_start + 2..y_start + 2), expected);
+
+        // a range across several defs formats all of them
+        let z_start = src.find("z").unwrap();
+        let expected = expected.replace("z   =  7", "z = 7");
+        assert_eq!(format_range(src, y_start + 2..z_start + 1), expected);
+
+        // a range between defs changes nothing
+        assert_eq!(format_range(src, y_start - 1..y_start - 1), src);
+    }
+
     // this is a parse error atm
     //    #[test]
     //    fn multiline_apply() {
@@ -8,7 +8,7 @@ use crate::llvm::build_list::{
     list_prepend, list_replace_unsafe, list_reserve, list_sort_with, list_sublist, list_swap,
     list_symbol_to_c_abi, list_with_capacity, pass_update_mode,
 };
-use crate::llvm::compare::{generic_eq, generic_neq};
+use crate::llvm::compare::{generic_eq, generic_neq, to_float_predicate, to_int_predicate};
 use crate::llvm::convert::{
     self, argument_type_from_layout, basic_type_from_builtin, basic_type_from_layout, zig_str_type,
 };
@@ -33,7 +33,7 @@ use inkwell::types::{
 };
 use inkwell::values::BasicValueEnum::{self, *};
 use inkwell::values::{
-    BasicMetadataValueEnum, BasicValue, CallSiteValue, FloatValue, FunctionValue,
+    BasicMetadataValueEnum, BasicValue, CallSiteValue, FloatValue, FunctionValue, GlobalValue,
     InstructionOpcode, InstructionValue, IntValue, PhiValue, PointerValue, StructValue,
 };
 use inkwell::OptimizationLevel;
@@ -50,6 +50,7 @@ use roc_debug_flags::ROC_PRINT_LLVM_FN_VERIFICATION;
 use roc_error_macros::internal_error;
 use roc_module::low_level::LowLevel;
 use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_mono::const_eval::{fold_switch, try_eval_const, ConstEnv};
 use roc_mono::ir::{
     BranchInfo, CallType, EntryPoint, HigherOrderLowLevel, JoinPointId, ListLiteralElement,
     ModifyRc, OptLevel, ProcLayout,
@@ -60,6 +61,7 @@ use roc_mono::layout::{
 };
 use roc_std::RocDec;
 use roc_target::{PtrWidth, TargetInfo};
+use std::cell::RefCell;
 use std::convert::TryInto;
 use std::path::Path;
 use target_lexicon::{Architecture, OperatingSystem, Triple};
@@ -131,6 +133,10 @@ pub struct Scope<'a, 'ctx> {
     symbols: ImMap<Symbol, (Layout<'a>, BasicValueEnum<'ctx>)>,
     pub top_level_thunks: ImMap<Symbol, (ProcLayout<'a>, FunctionValue<'ctx>)>,
     join_points: ImMap<JoinPointId, (BasicBlock<'ctx>, &'a [PhiValue<'ctx>])>,
+    /// symbols whose value was evaluated at compile time
+    consts: ConstEnv,
+    /// tag id symbols, with the union they were read from, so a switch on one knows every possible tag id
+    tag_id_unions: ImMap<Symbol, UnionLayout<'a>>,
 }
 
 impl<'a, 'ctx> Scope<'a, 'ctx> {
@@ -151,6 +157,8 @@ impl<'a, 'ctx> Scope<'a, 'ctx> {
     }
     fn remove(&mut self, symbol: &Symbol) {
         self.symbols.remove(symbol);
+        self.consts.remove(symbol);
+        self.tag_id_unions.remove(symbol);
     }
 
     pub fn retain_top_level_thunks_for_module(&mut self, module_id: ModuleId) {
@@ -159,6 +167,18 @@ impl<'a, 'ctx> Scope<'a, 'ctx> {
     }
 }
 
+/// A mono IR expression that is malformed in a way codegen can't recover from.
+/// Returned instead of panicking, so an embedder can report it without aborting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodegenError {
+    /// `Reset` is only generated for symbols with a union layout
+    ResetOfNonUnion { symbol: Symbol },
+    /// `StructAtIndex` on a value that is neither a struct nor a NonNullableUnwrapped union
+    IndexIntoNonStruct { structure: Symbol, index: u64 },
+    /// `RuntimeErrorFunction` is not supported by this backend yet
+    RuntimeErrorFunction(String),
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum LlvmBackendMode {
     /// Assumes primitives (roc_alloc, roc_panic, etc) are provided by the host
@@ -205,6 +225,8 @@ pub struct Env<'a, 'ctx, 'env> {
     pub layout_interner: &'env STLayoutInterner<'a>,
     pub context: &'ctx Context,
     pub builder: &'env Builder<'ctx>,
+    /// Only used by create_entry_block_alloca, so we don't create a new Builder for every alloca
+    pub alloca_builder: &'env Builder<'ctx>,
     pub dibuilder: &'env DebugInfoBuilder<'ctx>,
     pub compile_unit: &'env DICompileUnit<'ctx>,
     pub module: &'ctx Module<'ctx>,
@@ -212,6 +234,8 @@ pub struct Env<'a, 'ctx, 'env> {
     pub target_info: TargetInfo,
     pub mode: LlvmBackendMode,
     pub exposed_to_host: MutSet<Symbol>,
+    /// Constant globals by their byte content, so identical constants are only emitted once
+    pub global_constants: RefCell<ImMap<Vec<u8>, GlobalValue<'ctx>>>,
 }
 
 #[repr(u32)]
@@ -389,6 +413,38 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         call.set_call_convention(C_CALL_CONV);
     }
 
+    /// A private constant global with the given bytes as its content.
+    ///
+    /// Identical byte content gives back the global that was created first,
+    /// so the `name_hint` of later calls is not used in that case.
+    pub fn build_global_bytes(&self, bytes: &[u8], name_hint: &str) -> GlobalValue<'ctx> {
+        if let Some(global) = self.global_constants.borrow().get(bytes) {
+            return *global;
+        }
+
+        let i8_type = self.context.i8_type();
+
+        // use None for the address space (e.g. Const does not work)
+        let typ = i8_type.array_type(bytes.len() as u32);
+        let global = self.module.add_global(typ, None, name_hint);
+
+        let elements: Vec<_> = bytes
+            .iter()
+            .map(|b| i8_type.const_int(*b as u64, false))
+            .collect();
+        global.set_initializer(&i8_type.const_array(&elements));
+
+        global.set_constant(true);
+        global.set_unnamed_addr(true);
+        global.set_linkage(inkwell::module::Linkage::Private);
+
+        self.global_constants
+            .borrow_mut()
+            .insert(bytes.to_vec(), global);
+
+        global
+    }
+
     pub fn new_debug_info(module: &Module<'ctx>) -> (DebugInfoBuilder<'ctx>, DICompileUnit<'ctx>) {
         module.create_debug_info_builder(
             true,
@@ -914,6 +970,9 @@ fn int_with_precision<'a, 'ctx, 'env>(
     }
 }
 
+/// `const_float` keeps the exact bits of the `f64`, including negative zero and subnormals.
+/// For F32 it rounds to nearest, like `value as f32`. Infinite literals are already
+/// rejected by canonicalization, and there is no syntax for NaN literals.
 fn float_with_precision<'a, 'ctx, 'env>(
     env: &Env<'a, 'ctx, 'env>,
     value: f64,
@@ -1201,10 +1260,10 @@ pub fn build_exp_expr<'a, 'ctx, 'env>(
     parent: FunctionValue<'ctx>,
     layout: &Layout<'a>,
     expr: &roc_mono::ir::Expr<'a>,
-) -> BasicValueEnum<'ctx> {
+) -> Result<BasicValueEnum<'ctx>, CodegenError> {
     use roc_mono::ir::Expr::*;
 
-    match expr {
+    let value = match expr {
         Literal(literal) => build_exp_literal(env, parent, layout, literal),
 
         Call(call) => build_exp_call(
@@ -1275,7 +1334,7 @@ pub fn build_exp_expr<'a, 'ctx, 'env>(
             // reset is only generated for union values
             let union_layout = match layout {
                 Layout::Union(ul) => ul,
-                _ => unreachable!(),
+                _ => return Err(CodegenError::ResetOfNonUnion { symbol: *symbol }),
             };
 
             let ctx = env.context;
@@ -1326,73 +1385,13 @@ pub fn build_exp_expr<'a, 'ctx, 'env>(
 
         StructAtIndex {
             index, structure, ..
-        } => {
-            let (value, layout) = load_symbol_and_layout(scope, structure);
-
-            let layout = if let Layout::LambdaSet(lambda_set) = layout {
-                lambda_set.runtime_representation(env.layout_interner)
-            } else {
-                *layout
-            };
-
-            // extract field from a record
-            match (value, layout) {
-                (StructValue(argument), Layout::Struct { field_layouts, .. }) => {
-                    debug_assert!(!field_layouts.is_empty());
-
-                    let field_value = env
-                        .builder
-                        .build_extract_value(
-                            argument,
-                            *index as u32,
-                            env.arena
-                                .alloc(format!("struct_field_access_record_{}", index)),
-                        )
-                        .unwrap();
-
-                    let field_layout = field_layouts[*index as usize];
-                    use_roc_value(env, field_layout, field_value, "struct_field_tag")
-                }
-                (
-                    PointerValue(argument),
-                    Layout::Union(UnionLayout::NonNullableUnwrapped(fields)),
-                ) => {
-                    let struct_layout = Layout::struct_no_name_order(fields);
-                    let struct_type = basic_type_from_layout(env, &struct_layout);
-
-                    let cast_argument = env
-                        .builder
-                        .build_bitcast(
-                            argument,
-                            struct_type.ptr_type(AddressSpace::Generic),
-                            "cast_rosetree_like",
-                        )
-                        .into_pointer_value();
-
-                    let ptr = env
-                        .builder
-                        .build_struct_gep(
-                            cast_argument,
-                            *index as u32,
-                            env.arena.alloc(format!("non_nullable_unwrapped_{}", index)),
-                        )
-                        .unwrap();
-
-                    env.builder.build_load(ptr, "load_rosetree_like")
-                }
-                (other, layout) => {
-                    // potential cause: indexing into an unwrapped 1-element record/tag?
-                    unreachable!(
-                        "can only index into struct layout\nValue: {:?}\nLayout: {:?}\nIndex: {:?}",
-                        other, layout, index
-                    )
-                }
-            }
-        }
+        } => build_struct_at_index(env, scope, *structure, *index)?,
 
         EmptyArray => empty_polymorphic_list(env),
         Array { elem_layout, elems } => list_literal(env, parent, scope, elem_layout, elems),
-        RuntimeErrorFunction(_) => todo!(),
+        RuntimeErrorFunction(message) => {
+            return Err(CodegenError::RuntimeErrorFunction(message.to_string()))
+        }
 
         UnionAtIndex {
             tag_id,
@@ -1514,6 +1513,75 @@ pub fn build_exp_expr<'a, 'ctx, 'env>(
 
             get_tag_id(env, parent, union_layout, argument).into()
         }
+    };
+
+    Ok(value)
+}
+
+/// Extract field `index` from `structure`, a struct or a NonNullableUnwrapped union
+pub fn build_struct_at_index<'a, 'ctx, 'env>(
+    env: &Env<'a, 'ctx, 'env>,
+    scope: &Scope<'a, 'ctx>,
+    structure: Symbol,
+    index: u64,
+) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+    let (value, layout) = load_symbol_and_layout(scope, &structure);
+
+    let layout = if let Layout::LambdaSet(lambda_set) = layout {
+        lambda_set.runtime_representation(env.layout_interner)
+    } else {
+        *layout
+    };
+
+    // extract field from a record
+    match (value, layout) {
+        (StructValue(argument), Layout::Struct { field_layouts, .. }) => {
+            debug_assert!(!field_layouts.is_empty());
+
+            let field_value = env
+                .builder
+                .build_extract_value(
+                    argument,
+                    index as u32,
+                    env.arena
+                        .alloc(format!("struct_field_access_record_{}", index)),
+                )
+                .unwrap();
+
+            let field_layout = field_layouts[index as usize];
+            Ok(use_roc_value(
+                env,
+                field_layout,
+                field_value,
+                "struct_field_tag",
+            ))
+        }
+        (PointerValue(argument), Layout::Union(UnionLayout::NonNullableUnwrapped(fields))) => {
+            let struct_layout = Layout::struct_no_name_order(fields);
+            let struct_type = basic_type_from_layout(env, &struct_layout);
+
+            let cast_argument = env
+                .builder
+                .build_bitcast(
+                    argument,
+                    struct_type.ptr_type(AddressSpace::Generic),
+                    "cast_rosetree_like",
+                )
+                .into_pointer_value();
+
+            let ptr = env
+                .builder
+                .build_struct_gep(
+                    cast_argument,
+                    index as u32,
+                    env.arena.alloc(format!("non_nullable_unwrapped_{}", index)),
+                )
+                .unwrap();
+
+            Ok(env.builder.build_load(ptr, "load_rosetree_like"))
+        }
+        // potential cause: indexing into an unwrapped 1-element record/tag?
+        _ => Err(CodegenError::IndexIntoNonStruct { structure, index }),
     }
 }
 
@@ -2472,15 +2540,28 @@ pub fn build_exp_stmt<'a, 'ctx, 'env>(
             for (symbol, expr, layout) in queue {
                 debug_assert!(layout != &Layout::RecursivePointer);
 
-                let val = build_exp_expr(
-                    env,
-                    layout_ids,
-                    func_spec_solutions,
-                    scope,
-                    parent,
-                    layout,
-                    expr,
-                );
+                // emit a bare constant when the value is known at compile time
+                let val = match try_eval_const(expr, layout, &scope.consts) {
+                    Some(const_value) => {
+                        scope.consts.insert(*symbol, const_value);
+
+                        build_exp_literal(env, parent, layout, &const_value.to_literal())
+                    }
+                    None => build_exp_expr(
+                        env,
+                        layout_ids,
+                        func_spec_solutions,
+                        scope,
+                        parent,
+                        layout,
+                        expr,
+                    )
+                    .unwrap_or_else(|err| {
+                        internal_error!("Failed to build the value of {:?}: {:?}", symbol, err)
+                    }),
+                };
+
+                name_value_after_symbol(env, val, *symbol);
 
                 // Make a new scope which includes the binding we just encountered.
                 // This should be done *after* compiling the bound expr, since any
@@ -2490,6 +2571,9 @@ pub fn build_exp_stmt<'a, 'ctx, 'env>(
                 // scope = scope.clone();
 
                 scope.insert(*symbol, (*layout, val));
+                if let Expr::GetTagId { union_layout, .. } = expr {
+                    scope.tag_id_unions.insert(*symbol, *union_layout);
+                }
                 stack.push(*symbol);
             }
 
@@ -2597,6 +2681,27 @@ pub fn build_exp_stmt<'a, 'ctx, 'env>(
             cond_layout,
             cond_symbol,
         } => {
+            // only the branch that can be taken is generated
+            if let Some(cond) = scope.consts.get(cond_symbol) {
+                let taken = fold_switch(*cond, branches, default_branch.1);
+
+                return build_exp_stmt(env, layout_ids, func_spec_solutions, scope, parent, taken);
+            }
+
+            if let Some(value) = build_switch_select(
+                env,
+                layout_ids,
+                func_spec_solutions,
+                scope,
+                parent,
+                *cond_layout,
+                *cond_symbol,
+                branches,
+                default_branch.1,
+            ) {
+                return value;
+            }
+
             let ret_type = basic_type_from_layout(env, ret_layout);
 
             let switch_args = SwitchArgsIr {
@@ -2605,6 +2710,7 @@ pub fn build_exp_stmt<'a, 'ctx, 'env>(
                 branches,
                 default_branch: default_branch.1,
                 ret_type,
+                matched_union: scope.tag_id_unions.get(cond_symbol).copied(),
             };
 
             build_switch_ir(
@@ -2761,24 +2867,14 @@ pub fn build_exp_stmt<'a, 'ctx, 'env>(
                             if value.is_pointer_value() {
                                 let value_ptr = value.into_pointer_value();
 
-                                let then_block = env.context.append_basic_block(parent, "then");
-                                let done_block = env.context.append_basic_block(parent, "done");
-
                                 let condition =
                                     env.builder.build_is_not_null(value_ptr, "box_is_not_null");
-                                env.builder
-                                    .build_conditional_branch(condition, then_block, done_block);
 
-                                {
-                                    env.builder.position_at_end(then_block);
+                                build_if_no_else(env, parent, condition, "decref_box", || {
                                     let refcount_ptr =
                                         PointerToRefcount::from_ptr_to_data(env, value_ptr);
                                     refcount_ptr.decrement(env, layout);
-
-                                    env.builder.build_unconditional_branch(done_block);
-                                }
-
-                                env.builder.position_at_end(done_block);
+                                });
                             } else {
                                 eprint!("we're likely leaking memory; see issue #985 for details");
                             }
@@ -3174,6 +3270,21 @@ struct SwitchArgsIr<'a, 'ctx> {
     pub branches: &'a [(u64, BranchInfo<'a>, roc_mono::ir::Stmt<'a>)],
     pub default_branch: &'a roc_mono::ir::Stmt<'a>,
     pub ret_type: BasicTypeEnum<'ctx>,
+    /// The union whose tag id is the condition, if known. Then the condition can only be one of
+    /// its tag ids, and any other value is unreachable.
+    pub matched_union: Option<UnionLayout<'a>>,
+}
+
+/// The tag ids of `union_layout` that none of the branches match, so they take the default branch
+fn default_tag_ids(
+    union_layout: &UnionLayout,
+    branches: &[(u64, BranchInfo, roc_mono::ir::Stmt)],
+) -> std::vec::Vec<u64> {
+    let n_tags = union_layout.number_of_tags() as u64;
+
+    (0..n_tags)
+        .filter(|tag_id| !branches.iter().any(|(int, _, _)| int == tag_id))
+        .collect()
 }
 
 fn const_i128<'a, 'ctx, 'env>(env: &Env<'a, 'ctx, 'env>, value: i128) -> IntValue<'ctx> {
@@ -3202,6 +3313,121 @@ fn const_u128<'a, 'ctx, 'env>(env: &Env<'a, 'ctx, 'env>, value: u128) -> IntValu
         .const_int_arbitrary_precision(&[a, b])
 }
 
+/// Name the instruction that computes a binding after its Roc symbol, to make the IR readable.
+/// Constants can't be named, and generated symbols have no readable name, so those values keep
+/// the name (if any) given by the code that built them.
+fn name_value_after_symbol<'a, 'ctx, 'env>(
+    env: &Env<'a, 'ctx, 'env>,
+    value: BasicValueEnum<'ctx>,
+    symbol: Symbol,
+) {
+    if value.as_instruction_value().is_none() {
+        return;
+    }
+
+    let name = env
+        .interns
+        .all_ident_ids
+        .get(&symbol.module_id())
+        .and_then(|ident_ids| ident_ids.get_name(symbol.ident_id()));
+
+    if let Some(name) = name {
+        if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            value.set_name(name);
+        }
+    }
+}
+
+/// A branch that only binds a literal and immediately returns it or jumps with it
+fn trivial_literal_branch<'a, 'b>(
+    stmt: &'b roc_mono::ir::Stmt<'a>,
+) -> Option<(
+    Symbol,
+    &'b roc_mono::ir::Literal<'a>,
+    &'b Layout<'a>,
+    &'b roc_mono::ir::Stmt<'a>,
+)> {
+    use roc_mono::ir::{Expr, Literal, Stmt};
+
+    match stmt {
+        // string literals are not free to build, so they don't count as trivial
+        Stmt::Let(symbol, Expr::Literal(literal), layout, cont)
+            if !matches!(literal, Literal::Str(_)) =>
+        {
+            match cont {
+                Stmt::Ret(ret_symbol) if ret_symbol == symbol => {
+                    Some((*symbol, literal, layout, cont))
+                }
+                Stmt::Jump(_, [arg_symbol]) if arg_symbol == symbol => {
+                    Some((*symbol, literal, layout, cont))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Compile `if c then 1 else 2` to a `select` rather than branching to two new basic blocks.
+/// Only applies when both branches just produce a literal and continue in the same way.
+#[allow(clippy::too_many_arguments)]
+fn build_switch_select<'a, 'ctx, 'env>(
+    env: &Env<'a, 'ctx, 'env>,
+    layout_ids: &mut LayoutIds<'a>,
+    func_spec_solutions: &FuncSpecSolutions,
+    scope: &mut Scope<'a, 'ctx>,
+    parent: FunctionValue<'ctx>,
+    cond_layout: Layout<'a>,
+    cond_symbol: Symbol,
+    branches: &'a [(u64, BranchInfo<'a>, roc_mono::ir::Stmt<'a>)],
+    default_branch: &'a roc_mono::ir::Stmt<'a>,
+) -> Option<BasicValueEnum<'ctx>> {
+    use roc_mono::ir::Stmt;
+
+    let (true_branch, false_branch) = match (cond_layout, branches) {
+        (Layout::Builtin(Builtin::Bool), [(1, _, true_branch)]) => (true_branch, default_branch),
+        (Layout::Builtin(Builtin::Bool), [(0, _, false_branch)]) => (default_branch, false_branch),
+        _ => return None,
+    };
+
+    let (true_symbol, true_literal, layout, true_cont) = trivial_literal_branch(true_branch)?;
+    let (_, false_literal, false_layout, false_cont) = trivial_literal_branch(false_branch)?;
+
+    let same_cont = match (true_cont, false_cont) {
+        (Stmt::Ret(_), Stmt::Ret(_)) => true,
+        (Stmt::Jump(true_id, _), Stmt::Jump(false_id, _)) => true_id == false_id,
+        _ => false,
+    };
+
+    if layout != false_layout || !same_cont {
+        return None;
+    }
+
+    let cond = load_symbol(scope, &cond_symbol).into_int_value();
+    let true_value = build_exp_literal(env, parent, layout, true_literal);
+    let false_value = build_exp_literal(env, parent, layout, false_literal);
+
+    let selected = env
+        .builder
+        .build_select(cond, true_value, false_value, "select");
+
+    // continue as the true branch would, with the selected value in place of its literal
+    scope.insert(true_symbol, (*layout, selected));
+
+    let result = build_exp_stmt(
+        env,
+        layout_ids,
+        func_spec_solutions,
+        scope,
+        parent,
+        true_cont,
+    );
+
+    scope.remove(&true_symbol);
+
+    Some(result)
+}
+
 fn build_switch_ir<'a, 'ctx, 'env>(
     env: &Env<'a, 'ctx, 'env>,
     layout_ids: &mut LayoutIds<'a>,
@@ -3219,6 +3445,7 @@ fn build_switch_ir<'a, 'ctx, 'env>(
         mut cond_layout,
         default_branch,
         ret_type,
+        mut matched_union,
         ..
     } = switch_args;
 
@@ -3257,6 +3484,7 @@ fn build_switch_ir<'a, 'ctx, 'env>(
         }
         Layout::Union(variant) => {
             cond_layout = variant.tag_id_layout();
+            matched_union = Some(variant);
 
             get_tag_id(env, parent, &variant, cond_value)
         }
@@ -3320,19 +3548,21 @@ fn build_switch_ir<'a, 'ctx, 'env>(
         let default_block = context.append_basic_block(parent, "default");
         let mut cases = Vec::with_capacity_in(branches.len(), arena);
 
-        for (int, _, _) in branches.iter() {
-            // Switch constants must all be same type as switch value!
-            // e.g. this is incorrect, and will trigger a LLVM warning:
-            //
-            //   switch i8 %apple1, label %default [
-            //     i64 2, label %branch2
-            //     i64 0, label %branch0
-            //     i64 1, label %branch1
-            //   ]
-            //
-            // they either need to all be i8, or i64
-            let condition_int_type = cond.get_type();
+        // Switch constants must all be same type as switch value!
+        // e.g. this is incorrect, and will trigger a LLVM warning:
+        //
+        //   switch i8 %apple1, label %default [
+        //     i64 2, label %branch2
+        //     i64 0, label %branch0
+        //     i64 1, label %branch1
+        //   ]
+        //
+        // they either need to all be i8, or i64.
+        // The condition was built at the width of its layout above (e.g. an i8 tag id),
+        // so that is the width for the cases too.
+        let condition_int_type = cond.get_type();
 
+        for (int, _, _) in branches.iter() {
             let int_val = if condition_int_type == context.i128_type() {
                 const_i128(env, *int as i128)
             } else {
@@ -3344,7 +3574,28 @@ fn build_switch_ir<'a, 'ctx, 'env>(
             cases.push((int_val, block));
         }
 
-        builder.build_switch(cond, default_block, &cases);
+        // When we know all the tag ids, the tags without a branch of their own become explicit
+        // cases of the default branch, and LLVM's default (any other value) is unreachable.
+        // (If every tag has a branch, the default block itself is the unreachable one.)
+        let default_tag_ids = matched_union.map(|union| default_tag_ids(&union, branches));
+        let switch_default_block = match &default_tag_ids {
+            Some(tag_ids) if !tag_ids.is_empty() => {
+                for tag_id in tag_ids {
+                    let int_val = condition_int_type.const_int(*tag_id, false);
+                    cases.push((int_val, default_block));
+                }
+
+                context.append_basic_block(parent, "unreachable_default")
+            }
+            _ => default_block,
+        };
+
+        builder.build_switch(cond, switch_default_block, &cases);
+
+        if switch_default_block != default_block {
+            builder.position_at_end(switch_default_block);
+            builder.build_unreachable();
+        }
 
         for ((_, _, branch_expr), (_, block)) in branches.iter().zip(cases) {
             builder.position_at_end(block);
@@ -3367,18 +3618,23 @@ fn build_switch_ir<'a, 'ctx, 'env>(
         // The block for the conditional's default branch.
         builder.position_at_end(default_block);
 
-        let default_val = build_exp_stmt(
-            env,
-            layout_ids,
-            func_spec_solutions,
-            scope,
-            parent,
-            default_branch,
-        );
+        if default_tag_ids.map_or(false, |tag_ids| tag_ids.is_empty()) {
+            // Every tag has its own branch, so nothing jumps here
+            builder.build_unreachable();
+        } else {
+            let default_val = build_exp_stmt(
+                env,
+                layout_ids,
+                func_spec_solutions,
+                scope,
+                parent,
+                default_branch,
+            );
 
-        if default_block.get_terminator().is_none() {
-            builder.build_unconditional_branch(cont_block);
-            incoming.push((default_val, default_block));
+            if default_block.get_terminator().is_none() {
+                builder.build_unconditional_branch(cont_block);
+                incoming.push((default_val, default_block));
+            }
         }
     }
 
@@ -3402,6 +3658,101 @@ fn build_switch_ir<'a, 'ctx, 'env>(
     }
 }
 
+/// Run the code generated by `build_then` only if `condition` holds, and continue after it either way.
+/// This is an `if` without an `else`, so the then-branch is only there for its side effects.
+/// The builder is left positioned in the block after the `if`.
+pub(crate) fn build_if_no_else<'a, 'ctx, 'env, ThenFn>(
+    env: &Env<'a, 'ctx, 'env>,
+    parent: FunctionValue<'ctx>,
+    condition: IntValue<'ctx>,
+    name: &str,
+    build_then: ThenFn,
+) where
+    ThenFn: FnOnce(),
+{
+    let builder = env.builder;
+    let context = env.context;
+
+    let then_block = context.append_basic_block(parent, name);
+    let cont_block = context.append_basic_block(parent, &format!("{}_cont", name));
+
+    builder.build_conditional_branch(condition, then_block, cont_block);
+
+    builder.position_at_end(then_block);
+
+    build_then();
+
+    builder.build_unconditional_branch(cont_block);
+
+    builder.position_at_end(cont_block);
+}
+
+/// Build a while-style loop whose loop-carried values are phi nodes rather than allocas,
+/// so they can stay in registers without relying on mem2reg.
+///
+/// `init` holds the values on entry to the loop. Each iteration, `cond` gets the current values
+/// and decides whether to run the body, and `body` returns the values for the next iteration.
+/// Returns the values the loop exits with. The builder is left positioned after the loop.
+pub(crate) fn build_loop<'a, 'ctx, 'env, CondFn, BodyFn>(
+    env: &Env<'a, 'ctx, 'env>,
+    parent: FunctionValue<'ctx>,
+    init: &[BasicValueEnum<'ctx>],
+    mut cond: CondFn,
+    mut body: BodyFn,
+) -> Vec<'a, BasicValueEnum<'ctx>>
+where
+    CondFn: FnMut(&[BasicValueEnum<'ctx>]) -> IntValue<'ctx>,
+    BodyFn: FnMut(&[BasicValueEnum<'ctx>]) -> Vec<'a, BasicValueEnum<'ctx>>,
+{
+    let builder = env.builder;
+    let context = env.context;
+
+    let entry_block = builder.get_insert_block().unwrap();
+    let header_block = context.append_basic_block(parent, "loop_header");
+    let body_block = context.append_basic_block(parent, "loop_body");
+    let exit_block = context.append_basic_block(parent, "loop_exit");
+
+    builder.build_unconditional_branch(header_block);
+    builder.position_at_end(header_block);
+
+    let mut phis = Vec::with_capacity_in(init.len(), env.arena);
+    let mut current = Vec::with_capacity_in(init.len(), env.arena);
+
+    for init_value in init {
+        let phi = builder.build_phi(init_value.get_type(), "loop_var");
+        phi.add_incoming(&[(init_value, entry_block)]);
+
+        current.push(phi.as_basic_value());
+        phis.push(phi);
+    }
+
+    let keep_going = cond(&current);
+    builder.build_conditional_branch(keep_going, body_block, exit_block);
+
+    builder.position_at_end(body_block);
+
+    let next = body(&current);
+
+    debug_assert_eq!(
+        next.len(),
+        phis.len(),
+        "The loop body must produce a new value for every loop variable"
+    );
+
+    // The body may have created blocks of its own, so the back edge comes from wherever it ended
+    let latch_block = builder.get_insert_block().unwrap();
+
+    for (phi, next_value) in phis.iter().zip(next.iter()) {
+        phi.add_incoming(&[(next_value, latch_block)]);
+    }
+
+    builder.build_unconditional_branch(header_block);
+
+    builder.position_at_end(exit_block);
+
+    current
+}
+
 /// Creates a new stack allocation instruction in the entry block of the function.
 pub fn create_entry_block_alloca<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
@@ -3409,7 +3760,7 @@ pub fn create_entry_block_alloca<'a, 'ctx>(
     basic_type: BasicTypeEnum<'ctx>,
     name: &str,
 ) -> PointerValue<'ctx> {
-    let builder = env.context.create_builder();
+    let builder = env.alloca_builder;
     let entry = parent.get_first_basic_block().unwrap();
 
     match entry.get_first_instruction() {
@@ -4574,9 +4925,34 @@ fn build_procedures_help<'a, 'ctx, 'env>(
         }
     }
 
+    // Every function passed on its own, but the module can still be broken as a whole,
+    // e.g. a function that is declared with internal linkage but never defined.
+    // Callers verify the module again once it is optimized, so this early check is only done in debug builds.
+    if cfg!(debug_assertions) {
+        if let Err(errors) = verify_module(env.module) {
+            match debug_output_file {
+                Some(app_ll_file) => {
+                    env.module.print_to_file(&app_ll_file).unwrap();
+
+                    panic!(
+                        "😱 LLVM errors when defining module; I wrote the full LLVM IR to {:?}\n\n{}",
+                        app_ll_file, errors,
+                    );
+                }
+                None => panic!("Errors defining module:\n\n{}", errors),
+            }
+        }
+    }
+
     mod_solutions
 }
 
+/// Check the module as a whole, not just function by function like `FunctionValue::verify`.
+/// Returns LLVM's diagnostics on failure, so the caller can decide how to report them.
+pub fn verify_module(module: &Module<'_>) -> Result<(), String> {
+    module.verify().map_err(|errors| errors.to_string())
+}
+
 fn func_spec_name<'a>(
     arena: &'a Bump,
     interns: &Interns,
@@ -4663,6 +5039,23 @@ fn build_proc_header<'a, 'ctx, 'env>(
     fn_val
 }
 
+/// Procs are declared with internal linkage by `build_proc_header`. Give one external linkage,
+/// e.g. `main`, so it can be called by whatever links the generated module.
+pub fn export_proc(fn_val: FunctionValue<'_>) {
+    fn_val.set_linkage(Linkage::External);
+}
+
+/// The functions that this module defines and exposes to the linker,
+/// as opposed to external declarations like `roc_alloc` that the host defines.
+pub fn exported_functions<'ctx>(module: &Module<'ctx>) -> std::vec::Vec<FunctionValue<'ctx>> {
+    module
+        .get_functions()
+        .filter(|fn_val| {
+            fn_val.get_linkage() == Linkage::External && fn_val.count_basic_blocks() > 0
+        })
+        .collect()
+}
+
 #[allow(clippy::too_many_arguments)]
 fn expose_alias_to_host<'a, 'ctx, 'env>(
     env: &'a Env<'a, 'ctx, 'env>,
@@ -6849,163 +7242,161 @@ fn build_foreign_symbol<'a, 'ctx, 'env>(
     argument_symbols: &[Symbol],
     ret_layout: &Layout<'a>,
 ) -> BasicValueEnum<'ctx> {
-    let builder = env.builder;
-    let context = env.context;
+    let mut arguments = Vec::with_capacity_in(argument_symbols.len(), env.arena);
+    let mut argument_layouts = Vec::with_capacity_in(argument_symbols.len(), env.arena);
 
-    let fastcc_function_name = format!("{}_fastcc_wrapper", foreign.as_str());
-
-    let (fastcc_function, arguments) = match env.module.get_function(fastcc_function_name.as_str())
-    {
-        Some(function_value) => {
-            let mut arguments = Vec::with_capacity_in(argument_symbols.len(), env.arena);
-
-            for symbol in argument_symbols {
-                let (value, _) = load_symbol_and_layout(scope, symbol);
-
-                arguments.push(value);
-            }
+    for symbol in argument_symbols {
+        let (value, layout) = load_symbol_and_layout(scope, symbol);
 
-            (function_value, arguments)
-        }
-        None => {
-            // Here we build two functions:
-            //
-            // - an C_CALL_CONV extern that will be provided by the host, e.g. `roc_fx_putLine`
-            //      This is just a type signature that we make available to the linker,
-            //      and can use in the wrapper
-            // - a FAST_CALL_CONV wrapper that we make here, e.g. `roc_fx_putLine_fastcc_wrapper`
+        arguments.push(value);
+        argument_layouts.push(*layout);
+    }
 
-            let return_type = basic_type_from_layout(env, ret_layout);
-            let roc_return = RocReturn::from_layout(env, ret_layout);
-            let cc_return = to_cc_return(env, ret_layout);
+    let fastcc_function_name = format!("{}_fastcc_wrapper", foreign.as_str());
 
-            let mut cc_argument_types =
-                Vec::with_capacity_in(argument_symbols.len() + 1, env.arena);
-            let mut fastcc_argument_types =
-                Vec::with_capacity_in(argument_symbols.len(), env.arena);
-            let mut arguments = Vec::with_capacity_in(argument_symbols.len(), env.arena);
+    let fastcc_function = match env.module.get_function(fastcc_function_name.as_str()) {
+        Some(function_value) => function_value,
+        None => build_foreign_symbol_wrapper(env, foreign, &argument_layouts, ret_layout),
+    };
 
-            for symbol in argument_symbols {
-                let (value, layout) = load_symbol_and_layout(scope, symbol);
+    call_roc_function(env, fastcc_function, ret_layout, &arguments)
+}
 
-                cc_argument_types.push(to_cc_type(env, layout));
+/// Builds two functions:
+///
+/// - an C_CALL_CONV extern that will be provided by the host, e.g. `roc_fx_putLine`
+///      This is just a type signature that we make available to the linker,
+///      and can use in the wrapper
+/// - a FAST_CALL_CONV wrapper that we make here, e.g. `roc_fx_putLine_fastcc_wrapper`
+///
+/// and returns the wrapper, which roc code calls like any other roc function.
+pub fn build_foreign_symbol_wrapper<'a, 'ctx, 'env>(
+    env: &Env<'a, 'ctx, 'env>,
+    foreign: &roc_module::ident::ForeignSymbol,
+    argument_layouts: &[Layout<'a>],
+    ret_layout: &Layout<'a>,
+) -> FunctionValue<'ctx> {
+    let builder = env.builder;
+    let context = env.context;
 
-                let basic_type = argument_type_from_layout(env, layout);
-                fastcc_argument_types.push(basic_type);
+    let fastcc_function_name = format!("{}_fastcc_wrapper", foreign.as_str());
 
-                arguments.push(value);
-            }
+    let return_type = basic_type_from_layout(env, ret_layout);
+    let roc_return = RocReturn::from_layout(env, ret_layout);
+    let cc_return = to_cc_return(env, ret_layout);
 
-            let cc_type =
-                FunctionSpec::cconv(env, cc_return, Some(return_type), &cc_argument_types);
-            let cc_function = get_foreign_symbol(env, foreign.clone(), cc_type);
+    let cc_argument_types = Vec::from_iter_in(
+        argument_layouts
+            .iter()
+            .map(|layout| to_cc_type(env, layout)),
+        env.arena,
+    );
+    let fastcc_argument_types = Vec::from_iter_in(
+        argument_layouts
+            .iter()
+            .map(|layout| argument_type_from_layout(env, layout)),
+        env.arena,
+    );
 
-            let fastcc_type =
-                FunctionSpec::fastcc(env, roc_return, return_type, fastcc_argument_types);
+    let cc_function = declare_foreign_symbol(env, foreign.clone(), argument_layouts, ret_layout);
 
-            let fastcc_function = add_func(
-                env.context,
-                env.module,
-                &fastcc_function_name,
-                fastcc_type,
-                Linkage::Internal,
-            );
+    let fastcc_type = FunctionSpec::fastcc(env, roc_return, return_type, fastcc_argument_types);
 
-            let old = builder.get_insert_block().unwrap();
+    let fastcc_function = add_func(
+        env.context,
+        env.module,
+        &fastcc_function_name,
+        fastcc_type,
+        Linkage::Internal,
+    );
 
-            let entry = context.append_basic_block(fastcc_function, "entry");
-            {
-                builder.position_at_end(entry);
+    let old = builder.get_insert_block().unwrap();
 
-                let mut fastcc_parameters = fastcc_function.get_params();
-                let mut cc_arguments =
-                    Vec::with_capacity_in(fastcc_parameters.len() + 1, env.arena);
+    let entry = context.append_basic_block(fastcc_function, "entry");
+    {
+        builder.position_at_end(entry);
 
-                let return_pointer = match roc_return {
-                    RocReturn::Return => env.builder.build_alloca(return_type, "return_value"),
-                    RocReturn::ByPointer => fastcc_parameters.pop().unwrap().into_pointer_value(),
-                };
+        let mut fastcc_parameters = fastcc_function.get_params();
+        let mut cc_arguments = Vec::with_capacity_in(fastcc_parameters.len() + 1, env.arena);
 
-                if let CCReturn::ByPointer = cc_return {
-                    cc_arguments.push(return_pointer.into());
-                }
+        let return_pointer = match roc_return {
+            RocReturn::Return => env.builder.build_alloca(return_type, "return_value"),
+            RocReturn::ByPointer => fastcc_parameters.pop().unwrap().into_pointer_value(),
+        };
 
-                let it = fastcc_parameters.into_iter().zip(cc_argument_types.iter());
-                for (param, cc_type) in it {
-                    if param.get_type() == *cc_type {
-                        cc_arguments.push(param.into());
-                    } else {
-                        // not pretty, but seems to cover all our current case
-                        if cc_type.is_pointer_type() && !param.get_type().is_pointer_type() {
-                            // we need to pass this value by-reference; put it into an alloca
-                            // and bitcast the reference
-
-                            let param_alloca =
-                                env.builder.build_alloca(param.get_type(), "param_alloca");
-                            env.builder.build_store(param_alloca, param);
-
-                            let as_cc_type = env.builder.build_bitcast(
-                                param_alloca,
-                                cc_type.into_pointer_type(),
-                                "to_cc_type_ptr",
-                            );
+        if let CCReturn::ByPointer = cc_return {
+            cc_arguments.push(return_pointer.into());
+        }
 
-                            cc_arguments.push(as_cc_type.into());
-                        } else {
-                            // eprintln!("C type: {:?}", cc_type);
-                            // eprintln!("Fastcc type: {:?}", param.get_type());
-                            // todo!("C <-> Fastcc interaction that we haven't seen before")
+        let it = fastcc_parameters.into_iter().zip(cc_argument_types.iter());
+        for (param, cc_type) in it {
+            if param.get_type() == *cc_type {
+                cc_arguments.push(param.into());
+            } else {
+                // not pretty, but seems to cover all our current case
+                if cc_type.is_pointer_type() && !param.get_type().is_pointer_type() {
+                    // we need to pass this value by-reference; put it into an alloca
+                    // and bitcast the reference
+
+                    let param_alloca = env.builder.build_alloca(param.get_type(), "param_alloca");
+                    env.builder.build_store(param_alloca, param);
+
+                    let as_cc_type = env.builder.build_bitcast(
+                        param_alloca,
+                        cc_type.into_pointer_type(),
+                        "to_cc_type_ptr",
+                    );
 
-                            let as_cc_type = env.builder.build_pointer_cast(
-                                param.into_pointer_value(),
-                                cc_type.into_pointer_type(),
-                                "to_cc_type_ptr",
-                            );
-                            cc_arguments.push(as_cc_type.into());
-                        }
-                    }
+                    cc_arguments.push(as_cc_type.into());
+                } else {
+                    // eprintln!("C type: {:?}", cc_type);
+                    // eprintln!("Fastcc type: {:?}", param.get_type());
+                    // todo!("C <-> Fastcc interaction that we haven't seen before")
+
+                    let as_cc_type = env.builder.build_pointer_cast(
+                        param.into_pointer_value(),
+                        cc_type.into_pointer_type(),
+                        "to_cc_type_ptr",
+                    );
+                    cc_arguments.push(as_cc_type.into());
                 }
+            }
+        }
 
-                let call = env.builder.build_call(cc_function, &cc_arguments, "tmp");
-                call.set_call_convention(C_CALL_CONV);
+        let call = env.builder.build_call(cc_function, &cc_arguments, "tmp");
+        call.set_call_convention(C_CALL_CONV);
 
-                match roc_return {
-                    RocReturn::Return => {
-                        let return_value = match cc_return {
-                            CCReturn::Return => call.try_as_basic_value().left().unwrap(),
+        match roc_return {
+            RocReturn::Return => {
+                let return_value = match cc_return {
+                    CCReturn::Return => call.try_as_basic_value().left().unwrap(),
 
-                            CCReturn::ByPointer => {
-                                env.builder.build_load(return_pointer, "read_result")
-                            }
-                            CCReturn::Void => return_type.const_zero(),
-                        };
+                    CCReturn::ByPointer => env.builder.build_load(return_pointer, "read_result"),
+                    CCReturn::Void => return_type.const_zero(),
+                };
 
-                        builder.build_return(Some(&return_value));
+                builder.build_return(Some(&return_value));
+            }
+            RocReturn::ByPointer => {
+                match cc_return {
+                    CCReturn::Return => {
+                        let result = call.try_as_basic_value().left().unwrap();
+                        env.builder.build_store(return_pointer, result);
                     }
-                    RocReturn::ByPointer => {
-                        match cc_return {
-                            CCReturn::Return => {
-                                let result = call.try_as_basic_value().left().unwrap();
-                                env.builder.build_store(return_pointer, result);
-                            }
-
-                            CCReturn::ByPointer | CCReturn::Void => {
-                                // the return value (if any) is already written to the return pointer
-                            }
-                        }
 
-                        builder.build_return(None);
+                    CCReturn::ByPointer | CCReturn::Void => {
+                        // the return value (if any) is already written to the return pointer
                     }
                 }
-            }
-
-            builder.position_at_end(old);
 
-            (fastcc_function, arguments)
+                builder.build_return(None);
+            }
         }
-    };
+    }
 
-    call_roc_function(env, fastcc_function, ret_layout, &arguments)
+    builder.position_at_end(old);
+
+    fastcc_function
 }
 
 fn throw_on_overflow<'a, 'ctx, 'env>(
@@ -7057,7 +7448,6 @@ fn build_int_binop<'a, 'ctx, 'env>(
     rhs: IntValue<'ctx>,
     op: LowLevel,
 ) -> BasicValueEnum<'ctx> {
-    use inkwell::IntPredicate::*;
     use roc_module::low_level::LowLevel::*;
 
     let bd = env.builder;
@@ -7119,33 +7509,11 @@ fn build_int_binop<'a, 'ctx, 'env>(
             &LLVM_MUL_WITH_OVERFLOW[int_width],
             &[lhs.into(), rhs.into()],
         ),
-        NumGt => {
-            if int_width.is_signed() {
-                bd.build_int_compare(SGT, lhs, rhs, "gt_int").into()
-            } else {
-                bd.build_int_compare(UGT, lhs, rhs, "gt_uint").into()
-            }
-        }
-        NumGte => {
-            if int_width.is_signed() {
-                bd.build_int_compare(SGE, lhs, rhs, "gte_int").into()
-            } else {
-                bd.build_int_compare(UGE, lhs, rhs, "gte_uint").into()
-            }
-        }
-        NumLt => {
-            if int_width.is_signed() {
-                bd.build_int_compare(SLT, lhs, rhs, "lt_int").into()
-            } else {
-                bd.build_int_compare(ULT, lhs, rhs, "lt_uint").into()
-            }
-        }
-        NumLte => {
-            if int_width.is_signed() {
-                bd.build_int_compare(SLE, lhs, rhs, "lte_int").into()
-            } else {
-                bd.build_int_compare(ULE, lhs, rhs, "lte_uint").into()
-            }
+        NumGt | NumGte | NumLt | NumLte => {
+            let compare_op = op.compare_op().unwrap();
+            let predicate = to_int_predicate(compare_op, int_width.is_signed());
+            bd.build_int_compare(predicate, lhs, rhs, "compare_int")
+                .into()
         }
         NumRemUnchecked => {
             if int_width.is_signed() {
@@ -7324,7 +7692,6 @@ fn build_float_binop<'a, 'ctx, 'env>(
     rhs: FloatValue<'ctx>,
     op: LowLevel,
 ) -> BasicValueEnum<'ctx> {
-    use inkwell::FloatPredicate::*;
     use roc_module::low_level::LowLevel::*;
 
     let bd = env.builder;
@@ -7341,10 +7708,8 @@ fn build_float_binop<'a, 'ctx, 'env>(
                     .into_int_value();
             let is_infinite = bd.build_not(is_finite, "negate");
 
-            let struct_type = context.struct_type(
-                &[context.f64_type().into(), context.bool_type().into()],
-                false,
-            );
+            let struct_type =
+                context.struct_type(&[lhs.get_type().into(), context.bool_type().into()], false);
 
             let struct_value = {
                 let v1 = struct_type.const_zero();
@@ -7370,10 +7735,8 @@ fn build_float_binop<'a, 'ctx, 'env>(
                     .into_int_value();
             let is_infinite = bd.build_not(is_finite, "negate");
 
-            let struct_type = context.struct_type(
-                &[context.f64_type().into(), context.bool_type().into()],
-                false,
-            );
+            let struct_type =
+                context.struct_type(&[lhs.get_type().into(), context.bool_type().into()], false);
 
             let struct_value = {
                 let v1 = struct_type.const_zero();
@@ -7400,10 +7763,8 @@ fn build_float_binop<'a, 'ctx, 'env>(
                     .into_int_value();
             let is_infinite = bd.build_not(is_finite, "negate");
 
-            let struct_type = context.struct_type(
-                &[context.f64_type().into(), context.bool_type().into()],
-                false,
-            );
+            let struct_type =
+                context.struct_type(&[lhs.get_type().into(), context.bool_type().into()], false);
 
             let struct_value = {
                 let v1 = struct_type.const_zero();
@@ -7418,10 +7779,11 @@ fn build_float_binop<'a, 'ctx, 'env>(
             struct_value.into()
         }
         NumMulWrap => unreachable!("wrapping multiplication is not defined on floats"),
-        NumGt => bd.build_float_compare(OGT, lhs, rhs, "float_gt").into(),
-        NumGte => bd.build_float_compare(OGE, lhs, rhs, "float_gte").into(),
-        NumLt => bd.build_float_compare(OLT, lhs, rhs, "float_lt").into(),
-        NumLte => bd.build_float_compare(OLE, lhs, rhs, "float_lte").into(),
+        NumGt | NumGte | NumLt | NumLte => {
+            let predicate = to_float_predicate(op.compare_op().unwrap(), true);
+            bd.build_float_compare(predicate, lhs, rhs, "compare_float")
+                .into()
+        }
         NumDivFrac => bd.build_float_div(lhs, rhs, "div_float").into(),
         NumPow => env.call_intrinsic(&LLVM_POW[float_width], &[lhs.into(), rhs.into()]),
         _ => {
@@ -8103,85 +8465,39 @@ fn define_global_str_literal<'a, 'ctx, 'env>(
     env: &Env<'a, 'ctx, 'env>,
     message: &str,
 ) -> inkwell::values::GlobalValue<'ctx> {
-    let module = env.module;
-
-    // hash the name so we don't re-define existing messages
-    let name = {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-        message.hash(&mut hasher);
-        let hash = hasher.finish();
-
-        format!("_str_literal_{}", hash)
-    };
-
-    match module.get_global(&name) {
-        Some(current) => current,
-
-        None => {
-            let size = message.bytes().len() + env.target_info.ptr_width() as usize;
-            let mut bytes = Vec::with_capacity_in(size, env.arena);
-
-            // insert NULL bytes for the refcount
-            for _ in 0..env.target_info.ptr_width() as usize {
-                bytes.push(env.context.i8_type().const_zero());
-            }
-
-            // then add the data bytes
-            for b in message.bytes() {
-                bytes.push(env.context.i8_type().const_int(b as u64, false));
-            }
-
-            // use None for the address space (e.g. Const does not work)
-            let typ = env.context.i8_type().array_type(bytes.len() as u32);
-            let global = module.add_global(typ, None, &name);
+    let ptr_width = env.target_info.ptr_width() as usize;
+    let mut bytes = Vec::with_capacity_in(ptr_width + message.len(), env.arena);
 
-            global.set_initializer(&env.context.i8_type().const_array(bytes.into_bump_slice()));
+    // NULL bytes for the refcount, then the data bytes.
+    // We cannot use `build_global_string` because it assumes strings are NULL-terminated,
+    // which means we can't store the refcount (which is 8 NULL bytes)
+    bytes.extend(std::iter::repeat(0).take(ptr_width));
+    bytes.extend_from_slice(message.as_bytes());
 
-            // mimic the `global_string` function; we cannot use it directly because it assumes
-            // strings are NULL-terminated, which means we can't store the refcount (which is 8
-            // NULL bytes)
-            global.set_constant(true);
-            global.set_alignment(env.target_info.ptr_width() as u32);
-            global.set_unnamed_addr(true);
-            global.set_linkage(inkwell::module::Linkage::Private);
+    let global = env.build_global_bytes(&bytes, "_str_literal");
+    global.set_alignment(ptr_width as u32);
 
-            global
-        }
-    }
+    global
 }
 
 fn define_global_error_str<'a, 'ctx, 'env>(
     env: &Env<'a, 'ctx, 'env>,
     message: &str,
 ) -> inkwell::values::GlobalValue<'ctx> {
-    let module = env.module;
+    let mut bytes = Vec::with_capacity_in(message.len() + 1, env.arena);
 
-    // hash the name so we don't re-define existing messages
-    let name = {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-        message.hash(&mut hasher);
-        let hash = hasher.finish();
-
-        format!("_Error_message_{}", hash)
-    };
+    // the message is passed to roc_panic as a NULL-terminated string
+    bytes.extend_from_slice(message.as_bytes());
+    bytes.push(0);
 
-    match module.get_global(&name) {
-        Some(current) => current,
-        None => unsafe { env.builder.build_global_string(message, name.as_str()) },
-    }
+    env.build_global_bytes(&bytes, "_Error_message")
 }
 
 fn throw_exception<'a, 'ctx, 'env>(env: &Env<'a, 'ctx, 'env>, message: &str) {
     let builder = env.builder;
 
     // define the error message as a global
-    // (a hash is used such that the same value is not defined repeatedly)
+    // (identical messages share the same global)
     let error_msg_global = define_global_error_str(env, message);
 
     let cast = env
@@ -8198,6 +8514,30 @@ fn throw_exception<'a, 'ctx, 'env>(env: &Env<'a, 'ctx, 'env>, message: &str) {
     builder.build_unreachable();
 }
 
+/// Declare a host-provided function (e.g. `roc_fx_stdoutLine`) up front, with external linkage
+/// and no body, so calls to it resolve before any proc is built.
+/// The signature matches what `build_foreign_symbol` would declare for the same layouts.
+pub fn declare_foreign_symbol<'a, 'ctx, 'env>(
+    env: &Env<'a, 'ctx, 'env>,
+    foreign_symbol: roc_module::ident::ForeignSymbol,
+    argument_layouts: &[Layout<'a>],
+    ret_layout: &Layout<'a>,
+) -> FunctionValue<'ctx> {
+    let return_type = basic_type_from_layout(env, ret_layout);
+    let cc_return = to_cc_return(env, ret_layout);
+
+    let cc_argument_types = Vec::from_iter_in(
+        argument_layouts
+            .iter()
+            .map(|layout| to_cc_type(env, layout)),
+        env.arena,
+    );
+
+    let cc_type = FunctionSpec::cconv(env, cc_return, Some(return_type), &cc_argument_types);
+
+    get_foreign_symbol(env, foreign_symbol, cc_type)
+}
+
 fn get_foreign_symbol<'a, 'ctx, 'env>(
     env: &Env<'a, 'ctx, 'env>,
     foreign_symbol: roc_module::ident::ForeignSymbol,
@@ -1,7 +1,7 @@
 #![allow(clippy::too_many_arguments)]
 use crate::llvm::bitcode::build_dec_wrapper;
 use crate::llvm::build::{
-    allocate_with_refcount_help, cast_basic_basic, Env, RocFunctionCall, Scope,
+    allocate_with_refcount_help, build_loop, cast_basic_basic, Env, RocFunctionCall, Scope,
 };
 use crate::llvm::convert::basic_type_from_layout;
 use crate::llvm::refcounting::increment_refcount_layout;
@@ -599,8 +599,7 @@ pub(crate) fn incrementing_elem_loop<'a, 'ctx, 'env, LoopFn>(
     len: IntValue<'ctx>,
     index_name: &str,
     mut loop_fn: LoopFn,
-) -> PointerValue<'ctx>
-where
+) where
     LoopFn: FnMut(IntValue<'ctx>, BasicValueEnum<'ctx>),
 {
     let builder = env.builder;
@@ -628,46 +627,33 @@ pub(crate) fn incrementing_index_loop<'a, 'ctx, 'env, LoopFn>(
     end: IntValue<'ctx>,
     index_name: &str,
     mut loop_fn: LoopFn,
-) -> PointerValue<'ctx>
-where
+) where
     LoopFn: FnMut(IntValue<'ctx>),
 {
-    let ctx = env.context;
     let builder = env.builder;
 
     // constant 1usize
     let one = env.ptr_int().const_int(1, false);
     let zero = env.ptr_int().const_zero();
 
-    // allocate a stack slot for the current index
-    let index_alloca = builder.build_alloca(env.ptr_int(), index_name);
-    builder.build_store(index_alloca, zero);
-
-    let loop_bb = ctx.append_basic_block(parent, "loop");
-    let after_loop_bb = ctx.append_basic_block(parent, "after_loop");
-
-    let loop_end_cond = bounds_check_comparison(builder, zero, end);
-    builder.build_conditional_branch(loop_end_cond, loop_bb, after_loop_bb);
-
-    {
-        builder.position_at_end(loop_bb);
-
-        let current_index = builder.build_load(index_alloca, "index").into_int_value();
-        let next_index = builder.build_int_add(current_index, one, "next_index");
-        builder.build_store(index_alloca, next_index);
-
-        // The body of the loop
-        loop_fn(current_index);
-
+    // The index is a phi in the loop header, not a stack slot
+    build_loop(
+        env,
+        parent,
+        &[zero.into()],
         // #index < end
-        let loop_end_cond = bounds_check_comparison(builder, next_index, end);
+        |current| bounds_check_comparison(builder, current[0].into_int_value(), end),
+        |current| {
+            let current_index = current[0].into_int_value();
 
-        builder.build_conditional_branch(loop_end_cond, loop_bb, after_loop_bb);
-    }
+            // The body of the loop
+            loop_fn(current_index);
 
-    builder.position_at_end(after_loop_bb);
+            let next_index = builder.build_int_add(current_index, one, index_name);
 
-    index_alloca
+            bumpalo::vec![in env.arena; next_index.into()]
+        },
+    );
 }
 
 pub(crate) fn empty_polymorphic_list<'a, 'ctx, 'env>(
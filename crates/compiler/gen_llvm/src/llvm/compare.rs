@@ -10,12 +10,52 @@ use inkwell::values::{
 use inkwell::{AddressSpace, FloatPredicate, IntPredicate};
 use roc_builtins::bitcode;
 use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_module::low_level::CompareOp;
 use roc_module::symbol::Symbol;
 use roc_mono::layout::{Builtin, Layout, LayoutIds, UnionLayout};
 
 use super::build::{dec_binop_with_unchecked, load_roc_value, use_roc_value};
 use super::convert::argument_type_from_union_layout;
 
+/// The predicate for `build_int_compare` that compares two integers with `op`
+pub fn to_int_predicate(op: CompareOp, signed: bool) -> IntPredicate {
+    use IntPredicate::*;
+
+    match (op, signed) {
+        (CompareOp::Eq, _) => EQ,
+        (CompareOp::Ne, _) => NE,
+        (CompareOp::Lt, true) => SLT,
+        (CompareOp::Lt, false) => ULT,
+        (CompareOp::Le, true) => SLE,
+        (CompareOp::Le, false) => ULE,
+        (CompareOp::Gt, true) => SGT,
+        (CompareOp::Gt, false) => UGT,
+        (CompareOp::Ge, true) => SGE,
+        (CompareOp::Ge, false) => UGE,
+    }
+}
+
+/// The predicate for `build_float_compare` that compares two floats with `op`.
+/// An ordered comparison is false if either operand is NaN, an unordered one is true.
+pub fn to_float_predicate(op: CompareOp, ordered: bool) -> FloatPredicate {
+    use FloatPredicate::*;
+
+    match (op, ordered) {
+        (CompareOp::Eq, true) => OEQ,
+        (CompareOp::Eq, false) => UEQ,
+        (CompareOp::Ne, true) => ONE,
+        (CompareOp::Ne, false) => UNE,
+        (CompareOp::Lt, true) => OLT,
+        (CompareOp::Lt, false) => ULT,
+        (CompareOp::Le, true) => OLE,
+        (CompareOp::Le, false) => ULE,
+        (CompareOp::Gt, true) => OGT,
+        (CompareOp::Gt, false) => UGT,
+        (CompareOp::Ge, true) => OGE,
+        (CompareOp::Ge, false) => UGE,
+    }
+}
+
 #[derive(Clone, Debug)]
 enum WhenRecursive<'a> {
     Unreachable,
@@ -1415,3 +1455,42 @@ fn build_box_eq_help<'a, 'ctx, 'env>(
 
     env.builder.build_return(Some(&is_equal));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int_predicates() {
+        use IntPredicate::*;
+
+        let signed: std::vec::Vec<_> = CompareOp::ALL
+            .iter()
+            .map(|op| to_int_predicate(*op, true))
+            .collect();
+        assert_eq!(signed, [EQ, NE, SLT, SLE, SGT, SGE]);
+
+        let unsigned: std::vec::Vec<_> = CompareOp::ALL
+            .iter()
+            .map(|op| to_int_predicate(*op, false))
+            .collect();
+        assert_eq!(unsigned, [EQ, NE, ULT, ULE, UGT, UGE]);
+    }
+
+    #[test]
+    fn float_predicates() {
+        use FloatPredicate::*;
+
+        let ordered: std::vec::Vec<_> = CompareOp::ALL
+            .iter()
+            .map(|op| to_float_predicate(*op, true))
+            .collect();
+        assert_eq!(ordered, [OEQ, ONE, OLT, OLE, OGT, OGE]);
+
+        let unordered: std::vec::Vec<_> = CompareOp::ALL
+            .iter()
+            .map(|op| to_float_predicate(*op, false))
+            .collect();
+        assert_eq!(unordered, [UEQ, UNE, ULT, ULE, UGT, UGE]);
+    }
+}
@@ -1,8 +1,8 @@
 use crate::debug_info_init;
 use crate::llvm::bitcode::call_void_bitcode_fn;
 use crate::llvm::build::{
-    add_func, cast_basic_basic, get_tag_id, tag_pointer_clear_tag_id, use_roc_value, Env,
-    FAST_CALL_CONV,
+    add_func, build_if_no_else, cast_basic_basic, get_tag_id, tag_pointer_clear_tag_id,
+    use_roc_value, Env, FAST_CALL_CONV,
 };
 use crate::llvm::build_list::{incrementing_elem_loop, list_len, load_list};
 use crate::llvm::convert::{basic_type_from_layout, RocUnion};
@@ -715,49 +715,40 @@ fn modify_refcount_list_help<'a, 'ctx, 'env>(
         "len > 0",
     );
 
-    // build blocks
-    let modification_block = ctx.append_basic_block(parent, "modification_block");
-    let cont_block = ctx.append_basic_block(parent, "modify_rc_list_cont");
+    build_if_no_else(env, parent, is_non_empty, "modify_rc_list", || {
+        if element_layout.contains_refcounted(env.layout_interner) {
+            let ptr_type =
+                basic_type_from_layout(env, element_layout).ptr_type(AddressSpace::Generic);
 
-    builder.build_conditional_branch(is_non_empty, modification_block, cont_block);
+            let (len, ptr) = load_list(env.builder, original_wrapper, ptr_type);
 
-    builder.position_at_end(modification_block);
-
-    if element_layout.contains_refcounted(env.layout_interner) {
-        let ptr_type = basic_type_from_layout(env, element_layout).ptr_type(AddressSpace::Generic);
-
-        let (len, ptr) = load_list(env.builder, original_wrapper, ptr_type);
+            let loop_fn = |_index, element| {
+                modify_refcount_layout_help(
+                    env,
+                    parent,
+                    layout_ids,
+                    mode.to_call_mode(fn_val),
+                    when_recursive,
+                    element,
+                    element_layout,
+                );
+            };
 
-        let loop_fn = |_index, element| {
-            modify_refcount_layout_help(
+            incrementing_elem_loop(
                 env,
                 parent,
-                layout_ids,
-                mode.to_call_mode(fn_val),
-                when_recursive,
-                element,
-                element_layout,
+                *element_layout,
+                ptr,
+                len,
+                "modify_rc_index",
+                loop_fn,
             );
-        };
-
-        incrementing_elem_loop(
-            env,
-            parent,
-            *element_layout,
-            ptr,
-            len,
-            "modify_rc_index",
-            loop_fn,
-        );
-    }
-
-    let refcount_ptr = PointerToRefcount::from_list_wrapper(env, original_wrapper);
-    let call_mode = mode_to_call_mode(fn_val, mode);
-    refcount_ptr.modify(call_mode, layout, env);
-
-    builder.build_unconditional_branch(cont_block);
+        }
 
-    builder.position_at_end(cont_block);
+        let refcount_ptr = PointerToRefcount::from_list_wrapper(env, original_wrapper);
+        let call_mode = mode_to_call_mode(fn_val, mode);
+        refcount_ptr.modify(call_mode, layout, env);
+    });
 
     // this function returns void
     builder.build_return(None);
@@ -850,20 +841,11 @@ fn modify_refcount_str_help<'a, 'ctx, 'env>(
         "is_big_str",
     );
 
-    // the block we'll always jump to when we're done
-    let cont_block = ctx.append_basic_block(parent, "modify_rc_str_cont");
-    let modification_block = ctx.append_basic_block(parent, "modify_rc");
-
-    builder.build_conditional_branch(is_big_and_non_empty, modification_block, cont_block);
-    builder.position_at_end(modification_block);
-
-    let refcount_ptr = PointerToRefcount::from_list_wrapper(env, str_wrapper);
-    let call_mode = mode_to_call_mode(fn_val, mode);
-    refcount_ptr.modify(call_mode, layout, env);
-
-    builder.build_unconditional_branch(cont_block);
-
-    builder.position_at_end(cont_block);
+    build_if_no_else(env, parent, is_big_and_non_empty, "modify_rc_str", || {
+        let refcount_ptr = PointerToRefcount::from_list_wrapper(env, str_wrapper);
+        let call_mode = mode_to_call_mode(fn_val, mode);
+        refcount_ptr.modify(call_mode, layout, env);
+    });
 
     // this function returns void
     builder.build_return(None);
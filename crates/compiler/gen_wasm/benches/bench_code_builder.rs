@@ -0,0 +1,89 @@
+use bumpalo::Bump;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use roc_gen_wasm::wasm_module::{CodeBuilder, LocalId};
+
+// 4 instructions per group, 100k instructions in total
+const GROUPS: u32 = 25_000;
+
+fn emit_instructions(code_builder: &mut CodeBuilder) {
+    for i in 0..GROUPS {
+        code_builder.i32_const(i as i32);
+        code_builder.get_local(LocalId(i % 4));
+        code_builder.i32_add();
+        code_builder.drop_();
+    }
+
+    // leave a value behind, so the stacks have something to compare
+    code_builder.i32_const(0);
+}
+
+fn finished_bytes(code_builder: &mut CodeBuilder) -> Vec<u8> {
+    code_builder.build_fn_header_and_footer(&[], 0, None);
+
+    let mut buffer = Vec::with_capacity(code_builder.size());
+    code_builder.serialize_without_relocs(&mut buffer);
+
+    buffer
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let arena = Bump::new();
+
+    let mut blob = CodeBuilder::new(&arena);
+    emit_instructions(&mut blob);
+
+    // All paths must leave the same code, and pushing and splicing the same VM stack
+    {
+        let mut pushed = CodeBuilder::new(&arena);
+        emit_instructions(&mut pushed);
+
+        let mut spliced = CodeBuilder::new(&arena);
+        spliced.splice_inline(&blob, 0);
+
+        let mut trusted = CodeBuilder::new(&arena);
+        trusted.set_simulate_stack(false);
+        emit_instructions(&mut trusted);
+
+        assert_eq!(pushed.vm_stack_symbols(), spliced.vm_stack_symbols());
+        let pushed_bytes = finished_bytes(&mut pushed);
+        assert_eq!(pushed_bytes, finished_bytes(&mut spliced));
+        assert_eq!(pushed_bytes, finished_bytes(&mut trusted));
+    }
+
+    c.bench_function("push 100k instructions one by one", |b| {
+        b.iter(|| {
+            let arena = Bump::new();
+            let mut code_builder = CodeBuilder::new(&arena);
+
+            emit_instructions(&mut code_builder);
+
+            black_box(code_builder.size());
+        })
+    });
+
+    c.bench_function("push 100k instructions without stack simulation", |b| {
+        b.iter(|| {
+            let arena = Bump::new();
+            let mut code_builder = CodeBuilder::new(&arena);
+            code_builder.set_simulate_stack(false);
+
+            emit_instructions(&mut code_builder);
+
+            black_box(code_builder.size());
+        })
+    });
+
+    c.bench_function("splice 100k instructions", |b| {
+        b.iter(|| {
+            let arena = Bump::new();
+            let mut code_builder = CodeBuilder::new(&arena);
+
+            code_builder.splice_inline(&blob, 0);
+
+            black_box(code_builder.size());
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
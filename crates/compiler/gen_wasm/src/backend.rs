@@ -13,9 +13,10 @@ use roc_mono::ir::{
     ProcLayout, Stmt,
 };
 use roc_mono::layout::{Builtin, Layout, LayoutIds, TagIdIntType, UnionLayout};
+use roc_region::all::Region;
 use roc_std::RocDec;
 
-use crate::layout::{CallConv, ReturnMethod, WasmLayout};
+use crate::layout::{layout_to_wasm_value_type, CallConv, ReturnMethod, WasmLayout};
 use crate::low_level::{call_higher_order_lowlevel, LowLevelCall};
 use crate::storage::{AddressValue, Storage, StoredValue, StoredVarKind};
 use crate::wasm_module::linking::{DataSymbol, WasmObjectSymbol};
@@ -711,7 +712,13 @@ impl<'a> WasmBackend<'a> {
 
             Stmt::Refcounting(modify, following) => self.stmt_refcounting(modify, following),
 
-            Stmt::Expect { .. } => todo!("expect is not implemented in the wasm backend"),
+            Stmt::Expect {
+                condition,
+                region,
+                remainder,
+                ..
+            } => self.stmt_expect(*condition, *region, remainder),
+
             Stmt::ExpectFx { .. } => todo!("expect-fx is not implemented in the wasm backend"),
 
             Stmt::RuntimeError(msg) => self.stmt_runtime_error(msg),
@@ -843,8 +850,7 @@ impl<'a> WasmBackend<'a> {
         }
 
         let is_bool = matches!(cond_layout, Layout::Builtin(Builtin::Bool));
-        let cond_type =
-            WasmLayout::new(self.env.layout_interner, cond_layout).arg_types(CallConv::C)[0];
+        let cond_type = layout_to_wasm_value_type(self.env.layout_interner, cond_layout);
 
         // then, we jump whenever the value under scrutiny is equal to the value of a branch
         for (i, (value, _, _)) in branches.iter().enumerate() {
@@ -984,6 +990,21 @@ impl<'a> WasmBackend<'a> {
         self.stmt(rc_stmt);
     }
 
+    fn stmt_expect(&mut self, condition: Symbol, region: Region, remainder: &'a Stmt<'a>) {
+        self.storage
+            .load_symbols(&mut self.code_builder, &[condition]);
+        self.code_builder.i32_eqz();
+
+        // The failure path is a trap that reports where the expect is in the source.
+        // It doesn't jump anywhere, so it doesn't need to count as a block for `br`.
+        self.code_builder.if_();
+        let msg = bumpalo::format!(in self.env.arena, "Expect failed at {:?}", region);
+        self.stmt_runtime_error(msg.into_bump_str());
+        self.code_builder.end();
+
+        self.stmt(remainder);
+    }
+
     pub fn stmt_runtime_error(&mut self, msg: &'a str) {
         // Create a zero-terminated version of the message string
         let mut bytes = Vec::with_capacity_in(msg.len() + 1, self.env.arena);
@@ -108,6 +108,15 @@ impl WasmLayout {
         }
     }
 
+    /// The type of a Wasm local holding this layout: the value itself for primitives,
+    /// or a pointer to its stack memory
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::Primitive(value_type, _) => *value_type,
+            Self::StackMemory { .. } => PTR_TYPE,
+        }
+    }
+
     /// The `ValueType`s to use for this layout when calling a Wasm function
     /// One Roc argument can become 0, 1, or 2 Wasm arguments
     pub fn arg_types(&self, conv: CallConv) -> &'static [ValueType] {
@@ -212,3 +221,63 @@ impl CallConv {
         }
     }
 }
+
+/// The Wasm type of a local that holds a value of this layout,
+/// so callers of CodeBuilder can declare typed locals with the same rules as WasmLayout
+pub fn layout_to_wasm_value_type<'a>(
+    interner: &STLayoutInterner<'a>,
+    layout: &Layout<'a>,
+) -> ValueType {
+    WasmLayout::new(interner, layout).value_type()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roc_mono::layout::Builtin;
+
+    #[test]
+    fn value_types_of_layouts() {
+        let interner = STLayoutInterner::with_capacity(4);
+        let value_type = |layout| layout_to_wasm_value_type(&interner, &layout);
+
+        assert_eq!(
+            value_type(Layout::Builtin(Builtin::Int(IntWidth::I32))),
+            ValueType::I32
+        );
+        assert_eq!(
+            value_type(Layout::Builtin(Builtin::Int(IntWidth::U8))),
+            ValueType::I32
+        );
+        assert_eq!(
+            value_type(Layout::Builtin(Builtin::Int(IntWidth::I64))),
+            ValueType::I64
+        );
+        assert_eq!(
+            value_type(Layout::Builtin(Builtin::Float(FloatWidth::F64))),
+            ValueType::F64
+        );
+        assert_eq!(value_type(Layout::Builtin(Builtin::Bool)), ValueType::I32);
+    }
+
+    #[test]
+    fn pointers_are_i32_on_wasm32() {
+        let interner = STLayoutInterner::with_capacity(4);
+        let str_layout = Layout::Builtin(Builtin::Str);
+
+        assert_eq!(
+            layout_to_wasm_value_type(&interner, &Layout::Boxed(&str_layout)),
+            ValueType::I32
+        );
+
+        // values in stack memory are held through a pointer to the stack frame
+        assert_eq!(
+            layout_to_wasm_value_type(&interner, &str_layout),
+            ValueType::I32
+        );
+        assert_eq!(
+            layout_to_wasm_value_type(&interner, &Layout::Builtin(Builtin::Int(IntWidth::I128))),
+            ValueType::I32
+        );
+    }
+}
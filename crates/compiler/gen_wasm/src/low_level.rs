@@ -2,7 +2,7 @@ use bumpalo::collections::Vec;
 use bumpalo::Bump;
 use roc_builtins::bitcode::{self, FloatWidth, IntWidth};
 use roc_error_macros::internal_error;
-use roc_module::low_level::LowLevel;
+use roc_module::low_level::{CompareOp, LowLevel};
 use roc_module::symbol::Symbol;
 use roc_mono::code_gen_help::HelperOp;
 use roc_mono::ir::{HigherOrderLowLevel, PassedFunction, ProcLayout};
@@ -12,6 +12,7 @@ use roc_mono::low_level::HigherOrder;
 use crate::backend::{ProcLookupData, ProcSource, WasmBackend};
 use crate::layout::{CallConv, StackMemoryFormat, WasmLayout};
 use crate::storage::{AddressValue, StackMemoryLocation, StoredValue};
+use crate::wasm_module::opcodes::OpCode;
 use crate::wasm_module::{Align, LocalId, ValueType};
 use crate::{PTR_TYPE, TARGET_INFO};
 
@@ -130,6 +131,50 @@ fn symbol_is_signed_int(backend: &WasmBackend<'_>, symbol: Symbol) -> bool {
     layout_is_signed_int(&backend.storage.symbol_layouts[&symbol])
 }
 
+/// The instruction that compares two values of type `value_type` with `op`.
+/// Only integer comparisons depend on `signed`.
+pub fn to_wasm_instruction(op: CompareOp, value_type: ValueType, signed: bool) -> OpCode {
+    use OpCode::*;
+
+    match (value_type, op, signed) {
+        (ValueType::I32, CompareOp::Eq, _) => I32EQ,
+        (ValueType::I32, CompareOp::Ne, _) => I32NE,
+        (ValueType::I32, CompareOp::Lt, true) => I32LTS,
+        (ValueType::I32, CompareOp::Lt, false) => I32LTU,
+        (ValueType::I32, CompareOp::Le, true) => I32LES,
+        (ValueType::I32, CompareOp::Le, false) => I32LEU,
+        (ValueType::I32, CompareOp::Gt, true) => I32GTS,
+        (ValueType::I32, CompareOp::Gt, false) => I32GTU,
+        (ValueType::I32, CompareOp::Ge, true) => I32GES,
+        (ValueType::I32, CompareOp::Ge, false) => I32GEU,
+
+        (ValueType::I64, CompareOp::Eq, _) => I64EQ,
+        (ValueType::I64, CompareOp::Ne, _) => I64NE,
+        (ValueType::I64, CompareOp::Lt, true) => I64LTS,
+        (ValueType::I64, CompareOp::Lt, false) => I64LTU,
+        (ValueType::I64, CompareOp::Le, true) => I64LES,
+        (ValueType::I64, CompareOp::Le, false) => I64LEU,
+        (ValueType::I64, CompareOp::Gt, true) => I64GTS,
+        (ValueType::I64, CompareOp::Gt, false) => I64GTU,
+        (ValueType::I64, CompareOp::Ge, true) => I64GES,
+        (ValueType::I64, CompareOp::Ge, false) => I64GEU,
+
+        (ValueType::F32, CompareOp::Eq, _) => F32EQ,
+        (ValueType::F32, CompareOp::Ne, _) => F32NE,
+        (ValueType::F32, CompareOp::Lt, _) => F32LT,
+        (ValueType::F32, CompareOp::Le, _) => F32LE,
+        (ValueType::F32, CompareOp::Gt, _) => F32GT,
+        (ValueType::F32, CompareOp::Ge, _) => F32GE,
+
+        (ValueType::F64, CompareOp::Eq, _) => F64EQ,
+        (ValueType::F64, CompareOp::Ne, _) => F64NE,
+        (ValueType::F64, CompareOp::Lt, _) => F64LT,
+        (ValueType::F64, CompareOp::Le, _) => F64LE,
+        (ValueType::F64, CompareOp::Gt, _) => F64GT,
+        (ValueType::F64, CompareOp::Ge, _) => F64GE,
+    }
+}
+
 pub struct LowLevelCall<'a> {
     pub lowlevel: LowLevel,
     pub arguments: &'a [Symbol],
@@ -1062,94 +1107,20 @@ impl<'a> LowLevelCall<'a> {
                     x => internal_error!("NumMulChecked is not defined for {:?}", x),
                 }
             }
-            NumGt => {
+            NumGt | NumGte | NumLt | NumLte => {
                 self.load_args(backend);
-                match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
-                    I32 => {
-                        if symbol_is_signed_int(backend, self.arguments[0]) {
-                            backend.code_builder.i32_gt_s()
-                        } else {
-                            backend.code_builder.i32_gt_u()
-                        }
-                    }
-                    I64 => {
-                        if symbol_is_signed_int(backend, self.arguments[0]) {
-                            backend.code_builder.i64_gt_s()
-                        } else {
-                            backend.code_builder.i64_gt_u()
-                        }
-                    }
-                    F32 => backend.code_builder.f32_gt(),
-                    F64 => backend.code_builder.f64_gt(),
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
-                }
-            }
-            NumGte => {
-                self.load_args(backend);
-                match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
-                    I32 => {
-                        if symbol_is_signed_int(backend, self.arguments[0]) {
-                            backend.code_builder.i32_ge_s()
-                        } else {
-                            backend.code_builder.i32_ge_u()
-                        }
-                    }
-                    I64 => {
-                        if symbol_is_signed_int(backend, self.arguments[0]) {
-                            backend.code_builder.i64_ge_s()
-                        } else {
-                            backend.code_builder.i64_ge_u()
-                        }
-                    }
-                    F32 => backend.code_builder.f32_ge(),
-                    F64 => backend.code_builder.f64_ge(),
+                let value_type = match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
+                    I32 => ValueType::I32,
+                    I64 => ValueType::I64,
+                    F32 => ValueType::F32,
+                    F64 => ValueType::F64,
                     x => todo!("{:?} for {:?}", self.lowlevel, x),
-                }
-            }
-            NumLt => {
-                self.load_args(backend);
-                match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
-                    I32 => {
-                        if symbol_is_signed_int(backend, self.arguments[0]) {
-                            backend.code_builder.i32_lt_s()
-                        } else {
-                            backend.code_builder.i32_lt_u()
-                        }
-                    }
-                    I64 => {
-                        if symbol_is_signed_int(backend, self.arguments[0]) {
-                            backend.code_builder.i64_lt_s()
-                        } else {
-                            backend.code_builder.i64_lt_u()
-                        }
-                    }
-                    F32 => backend.code_builder.f32_lt(),
-                    F64 => backend.code_builder.f64_lt(),
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
-                }
-            }
-            NumLte => {
-                self.load_args(backend);
-                let layout = backend.storage.symbol_layouts[&self.arguments[0]];
-                match CodeGenNumType::from(layout) {
-                    I32 => {
-                        if layout_is_signed_int(&layout) {
-                            backend.code_builder.i32_le_s()
-                        } else {
-                            backend.code_builder.i32_le_u()
-                        }
-                    }
-                    I64 => {
-                        if layout_is_signed_int(&layout) {
-                            backend.code_builder.i64_le_s()
-                        } else {
-                            backend.code_builder.i64_le_u()
-                        }
-                    }
-                    F32 => backend.code_builder.f32_le(),
-                    F64 => backend.code_builder.f64_le(),
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
-                }
+                };
+                let signed = symbol_is_signed_int(backend, self.arguments[0]);
+                let compare_op = self.lowlevel.compare_op().unwrap();
+                backend
+                    .code_builder
+                    .compare(to_wasm_instruction(compare_op, value_type, signed));
             }
             NumCompare => {
                 let layout = backend.storage.symbol_layouts[&self.arguments[0]];
@@ -2489,3 +2460,50 @@ fn ensure_symbol_is_in_memory<'a>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use OpCode::*;
+
+    fn instructions(value_type: ValueType, signed: bool) -> std::vec::Vec<OpCode> {
+        CompareOp::ALL
+            .iter()
+            .map(|op| to_wasm_instruction(*op, value_type, signed))
+            .collect()
+    }
+
+    #[test]
+    fn int_comparisons() {
+        assert_eq!(
+            instructions(ValueType::I32, true),
+            [I32EQ, I32NE, I32LTS, I32LES, I32GTS, I32GES]
+        );
+        assert_eq!(
+            instructions(ValueType::I32, false),
+            [I32EQ, I32NE, I32LTU, I32LEU, I32GTU, I32GEU]
+        );
+        assert_eq!(
+            instructions(ValueType::I64, true),
+            [I64EQ, I64NE, I64LTS, I64LES, I64GTS, I64GES]
+        );
+        assert_eq!(
+            instructions(ValueType::I64, false),
+            [I64EQ, I64NE, I64LTU, I64LEU, I64GTU, I64GEU]
+        );
+    }
+
+    #[test]
+    fn float_comparisons_ignore_signedness() {
+        for signed in [true, false] {
+            assert_eq!(
+                instructions(ValueType::F32, signed),
+                [F32EQ, F32NE, F32LT, F32LE, F32GT, F32GE]
+            );
+            assert_eq!(
+                instructions(ValueType::F64, signed),
+                [F64EQ, F64NE, F64LT, F64LE, F64GT, F64GE]
+            );
+        }
+    }
+}
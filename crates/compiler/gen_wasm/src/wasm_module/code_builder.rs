@@ -1,11 +1,15 @@
 use bumpalo::collections::vec::Vec;
 use bumpalo::Bump;
 use core::panic;
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
 use roc_error_macros::internal_error;
 
-use roc_module::symbol::Symbol;
+use roc_module::symbol::{Interns, Symbol};
+use roc_region::all::Region;
 
-use super::opcodes::{OpCode, OpCode::*};
+use super::opcodes::{stack_effect, OpCode, OpCode::*};
+use super::parse::{Parse, ParseError, SkipBytes};
+use super::sections::Signature;
 use super::serialize::{SerialBuffer, Serialize};
 use crate::{
     round_up_to_alignment, DEBUG_SETTINGS, FRAME_ALIGNMENT_BYTES, STACK_POINTER_GLOBAL_ID,
@@ -50,6 +54,10 @@ impl From<u8> for ValueType {
 
 const BLOCK_NO_RESULT: u8 = 0x40;
 
+/// Default limit on declared locals per function. Engines reject more than this
+/// (it's the limit in V8), so hitting it means a bug in code gen.
+pub const DEFAULT_MAX_LOCALS: u32 = 50_000;
+
 /// A control block in our model of the VM
 /// Child blocks cannot "see" values from their parent block
 struct VmBlock<'a> {
@@ -57,6 +65,13 @@ struct VmBlock<'a> {
     opcode: OpCode,
     /// the stack of values for this block
     value_stack: Vec<'a, Symbol>,
+    /// the type of each value in value_stack, where we know it (only used for debug checks)
+    value_types: Vec<'a, Option<ValueType>>,
+    /// Set after an `unreachable` instruction. The rest of the block is dead code, and Wasm
+    /// validation treats its stack as polymorphic, so popping from an empty stack is allowed.
+    unreachable: bool,
+    /// the value every path through the block leaves on the stack, if any (see `begin_block`)
+    result_type: Option<ValueType>,
 }
 
 impl std::fmt::Debug for VmBlock<'_> {
@@ -126,6 +141,26 @@ pub enum VmSymbolState {
     Popped { pushed_at: usize },
 }
 
+impl VmSymbolState {
+    /// Describe the state for debug logs, including the instruction that pushed the value.
+    /// `pushed_at` is only meaningful relative to the code of a particular CodeBuilder.
+    pub fn describe(&self, builder: &CodeBuilder) -> String {
+        match self {
+            Self::NotYetPushed => "NotYetPushed".to_string(),
+            Self::Pushed { pushed_at } => format!(
+                "Pushed at #{} ({})",
+                pushed_at,
+                builder.describe_instruction_ending_at(*pushed_at)
+            ),
+            Self::Popped { pushed_at } => format!(
+                "Popped, pushed at #{} ({})",
+                pushed_at,
+                builder.describe_instruction_ending_at(*pushed_at)
+            ),
+        }
+    }
+}
+
 // An instruction (local.set or local.tee) to be inserted into the function code
 #[derive(Debug)]
 struct Insertion {
@@ -134,6 +169,17 @@ struct Insertion {
     end: usize,
 }
 
+/// Sizes of the main CodeBuilder buffers, for choosing better initial capacities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityStats {
+    pub code_cap: usize,
+    pub code_len: usize,
+    /// Value stack of the function-level block
+    pub stack_cap: usize,
+    pub stack_len: usize,
+    pub insertions_len: usize,
+}
+
 macro_rules! instruction_no_args {
     ($method_name: ident, $opcode: expr, $pops: expr, $push: expr) => {
         pub fn $method_name(&mut self) {
@@ -182,6 +228,18 @@ pub struct CodeBuilder<'a> {
     /// Relocations for calls to JS imports
     /// When we remove unused imports, the live ones are re-indexed
     import_relocations: Vec<'a, (usize, u32)>,
+
+    /// Source region of the Roc code we're currently generating instructions for, if known.
+    /// Only used to make panic messages point at the code that produced a bad instruction.
+    current_region: Option<Region>,
+
+    /// Maximum number of locals the function may declare
+    max_locals: u32,
+
+    /// Whether to keep the model of the VM value stack up to date.
+    /// Input that is already known to be valid (e.g. code copied from another module)
+    /// can skip it, but then none of the Symbol-tracking methods can be used.
+    simulate_stack: bool,
 }
 
 impl<'a> Serialize for CodeBuilder<'a> {
@@ -193,22 +251,86 @@ impl<'a> Serialize for CodeBuilder<'a> {
 #[allow(clippy::new_without_default)]
 impl<'a> CodeBuilder<'a> {
     pub fn new(arena: &'a Bump) -> Self {
+        Self::with_capacities(arena, 1024, 8)
+    }
+
+    /// Like `new`, with initial capacities for the code bytes and the function-level value stack
+    pub fn with_capacities(arena: &'a Bump, code_cap: usize, stack_cap: usize) -> Self {
         let mut vm_block_stack = Vec::with_capacity_in(8, arena);
         let function_block = VmBlock {
             opcode: BLOCK,
-            value_stack: Vec::with_capacity_in(8, arena),
+            value_stack: Vec::with_capacity_in(stack_cap, arena),
+            value_types: Vec::with_capacity_in(stack_cap, arena),
+            unreachable: false,
+            result_type: None,
         };
         vm_block_stack.push(function_block);
 
         CodeBuilder {
             arena,
-            code: Vec::with_capacity_in(1024, arena),
+            code: Vec::with_capacity_in(code_cap, arena),
             insertions: Vec::with_capacity_in(32, arena),
             insert_bytes: Vec::with_capacity_in(64, arena),
             preamble: Vec::with_capacity_in(32, arena),
             inner_length: Vec::with_capacity_in(5, arena),
             vm_block_stack,
             import_relocations: Vec::with_capacity_in(0, arena),
+            current_region: None,
+            max_locals: DEFAULT_MAX_LOCALS,
+            simulate_stack: true,
+        }
+    }
+
+    pub fn set_max_locals(&mut self, max_locals: u32) {
+        self.max_locals = max_locals;
+    }
+
+    /// Turn the VM stack simulation on or off. Without it, instructions are only appended to the code,
+    /// with no stack checks, and the methods that track Symbols on the stack will panic.
+    /// `end` also can't drop the values left unused in a block, because it doesn't know about them.
+    /// Only switch it off for instruction sequences that are known to be valid.
+    pub fn set_simulate_stack(&mut self, simulate_stack: bool) {
+        self.simulate_stack = simulate_stack;
+    }
+
+    fn require_stack_simulation(&self, method: &str) {
+        if !self.simulate_stack {
+            internal_error!(
+                "CodeBuilder::{} needs the VM stack simulation, but it is switched off",
+                method
+            );
+        }
+    }
+
+    /// Clear everything so that the builder can generate another function, reusing its allocations.
+    /// Bump arenas never free memory, so this avoids allocating new buffers for every function.
+    /// The previous function must already have been serialized, since its bytes are discarded.
+    pub fn reset_for_next_function(&mut self) {
+        self.code.clear();
+        self.insert_bytes.clear();
+        self.insertions.clear();
+        self.preamble.clear();
+        self.inner_length.clear();
+        self.import_relocations.clear();
+        self.current_region = None;
+
+        // Keep only the function-level block
+        self.vm_block_stack.truncate(1);
+        self.clear_current_stack();
+        self.vm_block_stack[0].unreachable = false;
+    }
+
+    /// Current lengths and capacities. Capacities survive `reset_for_next_function`,
+    /// so calling this before each reset shows how much the previous function needed.
+    pub fn capacity_stats(&self) -> CapacityStats {
+        let function_stack = &self.vm_block_stack[0].value_stack;
+
+        CapacityStats {
+            code_cap: self.code.capacity(),
+            code_len: self.code.len(),
+            stack_cap: function_stack.capacity(),
+            stack_len: function_stack.len(),
+            insertions_len: self.insertions.len(),
         }
     }
 
@@ -252,14 +374,24 @@ impl<'a> CodeBuilder<'a> {
         &block.value_stack
     }
 
-    fn current_stack_mut(&mut self) -> &mut Vec<'a, Symbol> {
+    fn clear_current_stack(&mut self) {
         let block = self.vm_block_stack.last_mut().unwrap();
-        &mut block.value_stack
+        block.value_stack.clear();
+        block.value_types.clear();
+    }
+
+    /// Record the type of the value at the top of the VM stack
+    fn set_top_type(&mut self, value_type: ValueType) {
+        let block = self.vm_block_stack.last_mut().unwrap();
+        if let Some(top_type) = block.value_types.last_mut() {
+            *top_type = Some(value_type);
+        }
     }
 
     /// Set the Symbol that is at the top of the VM stack right now
     /// We will use this later when we need to load the Symbol
     pub fn set_top_symbol(&mut self, sym: Symbol) -> VmSymbolState {
+        self.require_stack_simulation("set_top_symbol");
         let current_stack = &mut self.vm_block_stack.last_mut().unwrap().value_stack;
         let pushed_at = self.code.len();
         let top_symbol: &mut Symbol = current_stack
@@ -270,8 +402,59 @@ impl<'a> CodeBuilder<'a> {
         VmSymbolState::Pushed { pushed_at }
     }
 
+    /// Find the instruction that ends at byte offset `end`, which is where `set_top_symbol`
+    /// records a value as pushed, and render it with its immediate if it has a simple one.
+    fn describe_instruction_ending_at(&self, end: usize) -> String {
+        let mut cursor = 0;
+        let mut start = 0;
+        while cursor < end {
+            start = cursor;
+            if OpCode::skip_bytes(&self.code, &mut cursor).is_err() {
+                return format!("invalid instruction at #{}", start);
+            }
+        }
+        if end == 0 || cursor != end {
+            return "no instruction ends here".to_string();
+        }
+
+        // skip_bytes has already checked that this byte is a valid OpCode
+        let opcode = OpCode::try_from(self.code[start]).unwrap();
+        let mut imm_cursor = start + 1;
+        let immediate = match opcode {
+            I32CONST => i32::parse((), &self.code, &mut imm_cursor).map(|x| x.to_string()),
+            GETLOCAL | SETLOCAL | TEELOCAL | GETGLOBAL | SETGLOBAL | CALL | BR | BRIF => {
+                u32::parse((), &self.code, &mut imm_cursor).map(|x| x.to_string())
+            }
+            _ => return format!("{:?}", opcode),
+        };
+        match immediate {
+            Ok(imm) => format!("{:?} {}", opcode, imm),
+            Err(_) => format!("{:?}", opcode),
+        }
+    }
+
+    /// The Symbols on the VM stack of the current block, from bottom to top.
+    /// Useful for debugging when `verify_stack_match` fails.
+    pub fn vm_stack_symbols(&self) -> &[Symbol] {
+        self.require_stack_simulation("vm_stack_symbols");
+        self.current_stack()
+    }
+
+    /// The Symbol on top of the VM stack of the current block, if any
+    pub fn top_symbol(&self) -> Option<Symbol> {
+        self.require_stack_simulation("top_symbol");
+        self.current_stack().last().copied()
+    }
+
+    /// The Symbol `n` places below the top of the current block's VM stack (0 is the top)
+    pub fn nth_from_top(&self, n: usize) -> Option<Symbol> {
+        self.require_stack_simulation("nth_from_top");
+        self.current_stack().iter().rev().nth(n).copied()
+    }
+
     /// Verify if a sequence of symbols is at the top of the stack
     pub fn verify_stack_match(&self, symbols: &[Symbol]) -> bool {
+        self.require_stack_simulation("verify_stack_match");
         let current_stack = self.current_stack();
         let n_symbols = symbols.len();
         let stack_depth = current_stack.len();
@@ -321,6 +504,7 @@ impl<'a> CodeBuilder<'a> {
         vm_state: VmSymbolState,
         next_local_id: LocalId,
     ) -> Option<VmSymbolState> {
+        self.require_stack_simulation("load_symbol");
         use VmSymbolState::*;
 
         match vm_state {
@@ -379,6 +563,7 @@ impl<'a> CodeBuilder<'a> {
         vm_state: VmSymbolState,
         next_local_id: LocalId,
     ) {
+        self.require_stack_simulation("store_symbol_to_local");
         use VmSymbolState::*;
 
         match vm_state {
@@ -408,6 +593,7 @@ impl<'a> CodeBuilder<'a> {
         for block in self.vm_block_stack.iter_mut() {
             if let Some(found_index) = block.value_stack.iter().position(|&s| s == symbol) {
                 block.value_stack.remove(found_index);
+                block.value_types.remove(found_index);
                 found = true;
             }
         }
@@ -434,6 +620,14 @@ impl<'a> CodeBuilder<'a> {
 
     /// Generate bytes to declare the function's local variables
     fn build_local_declarations(&mut self, local_types: &[ValueType]) {
+        if local_types.len() > self.max_locals as usize {
+            internal_error!(
+                "Function declares {} locals, more than the limit of {}. This module would be invalid.",
+                local_types.len(),
+                self.max_locals
+            );
+        }
+
         // reserve one byte for num_batches
         self.preamble.push(0);
 
@@ -504,6 +698,15 @@ impl<'a> CodeBuilder<'a> {
         frame_size: i32,
         frame_pointer: Option<LocalId>,
     ) {
+        // Serializing would slice the code out of bounds, or put the insertion after the final `end`
+        if let Some(ins) = self.insertions.iter().find(|ins| ins.at > self.code.len()) {
+            internal_error!(
+                "Insertion at byte offset {} is past the end of the function code ({} bytes)",
+                ins.at,
+                self.code.len()
+            );
+        }
+
         self.build_local_declarations(local_types);
 
         if frame_size != 0 {
@@ -520,9 +723,112 @@ impl<'a> CodeBuilder<'a> {
         self.inner_length.encode_u32(inner_len as u32);
 
         // Sort insertions. They are not created in order of assignment, but in order of *second* usage.
+        // The sort is stable, so several insertions at the same position keep their creation order.
         self.insertions.sort_by_key(|ins| ins.at);
     }
 
+    /**********************************************************
+
+        INLINING
+
+    ***********************************************************/
+
+    /// Insertions in code order, without sorting them in place
+    fn sorted_insertions(&self) -> std::vec::Vec<&Insertion> {
+        let mut insertions: std::vec::Vec<&Insertion> = self.insertions.iter().collect();
+        insertions.sort_by_key(|ins| ins.at);
+        insertions
+    }
+
+    /// Append the body of another function at the current position, for inlining.
+    /// Its locals are renumbered by adding `local_offset`, so the caller must declare them.
+    /// The callee must not have a header or footer yet, and must not contain `return`.
+    pub fn splice_inline(&mut self, other: &CodeBuilder, local_offset: u32) {
+        debug_assert!(
+            other.preamble.is_empty(),
+            "Can't inline a function that already has a header and footer"
+        );
+        debug_assert!(
+            other.vm_block_stack.len() == 1,
+            "Can't inline a function with unclosed blocks"
+        );
+
+        let mut code_pos = 0;
+        for Insertion { at, start, end } in other.sorted_insertions() {
+            self.splice_code_range(other, code_pos, *at, local_offset);
+            self.splice_instructions(&other.insert_bytes[*start..*end], local_offset, &[]);
+            code_pos = *at;
+        }
+        self.splice_code_range(other, code_pos, other.code.len(), local_offset);
+
+        if !self.simulate_stack {
+            return;
+        }
+
+        // The callee's stack started out empty, so its net effect is just the values it leaves behind
+        let other_block = &other.vm_block_stack[0];
+        let block = self.vm_block_stack.last_mut().unwrap();
+        for value_type in other_block.value_types.iter() {
+            block.value_stack.push(Symbol::WASM_TMP);
+            block.value_types.push(*value_type);
+        }
+    }
+
+    fn splice_code_range(
+        &mut self,
+        other: &CodeBuilder,
+        from: usize,
+        to: usize,
+        local_offset: u32,
+    ) {
+        let import_relocs: std::vec::Vec<(usize, u32)> = other
+            .import_relocations
+            .iter()
+            .filter(|(code_index, _)| (from..to).contains(code_index))
+            .map(|(code_index, fn_index)| (code_index - from, *fn_index))
+            .collect();
+
+        self.splice_instructions(&other.code[from..to], local_offset, &import_relocs);
+    }
+
+    /// Copy instructions into the code, renumbering locals.
+    /// Import relocation offsets are relative to the start of `bytes`.
+    fn splice_instructions(
+        &mut self,
+        bytes: &[u8],
+        local_offset: u32,
+        import_relocs: &[(usize, u32)],
+    ) {
+        let mut cursor = 0;
+        while cursor < bytes.len() {
+            let start = cursor;
+            OpCode::skip_bytes(bytes, &mut cursor)
+                .unwrap_or_else(|e| internal_error!("Can't inline invalid instruction: {:?}", e));
+
+            let opcode_byte = bytes[start];
+            let is_local_op = opcode_byte == GETLOCAL as u8
+                || opcode_byte == SETLOCAL as u8
+                || opcode_byte == TEELOCAL as u8;
+
+            if is_local_op {
+                let mut immediate_cursor = start + 1;
+                let local_id = u32::parse((), bytes, &mut immediate_cursor).unwrap();
+                self.code.push(opcode_byte);
+                self.code.encode_u32(local_id + local_offset);
+            } else {
+                let new_start = self.code.len();
+                self.code.extend_from_slice(&bytes[start..cursor]);
+
+                for (offset, fn_index) in import_relocs.iter() {
+                    if (start..cursor).contains(offset) {
+                        self.import_relocations
+                            .push((new_start + offset - start, *fn_index));
+                    }
+                }
+            }
+        }
+    }
+
     /**********************************************************
 
         SERIALIZE
@@ -533,6 +839,51 @@ impl<'a> CodeBuilder<'a> {
         self.inner_length.len() + self.preamble.len() + self.code.len() + self.insert_bytes.len()
     }
 
+    /// Estimated size of the serialized function, for size budgets and inlining decisions.
+    /// Instructions are already encoded, so before the header is built the only guesses are
+    /// the local declarations and the stack frame code, which are assumed to be empty.
+    /// After `build_fn_header_and_footer`, this is the exact size.
+    pub fn estimated_byte_size(&self) -> usize {
+        if !self.inner_length.is_empty() {
+            return self.size();
+        }
+
+        // one byte for the number of local declaration batches, and the final `end`
+        let inner_len = 1 + self.code.len() + self.insert_bytes.len() + 1;
+        // the length prefix is LEB-128 encoded, 7 bits per byte
+        let significant_bits = (usize::BITS - inner_len.leading_zeros()) as usize;
+        let length_prefix_len = (significant_bits + 6) / 7;
+
+        length_prefix_len + inner_len
+    }
+
+    /// Number of instructions generated so far, including the pending insertions
+    pub fn instruction_count(&self) -> usize {
+        [&self.code[..], &self.insert_bytes[..]]
+            .iter()
+            .map(|bytes| {
+                let mut cursor = 0;
+                let mut count = 0;
+                while cursor < bytes.len() {
+                    OpCode::skip_bytes(bytes, &mut cursor)
+                        .unwrap_or_else(|e| internal_error!("Invalid instruction: {:?}", e));
+                    count += 1;
+                }
+                count
+            })
+            .sum()
+    }
+
+    /// True if no instructions have been generated yet.
+    /// Insertions are always anchored after some code, so they can't exist without it.
+    pub fn is_empty(&self) -> bool {
+        debug_assert!(
+            !self.code.is_empty() || self.insertions.is_empty(),
+            "CodeBuilder has insertions but no code"
+        );
+        self.code.is_empty()
+    }
+
     /// Serialize all byte vectors in the right order
     /// Also update relocation offsets relative to the base offset (code section body start)
     pub fn serialize_without_relocs<T: SerialBuffer>(&self, buffer: &mut T) {
@@ -549,29 +900,103 @@ impl<'a> CodeBuilder<'a> {
         buffer.append_slice(&self.code[code_pos..self.code.len()]);
     }
 
+    /**********************************************************
+
+        DEBUG
+
+    ***********************************************************/
+
+    /// Human-readable listing of the code generated so far, and of the VM stack.
+    /// Bytes waiting to be inserted are marked with `>>`, and Symbols are shown by name.
+    pub fn debug_dump(&self, interns: &Interns) -> String {
+        let mut output = String::new();
+
+        let mut insertions = self.sorted_insertions().into_iter().peekable();
+
+        let mut cursor = 0;
+        loop {
+            while let Some(Insertion { start, end, .. }) =
+                insertions.next_if(|ins| ins.at <= cursor)
+            {
+                let insert_bytes = &self.insert_bytes[*start..*end];
+                let mut insert_cursor = 0;
+                while insert_cursor < insert_bytes.len() {
+                    output.push_str(">> ");
+                    dump_instruction(insert_bytes, &mut insert_cursor, &mut output);
+                }
+            }
+
+            if cursor >= self.code.len() {
+                break;
+            }
+
+            output.push_str("   ");
+            dump_instruction(&self.code, &mut cursor, &mut output);
+        }
+
+        for block in self.vm_block_stack.iter() {
+            let symbol_names: std::vec::Vec<String> = block
+                .value_stack
+                .iter()
+                .map(|sym| symbol_name(*sym, interns))
+                .collect();
+
+            output.push_str(&format!(
+                "{:?} stack: [{}]\n",
+                block.opcode,
+                symbol_names.join(", ")
+            ));
+        }
+
+        output
+    }
+
     /**********************************************************
 
         INSTRUCTION HELPER METHODS
 
     ***********************************************************/
 
+    /// Generate some instructions, tagging them with the source region they came from
+    pub fn with_region<F: FnOnce(&mut Self)>(&mut self, region: Region, build: F) {
+        let outer_region = self.current_region.replace(region);
+        build(self);
+        self.current_region = outer_region;
+    }
+
+    fn region_description(&self) -> String {
+        match self.current_region {
+            Some(region) => format!(" (source region {:?})", region),
+            None => String::new(),
+        }
+    }
+
     /// Base method for generating instructions
     /// Emits the opcode and simulates VM stack push/pop
     fn inst_base(&mut self, opcode: OpCode, pops: usize, push: bool) {
-        let current_stack = self.current_stack_mut();
-        let stack_size = current_stack.len();
+        if !self.simulate_stack {
+            self.code.push(opcode as u8);
+            return;
+        }
+
+        let stack_size = self.current_stack().len();
+        let is_unreachable = self.vm_block_stack.last().unwrap().unreachable;
 
         debug_assert!(
-            stack_size >= pops,
-            "Wasm value stack underflow. Tried to pop {} but only {} available",
+            stack_size >= pops || is_unreachable,
+            "Wasm value stack underflow. Tried to pop {} but only {} available{}",
             pops,
-            stack_size
+            stack_size,
+            self.region_description()
         );
 
-        let new_len = stack_size - pops as usize;
-        current_stack.truncate(new_len);
+        let block = self.vm_block_stack.last_mut().unwrap();
+        let new_len = stack_size.saturating_sub(pops);
+        block.value_stack.truncate(new_len);
+        block.value_types.truncate(new_len);
         if push {
-            current_stack.push(Symbol::WASM_TMP);
+            block.value_stack.push(Symbol::WASM_TMP);
+            block.value_types.push(None);
         }
         self.code.push(opcode as u8);
     }
@@ -587,16 +1012,23 @@ impl<'a> CodeBuilder<'a> {
     }
 
     /// Block instruction
-    fn inst_block(&mut self, opcode: OpCode, pops: usize) {
+    fn inst_block(&mut self, opcode: OpCode, pops: usize, result_type: Option<ValueType>) {
         self.inst_base(opcode, pops, false);
 
-        // We don't support block result types. Too hard to track types through arbitrary control flow.
-        self.code.push(BLOCK_NO_RESULT);
+        // Result types are only tracked for blocks started with `begin_block`,
+        // where the caller promises that every arm ends with the result on the stack.
+        match result_type {
+            Some(value_type) => value_type.serialize(&mut self.code),
+            None => self.code.push(BLOCK_NO_RESULT),
+        }
 
         // Start a new block with a fresh value stack
         self.vm_block_stack.push(VmBlock {
             opcode,
             value_stack: Vec::with_capacity_in(8, self.arena),
+            value_types: Vec::with_capacity_in(8, self.arena),
+            unreachable: false,
+            result_type,
         });
 
         log_instruction!("{:10}\t{:?}", format!("{:?}", opcode), &self.vm_block_stack);
@@ -614,6 +1046,9 @@ impl<'a> CodeBuilder<'a> {
     }
 
     fn inst_mem(&mut self, opcode: OpCode, pops: usize, push: bool, align: Align, offset: u32) {
+        if cfg!(debug_assertions) {
+            self.check_address_type(opcode, pops);
+        }
         self.inst_base(opcode, pops, push);
         self.code.push(align as u8);
         self.code.encode_u32(offset);
@@ -626,6 +1061,75 @@ impl<'a> CodeBuilder<'a> {
         );
     }
 
+    /// Check that the current arm of a block leaves exactly the values its block type declares.
+    /// After `unreachable` or a branch, the stack is polymorphic and anything goes.
+    fn check_block_result(&self, result_count: usize) {
+        if !self.simulate_stack {
+            return;
+        }
+        let block = self.vm_block_stack.last().unwrap();
+        debug_assert!(
+            block.unreachable || block.value_stack.len() == result_count,
+            "Wrong number of values at the end of a {:?} block arm. Expected {}, found {}{}",
+            block.opcode,
+            result_count,
+            block.value_stack.len(),
+            self.region_description()
+        );
+    }
+
+    /// Loads and stores take the memory address as their first operand, and it must be an i32.
+    /// Engines reject a mistyped address with an unhelpful error, so catch it here if we can.
+    fn check_address_type(&self, opcode: OpCode, pops: usize) {
+        let value_types = &self.vm_block_stack.last().unwrap().value_types;
+        let address_type = value_types
+            .len()
+            .checked_sub(pops)
+            .and_then(|address_index| value_types[address_index]);
+
+        if let Some(value_type) = address_type {
+            if value_type != ValueType::I32 {
+                internal_error!(
+                    "{:?} needs an I32 memory address but the address operand is {:?}{}",
+                    opcode,
+                    value_type,
+                    self.region_description()
+                );
+            }
+        }
+    }
+
+    /// `select` needs two values of the same type, followed by an i32 condition.
+    /// Only checks the operands whose types we know.
+    fn check_select_types(&self) {
+        let value_types = &self.vm_block_stack.last().unwrap().value_types;
+        if value_types.len() < 3 {
+            // inst_base reports the underflow
+            return;
+        }
+
+        let operands = &value_types[value_types.len() - 3..];
+        if let [Some(type1), Some(type2), _] = operands {
+            if type1 != type2 {
+                internal_error!(
+                    "select needs two values of the same type but got {:?} and {:?}{}",
+                    type1,
+                    type2,
+                    self.region_description()
+                );
+            }
+        }
+        if let Some(condition_type) = operands[2] {
+            if condition_type != ValueType::I32 {
+                internal_error!(
+                    "select needs an I32 condition but got {:?}{}",
+                    condition_type,
+                    self.region_description()
+                );
+            }
+        }
+    }
+
     /**********************************************************
 
         INSTRUCTION METHODS
@@ -636,25 +1140,52 @@ impl<'a> CodeBuilder<'a> {
 
     ***********************************************************/
 
-    instruction_no_args!(unreachable_, UNREACHABLE, 0, false);
+    /// Trap. Code after this in the same block is never run, so stack checks are relaxed until
+    /// the block ends (or its `else` starts).
+    pub fn unreachable_(&mut self) {
+        self.inst(UNREACHABLE, 0, false);
+        self.vm_block_stack.last_mut().unwrap().unreachable = true;
+    }
     instruction_no_args!(nop, NOP, 0, false);
 
     pub fn block(&mut self) {
-        self.inst_block(BLOCK, 0);
+        self.inst_block(BLOCK, 0, None);
     }
     pub fn loop_(&mut self) {
-        self.inst_block(LOOP, 0);
+        self.inst_block(LOOP, 0, None);
     }
     pub fn if_(&mut self) {
-        self.inst_block(IF, 1);
+        self.inst_block(IF, 1, None);
+    }
+    /// Start a `block`, `loop` or `if` where every path leaves a value of `result_type` on the stack,
+    /// like an `if/else` expression. End it with `end_block_with_result`.
+    pub fn begin_block(&mut self, opcode: OpCode, result_type: Option<ValueType>) {
+        let pops = match opcode {
+            BLOCK | LOOP => 0,
+            IF => 1,
+            _ => internal_error!("{:?} does not start a block", opcode),
+        };
+        self.inst_block(opcode, pops, result_type);
     }
     pub fn else_(&mut self) {
+        // The 'then' arm of a value-producing if must leave its result before we discard it
+        let result_type = self.vm_block_stack.last().unwrap().result_type;
+        if result_type.is_some() {
+            self.check_block_result(1);
+        }
+
         // Reuse the 'then' block but clear its value stack
-        self.current_stack_mut().clear();
+        self.clear_current_stack();
+        self.vm_block_stack.last_mut().unwrap().unreachable = false;
         self.inst(ELSE, 0, false);
     }
 
     pub fn end(&mut self) {
+        if self.vm_block_stack.last().unwrap().result_type.is_some() {
+            self.end_block_with_result(1);
+            return;
+        }
+
         // We need to drop any unused values from the VM stack in order to pass Wasm validation.
         // This happens, for example, in test `gen_tags::if_guard_exhaustiveness`
         let n_unused = self
@@ -672,6 +1203,32 @@ impl<'a> CodeBuilder<'a> {
 
         log_instruction!("END       \t\t{:?}", &self.vm_block_stack);
     }
+    /// End a block started with `begin_block`. The fall-through path must leave `result_count` values,
+    /// which become the block's result on the enclosing stack, at the same height as when the block started.
+    pub fn end_block_with_result(&mut self, result_count: usize) {
+        let result_type = self.vm_block_stack.last().unwrap().result_type;
+        debug_assert_eq!(
+            result_count,
+            result_type.iter().count(),
+            "Block was started with result type {:?}{}",
+            result_type,
+            self.region_description()
+        );
+        self.check_block_result(result_count);
+
+        self.inst_base(END, 0, false);
+        self.vm_block_stack.pop();
+
+        if let Some(value_type) = result_type {
+            if self.simulate_stack {
+                let block = self.vm_block_stack.last_mut().unwrap();
+                block.value_stack.push(Symbol::WASM_TMP);
+                block.value_types.push(Some(value_type));
+            }
+        }
+
+        log_instruction!("END       \t\t{:?}", &self.vm_block_stack);
+    }
     pub fn br(&mut self, levels: u32) {
         self.inst_imm32(BR, 0, false, levels);
     }
@@ -681,9 +1238,22 @@ impl<'a> CodeBuilder<'a> {
         // where the branch was not taken. So we only pop 1 value, the condition.
         self.inst_imm32(BRIF, 1, false, levels);
     }
-    #[allow(dead_code)]
-    fn br_table() {
-        todo!("br instruction");
+    /// Branch to `targets[i]` where `i` is the value on top of the stack, or to `default` if it's out of range.
+    /// Like br_if, only the index is popped in our static model.
+    pub fn br_table(&mut self, targets: &[u32], default: u32) {
+        self.inst_base(BRTABLE, 1, false);
+        self.code.encode_u32(targets.len() as u32);
+        for target in targets {
+            self.code.encode_u32(*target);
+        }
+        self.code.encode_u32(default);
+        log_instruction!(
+            "{:10}\t{:?} {}\t{:?}",
+            format!("{:?}", BRTABLE),
+            targets,
+            default,
+            self.vm_block_stack
+        );
     }
 
     instruction_no_args!(return_, RETURN, 0, false);
@@ -729,7 +1299,13 @@ impl<'a> CodeBuilder<'a> {
     }
 
     instruction_no_args!(drop_, DROP, 1, false);
-    instruction_no_args!(select, SELECT, 3, true);
+
+    pub fn select(&mut self) {
+        if cfg!(debug_assertions) {
+            self.check_select_types();
+        }
+        self.inst(SELECT, 3, true);
+    }
 
     pub fn get_local(&mut self, id: LocalId) {
         self.inst_imm32(GETLOCAL, 0, true, id.0);
@@ -771,12 +1347,16 @@ impl<'a> CodeBuilder<'a> {
     instruction_memargs!(i64_store16, I64STORE16, 2, false);
     instruction_memargs!(i64_store32, I64STORE32, 2, false);
 
+    /// Push the current size of memory 0, in pages
     pub fn memory_size(&mut self) {
         self.inst(CURRENTMEMORY, 0, true);
+        self.set_top_type(ValueType::I32);
         self.code.push(0);
     }
+    /// Pop a number of pages to grow memory 0 by, and push the previous size (or -1 on failure)
     pub fn memory_grow(&mut self) {
         self.inst(GROWMEMORY, 1, true);
+        self.set_top_type(ValueType::I32);
         self.code.push(0);
     }
 
@@ -793,21 +1373,62 @@ impl<'a> CodeBuilder<'a> {
     }
     pub fn i32_const(&mut self, x: i32) {
         self.inst_base(I32CONST, 0, true);
+        self.set_top_type(ValueType::I32);
         self.code.encode_i32(x);
         self.log_const(I32CONST, x);
     }
     pub fn i64_const(&mut self, x: i64) {
         self.inst_base(I64CONST, 0, true);
+        self.set_top_type(ValueType::I64);
         self.code.encode_i64(x);
         self.log_const(I64CONST, x);
     }
+    /// Push an integer constant for a Roc int width.
+    /// Widths up to 32 bits use i32.const, and `value` must be in range for the width.
+    /// Unsigned values are stored as their bit pattern, e.g. u32::MAX becomes -1.
+    /// 64-bit widths use i64.const. 128-bit ints don't fit in a Wasm value and are rejected.
+    pub fn push_int_const(&mut self, value: i64, width: IntWidth) {
+        use IntWidth::*;
+
+        let range = match width {
+            U8 => 0..=u8::MAX as i64,
+            U16 => 0..=u16::MAX as i64,
+            U32 => 0..=u32::MAX as i64,
+            I8 => i8::MIN as i64..=i8::MAX as i64,
+            I16 => i16::MIN as i64..=i16::MAX as i64,
+            I32 => i32::MIN as i64..=i32::MAX as i64,
+            U64 | I64 => {
+                self.i64_const(value);
+                return;
+            }
+            U128 | I128 => internal_error!("Can't push a {:?} constant onto the VM stack", width),
+        };
+
+        if !range.contains(&value) {
+            internal_error!("Constant {} is out of range for {:?}", value, width);
+        }
+
+        self.i32_const(value as i32);
+    }
+    /// Push a float constant for a Roc float width. F32 rounds `value` to the nearest f32.
+    pub fn push_float_const(&mut self, value: f64, width: FloatWidth) {
+        match width {
+            FloatWidth::F32 => self.f32_const(value as f32),
+            FloatWidth::F64 => self.f64_const(value),
+            FloatWidth::F128 => {
+                internal_error!("Can't push a {:?} constant onto the VM stack", width)
+            }
+        }
+    }
     pub fn f32_const(&mut self, x: f32) {
         self.inst_base(F32CONST, 0, true);
+        self.set_top_type(ValueType::F32);
         self.code.encode_f32(x);
         self.log_const(F32CONST, x);
     }
     pub fn f64_const(&mut self, x: f64) {
         self.inst_base(F64CONST, 0, true);
+        self.set_top_type(ValueType::F64);
         self.code.encode_f64(x);
         self.log_const(F64CONST, x);
     }
@@ -848,6 +1469,23 @@ impl<'a> CodeBuilder<'a> {
     instruction_no_args!(f64_gt, F64GT, 2, true);
     instruction_no_args!(f64_le, F64LE, 2, true);
     instruction_no_args!(f64_ge, F64GE, 2, true);
+
+    /// Compare the two values on top of the stack with any of the comparison instructions above.
+    /// Useful when the instruction was chosen at runtime, for example by `to_wasm_instruction`.
+    pub fn compare(&mut self, opcode: OpCode) {
+        // The comparisons are numbered contiguously, apart from i64.eqz in the middle
+        let byte = opcode as u8;
+        debug_assert!(
+            (I32EQ as u8..=I32GEU as u8).contains(&byte)
+                || (I64EQ as u8..=F64GE as u8).contains(&byte),
+            "{:?} is not a comparison{}",
+            opcode,
+            self.region_description()
+        );
+        self.inst(opcode, 2, true);
+        self.set_top_type(ValueType::I32);
+    }
+
     instruction_no_args!(i32_clz, I32CLZ, 1, true);
     instruction_no_args!(i32_ctz, I32CTZ, 1, true);
     instruction_no_args!(i32_popcnt, I32POPCNT, 1, true);
@@ -938,3 +1576,1134 @@ impl<'a> CodeBuilder<'a> {
     instruction_no_args!(f32_reinterpret_i32, F32REINTERPRETI32, 1, true);
     instruction_no_args!(f64_reinterpret_i64, F64REINTERPRETI64, 1, true);
 }
+
+#[derive(Debug)]
+pub enum SimError {
+    /// The instruction at byte offset `at_index` pops more values than its block has
+    Underflow {
+        at_index: usize,
+        instruction: OpCode,
+    },
+    /// A call to a function index that has no entry in the signature table
+    UnknownFunction {
+        at_index: usize,
+        function_index: u32,
+    },
+    /// The bytes are not a valid instruction stream
+    Invalid(ParseError),
+}
+
+impl From<ParseError> for SimError {
+    fn from(e: ParseError) -> Self {
+        SimError::Invalid(e)
+    }
+}
+
+/// Run the same value stack accounting as CodeBuilder over an arbitrary instruction stream,
+/// returning the outer block's stack at the end, or the first instruction that would underflow.
+/// Unlike the builder itself, this never panics, so it can be fed fuzzer output.
+/// `fn_signatures` is indexed by function index, including imports.
+pub fn simulate<'a>(
+    arena: &'a Bump,
+    code: &[u8],
+    fn_signatures: &[Signature],
+) -> Result<Vec<'a, Symbol>, SimError> {
+    let mut block_stacks: std::vec::Vec<usize> = std::vec::Vec::with_capacity(8);
+    block_stacks.push(0);
+    // whether each block in block_stacks leaves a result on its parent's stack
+    let mut block_results: std::vec::Vec<bool> = std::vec::Vec::with_capacity(8);
+    block_results.push(false);
+    // whether each block in block_stacks has had an `unreachable`, after which its stack is polymorphic
+    let mut block_unreachable: std::vec::Vec<bool> = std::vec::Vec::with_capacity(8);
+    block_unreachable.push(false);
+    let mut cursor = 0;
+
+    while cursor < code.len() {
+        let start = cursor;
+        OpCode::skip_bytes(code, &mut cursor)?;
+        if cursor > code.len() {
+            return Err(SimError::Invalid(ParseError {
+                offset: start,
+                message: "Instruction immediates run past the end of the code".into(),
+            }));
+        }
+
+        // skip_bytes has just checked that this is a valid opcode
+        let opcode = OpCode::try_from(code[start]).unwrap();
+
+        let (pops, push) = match opcode {
+            BLOCK | LOOP => (0, false),
+            IF | BRIF | BRTABLE => (1, false),
+            BR | ELSE | END => (0, false),
+            CALL => {
+                let mut imm_cursor = start + 1;
+                let function_index = u32::parse((), code, &mut imm_cursor)?;
+                match fn_signatures.get(function_index as usize) {
+                    Some(sig) => (sig.param_types.len(), sig.ret_type.is_some()),
+                    None => {
+                        return Err(SimError::UnknownFunction {
+                            at_index: start,
+                            function_index,
+                        })
+                    }
+                }
+            }
+            CALLINDIRECT => {
+                return Err(SimError::Invalid(ParseError {
+                    offset: start,
+                    message: "call_indirect is not supported".into(),
+                }))
+            }
+            _ => stack_effect(opcode).unwrap(),
+        };
+
+        let stack_size = block_stacks.last_mut().unwrap();
+        if *stack_size < pops && !block_unreachable.last().unwrap() {
+            return Err(SimError::Underflow {
+                at_index: start,
+                instruction: opcode,
+            });
+        }
+        *stack_size = stack_size.saturating_sub(pops);
+        if push {
+            *stack_size += 1;
+        }
+
+        match opcode {
+            UNREACHABLE => *block_unreachable.last_mut().unwrap() = true,
+            BLOCK | LOOP | IF => {
+                block_stacks.push(0);
+                block_results.push(code[start + 1] != BLOCK_NO_RESULT);
+                block_unreachable.push(false);
+            }
+            ELSE => {
+                *block_stacks.last_mut().unwrap() = 0;
+                *block_unreachable.last_mut().unwrap() = false;
+            }
+            END => {
+                // The END of the function body closes the outer block
+                if block_stacks.len() == 1 {
+                    break;
+                }
+                block_stacks.pop();
+                block_unreachable.pop();
+                if block_results.pop() == Some(true) {
+                    *block_stacks.last_mut().unwrap() += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut symbols = Vec::with_capacity_in(block_stacks[0], arena);
+    symbols.extend(std::iter::repeat(Symbol::WASM_TMP).take(block_stacks[0]));
+    Ok(symbols)
+}
+
+/// Write one instruction as its opcode name followed by its immediate bytes in hex
+fn dump_instruction(bytes: &[u8], cursor: &mut usize, output: &mut String) {
+    let start = *cursor;
+
+    if OpCode::skip_bytes(bytes, cursor).is_err() {
+        output.push_str(&format!("invalid opcode 0x{:02x}\n", bytes[start]));
+        *cursor = bytes.len();
+        return;
+    }
+
+    // skip_bytes has just checked that this is a valid opcode
+    let opcode = OpCode::try_from(bytes[start]).unwrap();
+    output.push_str(&format!("{:?}", opcode));
+
+    for byte in &bytes[start + 1..*cursor] {
+        output.push_str(&format!(" {:02x}", byte));
+    }
+
+    output.push('\n');
+}
+
+/// Unlike Symbol::as_str, this doesn't panic for Symbols that are missing from interns
+fn symbol_name(symbol: Symbol, interns: &Interns) -> String {
+    interns
+        .all_ident_ids
+        .get(&symbol.module_id())
+        .and_then(|ident_ids| ident_ids.get_name(symbol.ident_id()))
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("{:?}", symbol))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roc_region::all::Position;
+
+    #[test]
+    fn code_after_unreachable() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.block();
+        code_builder.unreachable_();
+        // dead code popping from an empty stack, as Wasm validation allows
+        code_builder.i32_add();
+        code_builder.drop_();
+        code_builder.end();
+
+        // the enclosing block is still checked normally
+        assert!(!code_builder.vm_block_stack[0].unreachable);
+        assert_eq!(code_builder.current_stack().len(), 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Wasm value stack underflow")]
+    fn else_after_unreachable_is_checked() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i32_const(1);
+        code_builder.if_();
+        code_builder.unreachable_();
+        code_builder.else_();
+        code_builder.i32_add();
+    }
+
+    #[test]
+    fn if_else_with_result() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i32_const(5);
+        code_builder.i32_const(1);
+        code_builder.begin_block(IF, Some(ValueType::I32));
+        code_builder.i32_const(10);
+        code_builder.else_();
+        code_builder.i32_const(20);
+        code_builder.end_block_with_result(1);
+
+        // the condition was popped and exactly one result was pushed on top of the 5
+        assert_eq!(code_builder.current_stack().len(), 2);
+        assert_eq!(
+            code_builder.vm_block_stack[0].value_types.as_slice(),
+            &[Some(ValueType::I32), Some(ValueType::I32)]
+        );
+        assert_eq!(&code_builder.code[4..6], &[IF as u8, ValueType::I32 as u8]);
+
+        let stack = simulate(&arena, &code_builder.code, &[]).unwrap();
+        assert_eq!(stack.len(), 2);
+    }
+
+    #[test]
+    fn end_closes_block_with_result() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.begin_block(BLOCK, Some(ValueType::I64));
+        code_builder.i64_const(7);
+        code_builder.end();
+
+        assert_eq!(
+            code_builder.vm_block_stack[0].value_types.as_slice(),
+            &[Some(ValueType::I64)]
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(
+        expected = "Wrong number of values at the end of a IF block arm. Expected 1, found 0"
+    )]
+    fn then_arm_without_result() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i32_const(1);
+        code_builder.begin_block(IF, Some(ValueType::I32));
+        code_builder.else_();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(
+        expected = "Wrong number of values at the end of a IF block arm. Expected 1, found 2"
+    )]
+    fn else_arm_with_extra_value() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i32_const(1);
+        code_builder.begin_block(IF, Some(ValueType::I32));
+        code_builder.i32_const(10);
+        code_builder.else_();
+        code_builder.i32_const(20);
+        code_builder.i32_const(30);
+        code_builder.end_block_with_result(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Function declares 3 locals, more than the limit of 2")]
+    fn too_many_locals() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+        code_builder.set_max_locals(2);
+
+        code_builder.build_fn_header_and_footer(&[ValueType::I32; 3], 0, None);
+    }
+
+    #[test]
+    fn push_int_const_widths() {
+        use IntWidth::*;
+
+        let arena = Bump::new();
+        let cases = [
+            (U8, 200, ValueType::I32),
+            (I8, -128, ValueType::I32),
+            (U16, 65_535, ValueType::I32),
+            (I16, -300, ValueType::I32),
+            (U32, u32::MAX as i64, ValueType::I32),
+            (I32, i32::MIN as i64, ValueType::I32),
+            (U64, -1, ValueType::I64),
+            (I64, 1 << 40, ValueType::I64),
+        ];
+
+        for (width, value, value_type) in cases {
+            let mut code_builder = CodeBuilder::new(&arena);
+            code_builder.push_int_const(value, width);
+
+            let mut expected = CodeBuilder::new(&arena);
+            match value_type {
+                ValueType::I32 => expected.i32_const(value as i32),
+                _ => expected.i64_const(value),
+            }
+
+            assert_eq!(code_builder.code, expected.code, "{:?}", width);
+            assert_eq!(
+                code_builder.vm_block_stack[0].value_types.as_slice(),
+                &[Some(value_type)]
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Constant 4294967296 is out of range for I32")]
+    fn push_int_const_out_of_range() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.push_int_const(1 << 32, IntWidth::I32);
+    }
+
+    #[test]
+    #[should_panic(expected = "Constant 256 is out of range for U8")]
+    fn push_int_const_out_of_range_unsigned() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.push_int_const(256, IntWidth::U8);
+    }
+
+    #[test]
+    fn push_float_const_widths() {
+        let arena = Bump::new();
+
+        let mut code_builder = CodeBuilder::new(&arena);
+        code_builder.push_float_const(1.5, FloatWidth::F32);
+        code_builder.push_float_const(1.5, FloatWidth::F64);
+
+        let mut expected = CodeBuilder::new(&arena);
+        expected.f32_const(1.5);
+        expected.f64_const(1.5);
+
+        assert_eq!(code_builder.code, expected.code);
+        assert_eq!(
+            code_builder.vm_block_stack[0].value_types.as_slice(),
+            &[Some(ValueType::F32), Some(ValueType::F64)]
+        );
+    }
+
+    #[test]
+    fn describe_symbol_states() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+        let sym = Symbol::ATTR_ATTR;
+
+        assert_eq!(
+            VmSymbolState::NotYetPushed.describe(&code_builder),
+            "NotYetPushed"
+        );
+
+        code_builder.i32_const(7);
+        let pushed = code_builder.set_top_symbol(sym);
+        assert_eq!(pushed.describe(&code_builder), "Pushed at #2 (I32CONST 7)");
+
+        code_builder.get_local(LocalId(3));
+        let second = code_builder.set_top_symbol(sym);
+        code_builder.i32_add();
+        let popped = match second {
+            VmSymbolState::Pushed { pushed_at } => VmSymbolState::Popped { pushed_at },
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            popped.describe(&code_builder),
+            "Popped, pushed at #4 (GETLOCAL 3)"
+        );
+
+        // an offset in the middle of an instruction
+        let bogus = VmSymbolState::Pushed { pushed_at: 3 };
+        assert_eq!(
+            bogus.describe(&code_builder),
+            "Pushed at #3 (no instruction ends here)"
+        );
+    }
+
+    #[test]
+    fn with_capacities_and_stats() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::with_capacities(&arena, 16, 2);
+
+        let empty = code_builder.capacity_stats();
+        assert!(empty.code_cap >= 16);
+        assert!(empty.stack_cap >= 2);
+        assert_eq!(
+            (empty.code_len, empty.stack_len, empty.insertions_len),
+            (0, 0, 0)
+        );
+
+        code_builder.i32_const(1);
+        code_builder.i32_const(2);
+
+        let stats = code_builder.capacity_stats();
+        assert_eq!(stats.code_len, 4); // two i32.const with one-byte LEB immediates
+        assert_eq!(stats.stack_len, 2);
+
+        // reset keeps the allocations
+        code_builder.reset_for_next_function();
+        let reset = code_builder.capacity_stats();
+        assert_eq!(reset.code_len, 0);
+        assert_eq!(reset.stack_len, 0);
+        assert_eq!(reset.code_cap, stats.code_cap);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "only 1 available (source region @3-10)")]
+    fn stack_underflow_reports_region() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+        let region = Region::new(Position::new(3), Position::new(10));
+
+        code_builder.i32_const(1);
+        code_builder.with_region(region, |cb| cb.call(0, 2, true));
+    }
+
+    #[test]
+    fn read_vm_stack_symbols() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        assert!(code_builder.vm_stack_symbols().is_empty());
+
+        code_builder.i32_const(1);
+        code_builder.set_top_symbol(Symbol::ARG_1);
+        code_builder.i32_const(2);
+        code_builder.set_top_symbol(Symbol::ARG_2);
+        code_builder.i64_const(3);
+
+        assert_eq!(
+            code_builder.vm_stack_symbols(),
+            &[Symbol::ARG_1, Symbol::ARG_2, Symbol::WASM_TMP]
+        );
+        assert!(code_builder.verify_stack_match(&[Symbol::ARG_2, Symbol::WASM_TMP]));
+
+        // a new block starts with an empty stack
+        code_builder.block();
+        assert!(code_builder.vm_stack_symbols().is_empty());
+        code_builder.end();
+
+        code_builder.drop_();
+        assert_eq!(
+            code_builder.vm_stack_symbols(),
+            &[Symbol::ARG_1, Symbol::ARG_2]
+        );
+    }
+
+    #[test]
+    fn reuse_for_several_functions() {
+        let arena = Bump::new();
+
+        let build_fn = |code_builder: &mut CodeBuilder, x: i32| {
+            let local_id = LocalId(0);
+            code_builder.i32_const(x);
+            code_builder.set_local(local_id);
+            code_builder.get_local(local_id);
+            code_builder.block();
+            code_builder.i32_const(x);
+            code_builder.drop_();
+            code_builder.end();
+            code_builder.build_fn_header_and_footer(&[ValueType::I32], 16, Some(local_id));
+        };
+
+        let mut reused_builder = CodeBuilder::new(&arena);
+
+        for x in [1, 200, -3] {
+            let mut expected = std::vec::Vec::new();
+            let mut fresh_builder = CodeBuilder::new(&arena);
+            build_fn(&mut fresh_builder, x);
+            fresh_builder.serialize_without_relocs(&mut expected);
+
+            let mut actual = std::vec::Vec::new();
+            build_fn(&mut reused_builder, x);
+            reused_builder.serialize_without_relocs(&mut actual);
+            reused_builder.reset_for_next_function();
+
+            assert_eq!(actual, expected);
+            assert!(reused_builder.vm_stack_symbols().is_empty());
+            assert_eq!(reused_builder.size(), 0);
+        }
+    }
+
+    #[test]
+    fn address_type_ok() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i32_const(8);
+        code_builder.i64_const(42);
+        code_builder.i64_store(Align::Bytes8, 0);
+
+        code_builder.i32_const(8);
+        code_builder.i64_load(Align::Bytes8, 0);
+        code_builder.drop_();
+
+        // unknown types are not checked
+        code_builder.get_local(LocalId(0));
+        code_builder.f64_load(Align::Bytes8, 0);
+        code_builder.drop_();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "needs an I32 memory address but the address operand is I64")]
+    fn load_address_type_mismatch() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i64_const(8);
+        code_builder.i32_load(Align::Bytes4, 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "needs an I32 memory address but the address operand is F64")]
+    fn store_address_type_mismatch() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.f64_const(8.0);
+        code_builder.i32_const(42);
+        code_builder.i32_store(Align::Bytes4, 0);
+    }
+
+    #[test]
+    fn select_stack() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i64_const(1);
+        code_builder.i64_const(2);
+        code_builder.i32_const(0);
+        code_builder.select();
+        assert_eq!(code_builder.vm_stack_symbols().len(), 1);
+
+        // operands of unknown type are not checked
+        code_builder.get_local(LocalId(0));
+        code_builder.i32_const(1);
+        code_builder.select();
+        assert_eq!(code_builder.vm_stack_symbols().len(), 1);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "select needs two values of the same type but got I32 and F64")]
+    fn select_type_mismatch() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i32_const(1);
+        code_builder.f64_const(2.0);
+        code_builder.i32_const(0);
+        code_builder.select();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "select needs an I32 condition but got I64")]
+    fn select_condition_type_mismatch() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i32_const(1);
+        code_builder.i32_const(2);
+        code_builder.i64_const(0);
+        code_builder.select();
+    }
+
+    #[test]
+    fn br_if_pops_condition() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.block();
+        code_builder.i32_const(7);
+        code_builder.i32_const(1);
+        code_builder.br_if(0);
+        assert_eq!(code_builder.vm_stack_symbols().len(), 1);
+
+        code_builder.drop_();
+        code_builder.end();
+    }
+
+    #[test]
+    fn br_table_targets() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        // a dense `when` on 0, 1 and 2, where anything else goes to the outermost block
+        code_builder.block();
+        code_builder.block();
+        code_builder.block();
+        code_builder.i32_const(5);
+        code_builder.i32_const(1);
+        let table_start = code_builder.code.len();
+        code_builder.br_table(&[0, 1, 2], 2);
+
+        // only the index is popped
+        assert_eq!(code_builder.vm_stack_symbols().len(), 1);
+
+        let code = &code_builder.code;
+        assert_eq!(code[table_start], BRTABLE as u8);
+
+        let mut cursor = table_start + 1;
+        let n_targets = u32::parse((), code, &mut cursor).unwrap();
+        let targets: std::vec::Vec<u32> = (0..n_targets)
+            .map(|_| u32::parse((), code, &mut cursor).unwrap())
+            .collect();
+        let default = u32::parse((), code, &mut cursor).unwrap();
+
+        assert_eq!(targets, [0, 1, 2]);
+        assert_eq!(default, 2);
+        assert_eq!(cursor, code.len());
+    }
+
+    #[test]
+    fn memory_size_and_grow() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.memory_size();
+        assert_eq!(code_builder.vm_stack_symbols().len(), 1);
+
+        code_builder.memory_grow();
+        assert_eq!(code_builder.vm_stack_symbols().len(), 1);
+
+        // the results are i32, so they can be used as addresses
+        code_builder.i32_load(Align::Bytes4, 0);
+
+        assert_eq!(
+            &code_builder.code[..],
+            &[
+                CURRENTMEMORY as u8,
+                0,
+                GROWMEMORY as u8,
+                0,
+                I32LOAD as u8,
+                Align::Bytes4 as u8,
+                0
+            ]
+        );
+    }
+
+    #[test]
+    fn stack_frame_prologue_and_epilogue() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+        let frame_pointer = LocalId(0);
+
+        code_builder.i32_const(7);
+        code_builder.drop_();
+        code_builder.build_fn_header_and_footer(&[ValueType::I32], 20, Some(frame_pointer));
+
+        let mut actual = std::vec::Vec::new();
+        code_builder.serialize_without_relocs(&mut actual);
+
+        let sp = STACK_POINTER_GLOBAL_ID as u8;
+        let frame_size = 32; // 20 rounded up to FRAME_ALIGNMENT_BYTES
+        let expected_body = [
+            // locals: one batch of one i32
+            1,
+            1,
+            ValueType::I32 as u8,
+            // prologue
+            GETGLOBAL as u8,
+            sp,
+            I32CONST as u8,
+            frame_size,
+            I32SUB as u8,
+            TEELOCAL as u8,
+            0,
+            SETGLOBAL as u8,
+            sp,
+            // body
+            I32CONST as u8,
+            7,
+            DROP as u8,
+            // epilogue
+            GETLOCAL as u8,
+            0,
+            I32CONST as u8,
+            frame_size,
+            I32ADD as u8,
+            SETGLOBAL as u8,
+            sp,
+            END as u8,
+        ];
+
+        assert_eq!(actual[0] as usize, expected_body.len());
+        assert_eq!(&actual[1..], &expected_body);
+    }
+
+    #[test]
+    fn no_stack_frame() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i32_const(7);
+        code_builder.drop_();
+        code_builder.build_fn_header_and_footer(&[], 0, None);
+
+        let mut actual = std::vec::Vec::new();
+        code_builder.serialize_without_relocs(&mut actual);
+
+        assert_eq!(actual, [5, 0, I32CONST as u8, 7, DROP as u8, END as u8]);
+    }
+
+    #[test]
+    fn is_empty() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+        assert!(code_builder.is_empty());
+
+        code_builder.i32_const(1);
+        let vm_state = code_builder.set_top_symbol(Symbol::ARG_1);
+        let vm_state = code_builder
+            .load_symbol(Symbol::ARG_1, vm_state, LocalId(0))
+            .unwrap();
+        code_builder.load_symbol(Symbol::ARG_1, vm_state, LocalId(0));
+        assert!(!code_builder.is_empty());
+
+        code_builder.reset_for_next_function();
+        assert!(code_builder.is_empty());
+    }
+
+    #[test]
+    fn splice_inline() {
+        let arena = Bump::new();
+
+        // A helper that adds 1 to its argument
+        let mut callee = CodeBuilder::new(&arena);
+        callee.get_local(LocalId(0));
+        callee.i32_const(1);
+        callee.i32_add();
+
+        let mut caller = CodeBuilder::new(&arena);
+        caller.i32_const(41);
+        caller.set_local(LocalId(2));
+        caller.splice_inline(&callee, 2);
+
+        assert_eq!(caller.vm_stack_symbols(), &[Symbol::WASM_TMP]);
+
+        caller.build_fn_header_and_footer(&[], 0, None);
+        let mut buffer = std::vec::Vec::with_capacity(caller.size());
+        caller.serialize_without_relocs(&mut buffer);
+
+        let mut expected = CodeBuilder::new(&arena);
+        expected.i32_const(41);
+        expected.set_local(LocalId(2));
+        expected.get_local(LocalId(2));
+        expected.i32_const(1);
+        expected.i32_add();
+        expected.build_fn_header_and_footer(&[], 0, None);
+        let mut expected_buffer = std::vec::Vec::with_capacity(expected.size());
+        expected.serialize_without_relocs(&mut expected_buffer);
+
+        assert_eq!(buffer, expected_buffer);
+    }
+
+    #[test]
+    fn estimated_byte_size() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i32_const(1_000_000);
+        let pushed_at = code_builder.code.len();
+        code_builder.add_insertion(pushed_at, SETLOCAL, 0);
+        code_builder.get_local(LocalId(0));
+        code_builder.get_local(LocalId(0));
+        code_builder.i32_add();
+        code_builder.i64_const(-1);
+        code_builder.drop_();
+
+        assert_eq!(code_builder.instruction_count(), 7);
+
+        let estimate = code_builder.estimated_byte_size();
+
+        // Declaring the local adds a batch (count and type) to the header
+        code_builder.build_fn_header_and_footer(&[ValueType::I32], 0, None);
+        let mut buffer = std::vec::Vec::with_capacity(code_builder.size());
+        code_builder.serialize_without_relocs(&mut buffer);
+
+        assert!(estimate <= buffer.len() && buffer.len() <= estimate + 2);
+        assert_eq!(code_builder.estimated_byte_size(), buffer.len());
+    }
+
+    #[test]
+    fn estimated_byte_size_without_locals_is_exact() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        for _ in 0..100 {
+            code_builder.i32_const(0);
+            code_builder.drop_();
+        }
+        let estimate = code_builder.estimated_byte_size();
+
+        code_builder.build_fn_header_and_footer(&[], 0, None);
+        let mut buffer = std::vec::Vec::with_capacity(code_builder.size());
+        code_builder.serialize_without_relocs(&mut buffer);
+
+        // the inner length is over 127, so it takes 2 bytes
+        assert_eq!(estimate, buffer.len());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Insertion at byte offset 3 is past the end of the function code (0 bytes)"
+    )]
+    fn leftover_insertion_without_code() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.add_insertion(3, SETLOCAL, 0);
+        code_builder.build_fn_header_and_footer(&[], 0, None);
+    }
+
+    #[test]
+    fn insertions_at_same_position() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        code_builder.i32_const(1);
+        let pushed_at = code_builder.code.len();
+        code_builder.add_insertion(pushed_at, TEELOCAL, 0);
+        code_builder.add_insertion(pushed_at, TEELOCAL, 1);
+        code_builder.drop_();
+        code_builder.build_fn_header_and_footer(&[], 0, None);
+
+        let mut buffer = std::vec::Vec::with_capacity(code_builder.size());
+        code_builder.serialize_without_relocs(&mut buffer);
+
+        assert!(buffer.ends_with(&[
+            I32CONST as u8,
+            1,
+            TEELOCAL as u8,
+            0,
+            TEELOCAL as u8,
+            1,
+            DROP as u8,
+            END as u8
+        ]));
+    }
+
+    #[test]
+    fn splice_inline_insertions() {
+        let arena = Bump::new();
+
+        // Use the same value twice, so that a local.tee is inserted where it was pushed
+        let mut callee = CodeBuilder::new(&arena);
+        callee.i32_const(3);
+        let vm_state = callee.set_top_symbol(Symbol::ARG_1);
+        let vm_state = callee
+            .load_symbol(Symbol::ARG_1, vm_state, LocalId(0))
+            .unwrap();
+        callee.load_symbol(Symbol::ARG_1, vm_state, LocalId(0));
+        callee.i32_mul();
+
+        let mut caller = CodeBuilder::new(&arena);
+        caller.splice_inline(&callee, 5);
+
+        let interns = Interns {
+            module_ids: roc_module::symbol::ModuleIds::default(),
+            all_ident_ids: roc_module::symbol::IdentIds::exposed_builtins(0),
+        };
+        assert_eq!(
+            caller.debug_dump(&interns),
+            concat!(
+                "   I32CONST 03\n",
+                "   TEELOCAL 05\n",
+                "   GETLOCAL 05\n",
+                "   I32MUL\n",
+                "BLOCK stack: [#wasm_tmp]\n",
+            )
+        );
+    }
+
+    #[test]
+    fn debug_dump() {
+        use roc_module::symbol::{IdentIds, ModuleIds};
+
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+        let interns = Interns {
+            module_ids: ModuleIds::default(),
+            all_ident_ids: IdentIds::exposed_builtins(0),
+        };
+
+        code_builder.i32_const(5);
+        let vm_state = code_builder.set_top_symbol(Symbol::ARG_1);
+
+        // Load the Symbol twice, so it has to be stored in a local where it was pushed
+        let vm_state = code_builder
+            .load_symbol(Symbol::ARG_1, vm_state, LocalId(3))
+            .unwrap();
+        let moved_to_local = code_builder.load_symbol(Symbol::ARG_1, vm_state, LocalId(3));
+        assert!(moved_to_local.is_none());
+
+        let dump = code_builder.debug_dump(&interns);
+
+        assert_eq!(
+            dump,
+            concat!(
+                "   I32CONST 05\n",
+                ">> TEELOCAL 03\n",
+                "   GETLOCAL 03\n",
+                "BLOCK stack: [#arg1, #arg1]\n",
+            )
+        );
+    }
+
+    #[test]
+    fn region_is_restored() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+        let outer = Region::new(Position::new(0), Position::new(20));
+        let inner = Region::new(Position::new(5), Position::new(8));
+
+        code_builder.with_region(outer, |cb| {
+            cb.with_region(inner, |cb| cb.nop());
+            assert_eq!(cb.current_region, Some(outer));
+        });
+        assert_eq!(code_builder.current_region, None);
+    }
+
+    #[test]
+    fn simulate_valid_stream() {
+        let arena = Bump::new();
+        let signatures = [Signature {
+            param_types: Vec::from_iter_in([ValueType::I32], &arena),
+            ret_type: Some(ValueType::I32),
+        }];
+        let code = [
+            I32CONST as u8,
+            1,
+            I32CONST as u8,
+            2,
+            I32ADD as u8,
+            CALL as u8,
+            0,
+            BLOCK as u8,
+            BLOCK_NO_RESULT,
+            I32CONST as u8,
+            3,
+            DROP as u8,
+            END as u8,
+        ];
+
+        let stack = simulate(&arena, &code, &signatures).unwrap();
+        assert_eq!(stack.as_slice(), &[Symbol::WASM_TMP]);
+    }
+
+    #[test]
+    fn simulate_underflow() {
+        let arena = Bump::new();
+        let code = [
+            I32CONST as u8,
+            1,
+            BLOCK as u8,
+            BLOCK_NO_RESULT,
+            I32CONST as u8,
+            2,
+            I32ADD as u8,
+            END as u8,
+        ];
+
+        match simulate(&arena, &code, &[]) {
+            Err(SimError::Underflow {
+                at_index,
+                instruction,
+            }) => {
+                assert_eq!(at_index, 6);
+                assert_eq!(instruction, I32ADD);
+            }
+            other => panic!("Expected underflow but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn opcode_try_from_u8() {
+        assert_eq!(OpCode::try_from(0x0b), Ok(END));
+        assert_eq!(OpCode::try_from(0xbf), Ok(F64REINTERPRETI64));
+        assert!(OpCode::try_from(0x06).is_err());
+        assert!(OpCode::try_from(0xc0).is_err());
+    }
+
+    #[test]
+    fn simulate_code_after_unreachable() {
+        let arena = Bump::new();
+        let code = [
+            BLOCK as u8,
+            BLOCK_NO_RESULT,
+            UNREACHABLE as u8,
+            // dead code popping from an empty stack, as Wasm validation allows
+            I32ADD as u8,
+            DROP as u8,
+            END as u8,
+            // the enclosing block is still checked normally
+            I32CONST as u8,
+            1,
+            I32ADD as u8,
+        ];
+
+        match simulate(&arena, &code, &[]) {
+            Err(SimError::Underflow {
+                at_index,
+                instruction,
+            }) => {
+                assert_eq!(at_index, 8);
+                assert_eq!(instruction, I32ADD);
+            }
+            other => panic!("Expected underflow but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn simulate_truncated_input() {
+        let arena = Bump::new();
+        let truncated: [&[u8]; 5] = [
+            &[BRTABLE as u8],
+            &[BRTABLE as u8, 0x80],
+            &[I32CONST as u8, 0, BRTABLE as u8, 0x02, 0x00],
+            &[BLOCK as u8],
+            &[CALL as u8, 0x80, 0x80],
+        ];
+
+        for code in truncated {
+            assert!(
+                simulate(&arena, code, &[]).is_err(),
+                "Expected an error for {:2x?}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn top_symbol_and_nth_from_top() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+
+        assert_eq!(code_builder.top_symbol(), None);
+        assert_eq!(code_builder.nth_from_top(0), None);
+
+        code_builder.i32_const(1);
+        code_builder.set_top_symbol(Symbol::ARG_1);
+        code_builder.i32_const(2);
+        code_builder.set_top_symbol(Symbol::ARG_2);
+        code_builder.i32_const(3);
+
+        assert_eq!(code_builder.top_symbol(), Some(Symbol::WASM_TMP));
+        assert_eq!(code_builder.nth_from_top(0), Some(Symbol::WASM_TMP));
+        assert_eq!(code_builder.nth_from_top(1), Some(Symbol::ARG_2));
+        assert_eq!(code_builder.nth_from_top(2), Some(Symbol::ARG_1));
+        assert_eq!(code_builder.nth_from_top(3), None);
+
+        code_builder.set_top_symbol(Symbol::ARG_3);
+        assert_eq!(code_builder.top_symbol(), Some(Symbol::ARG_3));
+    }
+
+    fn emit_if_else_and_loads(code_builder: &mut CodeBuilder) {
+        code_builder.i32_const(16);
+        code_builder.i32_const(1);
+        code_builder.begin_block(IF, Some(ValueType::I32));
+        code_builder.get_local(LocalId(0));
+        code_builder.i32_load(Align::Bytes4, 8);
+        code_builder.else_();
+        code_builder.i32_const(20);
+        code_builder.end_block_with_result(1);
+        code_builder.i32_add();
+        code_builder.block();
+        code_builder.i64_const(3);
+        code_builder.drop_();
+        code_builder.end();
+    }
+
+    #[test]
+    fn without_stack_simulation_code_is_identical() {
+        let arena = Bump::new();
+
+        let mut simulated = CodeBuilder::new(&arena);
+        emit_if_else_and_loads(&mut simulated);
+
+        let mut trusted = CodeBuilder::new(&arena);
+        trusted.set_simulate_stack(false);
+        emit_if_else_and_loads(&mut trusted);
+
+        assert_eq!(simulated.code, trusted.code);
+        assert_eq!(trusted.vm_block_stack.len(), 1);
+        assert!(trusted.current_stack().is_empty());
+    }
+
+    #[test]
+    fn without_stack_simulation_end_keeps_unused_values() {
+        let arena = Bump::new();
+
+        let mut simulated = CodeBuilder::new(&arena);
+        simulated.block();
+        simulated.i64_const(3);
+        simulated.end();
+
+        let mut trusted = CodeBuilder::new(&arena);
+        trusted.set_simulate_stack(false);
+        trusted.block();
+        trusted.i64_const(3);
+        trusted.end();
+
+        let block_and_const = [BLOCK as u8, BLOCK_NO_RESULT, I64CONST as u8, 3];
+        assert_eq!(simulated.code[..4], block_and_const);
+        assert_eq!(simulated.code[4..], [DROP as u8, END as u8]);
+        assert_eq!(trusted.code[..4], block_and_const);
+        assert_eq!(trusted.code[4..], [END as u8]);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs the VM stack simulation")]
+    fn load_symbol_without_stack_simulation() {
+        let arena = Bump::new();
+        let mut code_builder = CodeBuilder::new(&arena);
+        code_builder.set_simulate_stack(false);
+
+        code_builder.i32_const(1);
+        code_builder.load_symbol(
+            Symbol::ARG_1,
+            VmSymbolState::Pushed { pushed_at: 0 },
+            LocalId(0),
+        );
+    }
+}
@@ -183,6 +183,20 @@ pub enum OpCode {
     F64REINTERPRETI64 = 0xbf,
 }
 
+impl TryFrom<u8> for OpCode {
+    type Error = String;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            0x00..=0x05 | 0x0b..=0x11 | 0x1a..=0x1b | 0x20..=0x24 | 0x28..=0xbf => {
+                // Safe because these are exactly the discriminants of the variants above
+                Ok(unsafe { std::mem::transmute(byte) })
+            }
+            _ => Err(format!("Unknown Wasm instruction 0x{:02x}", byte)),
+        }
+    }
+}
+
 /// The format of the *immediate* operands of an operator
 /// Immediates appear directly in the byte stream after the opcode,
 /// rather than being popped off the value stack. These are the possible forms.
@@ -198,11 +212,11 @@ enum OpImmediates {
     BrTable,
 }
 
-fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
+fn immediates_for(op: OpCode) -> OpImmediates {
     use OpCode::*;
     use OpImmediates::*;
 
-    let imm = match op {
+    match op {
         UNREACHABLE => NoImmediate,
         NOP => NoImmediate,
         BLOCK | LOOP | IF => Byte1,
@@ -248,30 +262,67 @@ fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
         | I32REINTERPRETF32 | I64REINTERPRETF64 | F32REINTERPRETI32 | F64REINTERPRETI64 => {
             NoImmediate
         }
+    }
+}
+
+/// Static stack effect of an instruction as (pops, push), in the same terms as CodeBuilder.
+/// Returns None for control flow and calls, whose effect depends on blocks or signatures.
+pub fn stack_effect(op: OpCode) -> Option<(usize, bool)> {
+    use OpCode::*;
 
-        // Catch-all in case of an invalid cast from u8 to OpCode while parsing binary
-        // (rustc keeps this code, I verified in Compiler Explorer)
-        #[allow(unreachable_patterns)]
-        _ => return Err(format!("Unknown Wasm instruction 0x{:02x}", op as u8)),
+    let effect = match op {
+        UNREACHABLE | NOP | RETURN => (0, false),
+        BLOCK | LOOP | IF | ELSE | END | BR | BRIF | BRTABLE | CALL | CALLINDIRECT => return None,
+        DROP => (1, false),
+        SELECT => (3, true),
+        GETLOCAL | GETGLOBAL => (0, true),
+        SETLOCAL | SETGLOBAL => (1, false),
+        TEELOCAL => (0, false),
+
+        I32LOAD | I64LOAD | F32LOAD | F64LOAD | I32LOAD8S | I32LOAD8U | I32LOAD16S | I32LOAD16U
+        | I64LOAD8S | I64LOAD8U | I64LOAD16S | I64LOAD16U | I64LOAD32S | I64LOAD32U => (1, true),
+
+        I32STORE | I64STORE | F32STORE | F64STORE | I32STORE8 | I32STORE16 | I64STORE8
+        | I64STORE16 | I64STORE32 => (2, false),
+
+        CURRENTMEMORY => (0, true),
+        GROWMEMORY => (1, true),
+
+        I32CONST | I64CONST | F32CONST | F64CONST => (0, true),
+
+        I32EQZ | I64EQZ | I32CLZ | I32CTZ | I32POPCNT | I64CLZ | I64CTZ | I64POPCNT | F32ABS
+        | F32NEG | F32CEIL | F32FLOOR | F32TRUNC | F32NEAREST | F32SQRT | F64ABS | F64NEG
+        | F64CEIL | F64FLOOR | F64TRUNC | F64NEAREST | F64SQRT | I32WRAPI64 | I32TRUNCSF32
+        | I32TRUNCUF32 | I32TRUNCSF64 | I32TRUNCUF64 | I64EXTENDSI32 | I64EXTENDUI32
+        | I64TRUNCSF32 | I64TRUNCUF32 | I64TRUNCSF64 | I64TRUNCUF64 | F32CONVERTSI32
+        | F32CONVERTUI32 | F32CONVERTSI64 | F32CONVERTUI64 | F32DEMOTEF64 | F64CONVERTSI32
+        | F64CONVERTUI32 | F64CONVERTSI64 | F64CONVERTUI64 | F64PROMOTEF32 | I32REINTERPRETF32
+        | I64REINTERPRETF64 | F32REINTERPRETI32 | F64REINTERPRETI64 => (1, true),
+
+        I32EQ | I32NE | I32LTS | I32LTU | I32GTS | I32GTU | I32LES | I32LEU | I32GES | I32GEU
+        | I64EQ | I64NE | I64LTS | I64LTU | I64GTS | I64GTU | I64LES | I64LEU | I64GES | I64GEU
+        | F32EQ | F32NE | F32LT | F32GT | F32LE | F32GE | F64EQ | F64NE | F64LT | F64GT | F64LE
+        | F64GE | I32ADD | I32SUB | I32MUL | I32DIVS | I32DIVU | I32REMS | I32REMU | I32AND
+        | I32OR | I32XOR | I32SHL | I32SHRS | I32SHRU | I32ROTL | I32ROTR | I64ADD | I64SUB
+        | I64MUL | I64DIVS | I64DIVU | I64REMS | I64REMU | I64AND | I64OR | I64XOR | I64SHL
+        | I64SHRS | I64SHRU | I64ROTL | I64ROTR | F32ADD | F32SUB | F32MUL | F32DIV | F32MIN
+        | F32MAX | F32COPYSIGN | F64ADD | F64SUB | F64MUL | F64DIV | F64MIN | F64MAX
+        | F64COPYSIGN => (2, true),
     };
 
-    Ok(imm)
+    Some(effect)
 }
 
 impl SkipBytes for OpCode {
     fn skip_bytes(bytes: &[u8], cursor: &mut usize) -> Result<(), ParseError> {
         use OpImmediates::*;
 
-        let opcode_byte: u8 = bytes[*cursor];
-
-        let opcode: OpCode = unsafe { std::mem::transmute(opcode_byte) };
-        // will return Err if transmute was invalid
-        let immediates = immediates_for(opcode).map_err(|message| ParseError {
+        let opcode = OpCode::try_from(bytes[*cursor]).map_err(|message| ParseError {
             message,
             offset: *cursor,
         })?;
 
-        match immediates {
+        match immediates_for(opcode) {
             NoImmediate => {
                 *cursor += 1;
             }
@@ -299,8 +350,9 @@ impl SkipBytes for OpCode {
             }
             BrTable => {
                 *cursor += 1;
-                let n_labels = 1 + u32::parse((), bytes, cursor)?;
-                for _ in 0..n_labels {
+                // the targets are followed by a default target
+                let n_targets = u32::parse((), bytes, cursor)?;
+                for _ in 0..=n_targets {
                     u32::skip_bytes(bytes, cursor)?;
                 }
             }
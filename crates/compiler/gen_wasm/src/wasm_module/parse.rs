@@ -31,7 +31,8 @@ fn decode_u32(bytes: &[u8]) -> Result<(u32, usize), ()> {
 
 impl Parse<()> for u32 {
     fn parse(_ctx: (), bytes: &[u8], cursor: &mut usize) -> Result<Self, ParseError> {
-        match decode_u32(&bytes[*cursor..]) {
+        let remaining = bytes.get(*cursor..).unwrap_or_default();
+        match decode_u32(remaining) {
             Ok((value, len)) => {
                 *cursor += len;
                 Ok(value)
@@ -40,7 +41,7 @@ impl Parse<()> for u32 {
                 offset: *cursor,
                 message: format!(
                     "Failed to decode u32 as LEB-128 from bytes: {:2x?}",
-                    &bytes[*cursor..][..MAX_SIZE_ENCODED_U32]
+                    &remaining[..remaining.len().min(MAX_SIZE_ENCODED_U32)]
                 ),
             }),
         }
@@ -76,7 +77,8 @@ fn decode_i32(bytes: &[u8]) -> Result<(i32, usize), ()> {
 
 impl Parse<()> for i32 {
     fn parse(_ctx: (), bytes: &[u8], cursor: &mut usize) -> Result<Self, ParseError> {
-        match decode_i32(&bytes[*cursor..]) {
+        let remaining = bytes.get(*cursor..).unwrap_or_default();
+        match decode_i32(remaining) {
             Ok((value, len)) => {
                 *cursor += len;
                 Ok(value)
@@ -85,7 +87,7 @@ impl Parse<()> for i32 {
                 offset: *cursor,
                 message: format!(
                     "Failed to decode i32 as LEB-128 from bytes: {:2x?}",
-                    &bytes[*cursor..][..MAX_SIZE_ENCODED_U32]
+                    &remaining[..remaining.len().min(MAX_SIZE_ENCODED_U32)]
                 ),
             }),
         }
@@ -238,4 +240,16 @@ mod tests {
         assert_eq!(u32::parse((), bytes, &mut cursor).unwrap(), expected[2]);
         assert_eq!(cursor, 8);
     }
+
+    #[test]
+    fn test_parse_truncated_leb() {
+        let mut cursor = 1;
+        assert!(u32::parse((), &[0x0e, 0x80], &mut cursor).is_err());
+
+        let mut cursor = 2;
+        assert!(u32::parse((), &[0x0e, 0x80], &mut cursor).is_err());
+
+        let mut cursor = 3;
+        assert!(i32::parse((), &[0x0e, 0x80], &mut cursor).is_err());
+    }
 }
@@ -59,6 +59,12 @@ pub struct TagName(pub Uppercase);
 roc_error_macros::assert_sizeof_non_wasm!(TagName, 16);
 roc_error_macros::assert_sizeof_wasm!(TagName, 8);
 
+/// Sort (name, value) pairs by name, e.g. record fields by `Lowercase` or tags by `TagName`.
+/// Names compare by their string, so everything that sorts fields or tags agrees on the order.
+pub fn sort_by_name<N: Ord, T>(entries: &mut [(N, T)]) {
+    entries.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
+}
+
 impl TagName {
     pub fn as_ident_str(&self) -> IdentStr {
         self.0.as_ident_str().clone()
@@ -140,6 +140,44 @@ impl LowLevel {
             _ => unreachable!(),
         }
     }
+
+    /// The comparison this lowlevel performs on two numbers, if it is one
+    pub fn compare_op(&self) -> Option<CompareOp> {
+        use LowLevel::*;
+
+        match self {
+            Eq => Some(CompareOp::Eq),
+            NotEq => Some(CompareOp::Ne),
+            NumLt => Some(CompareOp::Lt),
+            NumLte => Some(CompareOp::Le),
+            NumGt => Some(CompareOp::Gt),
+            NumGte => Some(CompareOp::Ge),
+            _ => None,
+        }
+    }
+}
+
+/// A comparison between two numbers. Each backend maps it to its own instructions,
+/// depending on whether the operands are signed integers or floats.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    pub const ALL: [CompareOp; 6] = [
+        CompareOp::Eq,
+        CompareOp::Ne,
+        CompareOp::Lt,
+        CompareOp::Le,
+        CompareOp::Gt,
+        CompareOp::Ge,
+    ];
 }
 
 /// Some wrapper functions can just be replaced by lowlevels in the backend for performance.
@@ -0,0 +1,305 @@
+//! Evaluate mono expressions whose value is known at compile time, like literals and
+//! arithmetic on literals. A backend can emit a single constant instead of the computation,
+//! and only generate the branch of a `Switch` on a constant that can actually be taken.
+
+use crate::ir::{BranchInfo, Call, CallType, Expr, Literal, Stmt};
+use crate::layout::{Builtin, Layout};
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_collections::all::MutMap;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+
+/// A value that is known at compile time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstValue {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+}
+
+/// The symbols of a proc whose values are known, in scope at the current statement
+pub type ConstEnv = MutMap<Symbol, ConstValue>;
+
+impl ConstValue {
+    /// The literal a backend can emit for this value
+    pub fn to_literal(self) -> Literal<'static> {
+        match self {
+            ConstValue::Int(int) => Literal::Int(int.to_ne_bytes()),
+            ConstValue::Float(float) => Literal::Float(float),
+            ConstValue::Bool(b) => Literal::Bool(b),
+        }
+    }
+
+    /// The value that a `Switch` on this constant compares to its branches
+    fn switch_value(self) -> Option<u64> {
+        match self {
+            ConstValue::Int(int) => Some(int as u64),
+            ConstValue::Bool(b) => Some(b as u64),
+            ConstValue::Float(_) => None,
+        }
+    }
+}
+
+/// The value of `expr` with the given layout, if it only depends on literals and on the symbols in `consts`.
+/// Operations that would overflow or panic at runtime are not folded, so the runtime still reports them.
+pub fn try_eval_const(expr: &Expr, layout: &Layout, consts: &ConstEnv) -> Option<ConstValue> {
+    match expr {
+        Expr::Literal(literal) => eval_literal(literal, layout),
+        Expr::Call(Call {
+            call_type: CallType::LowLevel { op, .. },
+            arguments,
+        }) => {
+            let mut args = Vec::with_capacity(arguments.len());
+            for symbol in arguments.iter() {
+                args.push(*consts.get(symbol)?);
+            }
+
+            eval_low_level(*op, &args, layout)
+        }
+        _ => None,
+    }
+}
+
+/// The statement that a `Switch` on the constant `cond` jumps to
+pub fn fold_switch<'a, 'b>(
+    cond: ConstValue,
+    branches: &'b [(u64, BranchInfo<'a>, Stmt<'a>)],
+    default_branch: &'b Stmt<'a>,
+) -> &'b Stmt<'a> {
+    let value = cond.switch_value();
+
+    branches
+        .iter()
+        .find(|(branch_value, _, _)| Some(*branch_value) == value)
+        .map(|(_, _, stmt)| stmt)
+        .unwrap_or(default_branch)
+}
+
+fn eval_literal(literal: &Literal, layout: &Layout) -> Option<ConstValue> {
+    match (literal, layout) {
+        (Literal::Int(bytes), Layout::Builtin(Builtin::Bool)) => {
+            Some(ConstValue::Bool(i128::from_ne_bytes(*bytes) != 0))
+        }
+        (Literal::Int(bytes), Layout::Builtin(Builtin::Int(int_width))) => {
+            let int = i128::from_ne_bytes(*bytes);
+
+            if fits_in(*int_width, int) {
+                Some(ConstValue::Int(int))
+            } else {
+                None
+            }
+        }
+        (Literal::Float(float), Layout::Builtin(Builtin::Float(FloatWidth::F64))) => {
+            Some(ConstValue::Float(*float))
+        }
+        // the literal is stored as an f64, but at runtime it is the nearest f32
+        (Literal::Float(float), Layout::Builtin(Builtin::Float(FloatWidth::F32))) => {
+            Some(ConstValue::Float(*float as f32 as f64))
+        }
+        (Literal::Bool(b), _) => Some(ConstValue::Bool(*b)),
+        _ => None,
+    }
+}
+
+fn eval_low_level(op: LowLevel, args: &[ConstValue], layout: &Layout) -> Option<ConstValue> {
+    use ConstValue::*;
+    use LowLevel::*;
+
+    match (op, args) {
+        (NumAdd | NumSub | NumMul, [Int(a), Int(b)]) => {
+            let result = match op {
+                NumAdd => a.checked_add(*b)?,
+                NumSub => a.checked_sub(*b)?,
+                _ => a.checked_mul(*b)?,
+            };
+
+            match layout {
+                Layout::Builtin(Builtin::Int(int_width)) if fits_in(*int_width, result) => {
+                    Some(Int(result))
+                }
+                _ => None,
+            }
+        }
+        (NumAdd | NumSub | NumMul, [Float(a), Float(b)]) => {
+            let result = match op {
+                NumAdd => a + b,
+                NumSub => a - b,
+                _ => a * b,
+            };
+
+            match layout {
+                Layout::Builtin(Builtin::Float(FloatWidth::F64)) => Some(Float(result)),
+                // the f64 result rounds to the same f32 as the operation on f32s would
+                Layout::Builtin(Builtin::Float(FloatWidth::F32)) => {
+                    Some(Float(result as f32 as f64))
+                }
+                _ => None,
+            }
+        }
+        (NumLt | NumLte | NumGt | NumGte, [a, b]) => {
+            let ordering = match (a, b) {
+                (Int(a), Int(b)) => a.partial_cmp(b),
+                (Float(a), Float(b)) => a.partial_cmp(b),
+                _ => None,
+            }?;
+
+            Some(Bool(match op {
+                NumLt => ordering.is_lt(),
+                NumLte => ordering.is_le(),
+                NumGt => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        (Eq, [a, b]) => Some(Bool(a == b)),
+        (NotEq, [a, b]) => Some(Bool(a != b)),
+        (And, [Bool(a), Bool(b)]) => Some(Bool(*a && *b)),
+        (Or, [Bool(a), Bool(b)]) => Some(Bool(*a || *b)),
+        (Not, [Bool(a)]) => Some(Bool(!a)),
+        _ => None,
+    }
+}
+
+/// Whether `int` is in the range of `int_width`. U128 values above i128::MAX are not represented,
+/// so none of them are folded.
+fn fits_in(int_width: IntWidth, int: i128) -> bool {
+    let bits = int_width.stack_size() * 8;
+
+    match (int_width.is_signed(), bits) {
+        (true, 128) => true,
+        (false, 128) => int >= 0,
+        (true, _) => {
+            let max = (1i128 << (bits - 1)) - 1;
+            (-max - 1..=max).contains(&int)
+        }
+        (false, _) => (0..1i128 << bits).contains(&int),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ir::UpdateModeId;
+    use bumpalo::Bump;
+
+    const I64: Layout = Layout::Builtin(Builtin::Int(IntWidth::I64));
+    const U8: Layout = Layout::Builtin(Builtin::Int(IntWidth::U8));
+    const F32: Layout = Layout::Builtin(Builtin::Float(FloatWidth::F32));
+
+    fn int_literal(int: i128) -> Expr<'static> {
+        Expr::Literal(Literal::Int(int.to_ne_bytes()))
+    }
+
+    fn low_level<'a>(arena: &'a Bump, op: LowLevel, arguments: &[Symbol]) -> Expr<'a> {
+        Expr::Call(Call {
+            call_type: CallType::LowLevel {
+                op,
+                update_mode: UpdateModeId::BACKEND_DUMMY,
+            },
+            arguments: arena.alloc_slice_copy(arguments),
+        })
+    }
+
+    #[test]
+    fn fold_if_true() {
+        // if True then 1 else 2
+        let cond = Symbol::ARG_1;
+        let mut consts = ConstEnv::default();
+        let cond_value = try_eval_const(
+            &Expr::Literal(Literal::Bool(true)),
+            &Layout::Builtin(Builtin::Bool),
+            &consts,
+        )
+        .unwrap();
+        consts.insert(cond, cond_value);
+
+        let then_branch = Stmt::Ret(Symbol::ARG_2);
+        let else_branch = Stmt::Ret(Symbol::ARG_3);
+        let branches = [(1, BranchInfo::None, then_branch.clone())];
+
+        assert_eq!(
+            fold_switch(consts[&cond], &branches, &else_branch),
+            &then_branch
+        );
+        assert_eq!(
+            fold_switch(ConstValue::Bool(false), &branches, &else_branch),
+            &else_branch
+        );
+    }
+
+    #[test]
+    fn fold_arithmetic() {
+        let arena = Bump::new();
+        let mut consts = ConstEnv::default();
+        consts.insert(Symbol::ARG_1, ConstValue::Int(40));
+        consts.insert(Symbol::ARG_2, ConstValue::Int(2));
+
+        let sum = low_level(&arena, LowLevel::NumAdd, &[Symbol::ARG_1, Symbol::ARG_2]);
+        assert_eq!(
+            try_eval_const(&sum, &I64, &consts),
+            Some(ConstValue::Int(42))
+        );
+
+        let less = low_level(&arena, LowLevel::NumLt, &[Symbol::ARG_1, Symbol::ARG_2]);
+        assert_eq!(
+            try_eval_const(&less, &Layout::Builtin(Builtin::Bool), &consts),
+            Some(ConstValue::Bool(false))
+        );
+
+        assert_eq!(
+            try_eval_const(&int_literal(7), &I64, &consts),
+            Some(ConstValue::Int(7))
+        );
+    }
+
+    #[test]
+    fn leave_non_constant_alone() {
+        let arena = Bump::new();
+        let mut consts = ConstEnv::default();
+        consts.insert(Symbol::ARG_1, ConstValue::Int(1));
+
+        // ARG_2 is e.g. an argument of the proc
+        let sum = low_level(&arena, LowLevel::NumAdd, &[Symbol::ARG_1, Symbol::ARG_2]);
+        assert_eq!(try_eval_const(&sum, &I64, &consts), None);
+
+        // calls to other procs are not evaluated
+        assert_eq!(try_eval_const(&Expr::EmptyArray, &I64, &consts), None);
+    }
+
+    #[test]
+    fn overflow_is_not_folded() {
+        let arena = Bump::new();
+        let mut consts = ConstEnv::default();
+        consts.insert(Symbol::ARG_1, ConstValue::Int(200));
+        consts.insert(Symbol::ARG_2, ConstValue::Int(100));
+
+        // 300 does not fit in a U8, so the runtime overflow check must still happen
+        let sum = low_level(&arena, LowLevel::NumAdd, &[Symbol::ARG_1, Symbol::ARG_2]);
+        assert_eq!(try_eval_const(&sum, &U8, &consts), None);
+
+        let difference = low_level(&arena, LowLevel::NumSub, &[Symbol::ARG_1, Symbol::ARG_2]);
+        assert_eq!(
+            try_eval_const(&difference, &U8, &consts),
+            Some(ConstValue::Int(100))
+        );
+    }
+
+    #[test]
+    fn f32_literals_are_rounded() {
+        let arena = Bump::new();
+        let mut consts = ConstEnv::default();
+
+        // 16777217 is not representable as an f32, it rounds to 16777216
+        for (symbol, float) in [(Symbol::ARG_1, 16777216.0), (Symbol::ARG_2, 16777217.0)] {
+            let literal = Expr::Literal(Literal::Float(float));
+            let value = try_eval_const(&literal, &F32, &consts).unwrap();
+            consts.insert(symbol, value);
+        }
+
+        // 16777216f32 < 16777217f32
+        let less = low_level(&arena, LowLevel::NumLt, &[Symbol::ARG_1, Symbol::ARG_2]);
+        assert_eq!(
+            try_eval_const(&less, &Layout::Builtin(Builtin::Bool), &consts),
+            Some(ConstValue::Bool(false))
+        );
+    }
+}
@@ -4,6 +4,7 @@
 
 pub mod borrow;
 pub mod code_gen_help;
+pub mod const_eval;
 pub mod inc_dec;
 pub mod ir;
 pub mod layout;
@@ -29,6 +29,11 @@ impl<T> Solved<T> {
     pub fn into_inner(self) -> T {
         self.0
     }
+
+    /// Transform the solved value without unwrapping it, e.g. to reuse the allocations of solved Subs
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Solved<U> {
+        Solved(f(self.0))
+    }
 }
 
 #[derive(Debug)]
@@ -7823,4 +7823,26 @@ mod solve_expr {
             "Result Str [] -> Str",
         );
     }
+
+    #[test]
+    fn solved_map() {
+        use roc_solve::module::Solved;
+        use roc_types::subs::{Content, Subs};
+
+        let solved = Solved(Subs::new());
+        let before = solved.inner().len();
+
+        // reuse the solved Subs, without taking it out of Solved
+        let solved = solved.map(|mut subs| {
+            let var = subs.fresh_unnamed_flex_var();
+            (subs, var)
+        });
+
+        let (subs, var) = solved.inner();
+        assert_eq!(subs.len(), before + 1);
+        assert!(matches!(
+            subs.get_content_without_compacting(*var),
+            Content::FlexVar(None)
+        ));
+    }
 }
@@ -1464,6 +1464,44 @@ fn list_repeat() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn list_repeat_nested_dealloc() {
+    // dropping the outer lists loops over every inner list, and each of those over its strings
+    assert_evals_to!(
+        indoc!(
+            r#"
+                inner = List.repeat "A long enough string to be heap-allocated" 100
+                outer = List.repeat inner 50
+
+                List.len outer + List.len (List.dropFirst outer)
+            "#
+        ),
+        99,
+        usize
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn list_empty_and_non_empty_dealloc() {
+    // only the non-empty list has a refcount to modify
+    assert_evals_to!(
+        indoc!(
+            r#"
+                empty : List Str
+                empty = []
+
+                nonEmpty = ["A long enough string to be heap-allocated", "short"]
+
+                List.len empty + List.len nonEmpty
+            "#
+        ),
+        2,
+        usize
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn list_reverse() {
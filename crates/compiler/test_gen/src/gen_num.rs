@@ -1155,6 +1155,27 @@ fn gen_div_u64() {
     assert_evals_to!("1000u64 // 10", 100, u64);
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn gen_div_signed_vs_unsigned() {
+    // 200u8 and -56i8 have the same bits, so these only agree if signedness is respected
+    assert_evals_to!("200u8 // 3", 66, u8);
+    assert_evals_to!("-56i8 // 3", -18, i8);
+
+    assert_evals_to!("18446744073709551615u64 // 2", 9223372036854775807, u64);
+    assert_evals_to!("-1i64 // 2", 0, i64);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn gen_rem_signed_vs_unsigned() {
+    assert_evals_to!("Num.rem 200u8 7", 4, u8);
+    assert_evals_to!("Num.rem -56i8 7", 0, i8);
+
+    assert_evals_to!("Num.rem 18446744073709551615u64 10", 5, u64);
+    assert_evals_to!("Num.rem -1i64 10", -1, i64);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn gen_div_checked_i64() {
@@ -1465,6 +1486,15 @@ fn gt_f64() {
     assert_evals_to!("0.0 > 0.0", false, bool);
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn compare_f32() {
+    assert_evals_to!("1.1f32 < 1.2", true, bool);
+    assert_evals_to!("1.2f32 <= 1.1", false, bool);
+    assert_evals_to!("2.2f32 > 1.1", true, bool);
+    assert_evals_to!("1.1f32 >= 1.1", true, bool);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn gte_f64() {
@@ -1836,6 +1866,52 @@ fn float_add_checked_fail() {
     );
 }
 
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn f32_add_checked() {
+    assert_evals_to!(
+        "Num.addChecked 1.5f32 0.25",
+        RocResult::ok(1.75),
+        RocResult<f32, ()>
+    );
+    assert_evals_to!(
+        "Num.addChecked 3.4e38f32 3.4e38",
+        RocResult::err(()),
+        RocResult<f32, ()>
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn f32_sub_mul_checked() {
+    assert_evals_to!(
+        "Num.subChecked 1.5f32 0.25",
+        RocResult::ok(1.25),
+        RocResult<f32, ()>
+    );
+    assert_evals_to!(
+        "Num.mulChecked 1.5f32 4",
+        RocResult::ok(6.0),
+        RocResult<f32, ()>
+    );
+    assert_evals_to!(
+        "Num.mulChecked 3.4e38f32 2",
+        RocResult::err(()),
+        RocResult<f32, ()>
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn float_literal_bits() {
+    assert_evals_to!("-0.0f64", (-0.0f64).to_bits(), f64, f64::to_bits);
+    assert_evals_to!("-0.0f32", (-0.0f32).to_bits(), f32, f32::to_bits);
+
+    // smallest subnormals
+    assert_evals_to!("5e-324f64", 1, f64, f64::to_bits);
+    assert_evals_to!("1.5e-45f32", 1, f32, f32::to_bits);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen_dev"))]
 fn float_add_overflow() {
@@ -123,6 +123,136 @@ fn branch_third_int() {
     );
 }
 
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn if_literal_branches_return() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                f = \b -> if b then 42 else 7
+
+                f Bool.true + 10 * f Bool.false
+            "#
+        ),
+        112,
+        i64
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn if_literal_branches_join() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                f = \b ->
+                    x = if b then 3.5 else 1.25
+
+                    x * 2
+
+                f Bool.true + f Bool.false
+            "#
+        ),
+        9.5,
+        f64
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn if_literal_branches_emit_select() {
+    let ir = crate::helpers::llvm::llvm_ir(indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        pickInt : Bool -> I64
+        pickInt = \b -> if b then 42 else 7
+
+        pickFloat : Bool -> F64
+        pickFloat = \b ->
+            x = if b then 3.5 else 1.25
+
+            x * 2
+
+        main = Num.toF64 (pickInt Bool.true) + pickFloat Bool.false
+        "#
+    ));
+
+    for ident in ["pickInt", "pickFloat"] {
+        let function_ir = crate::helpers::llvm::llvm_function_ir(&ir, ident);
+
+        assert!(
+            function_ir.contains(" select "),
+            "no select in:\n{}",
+            function_ir
+        );
+        assert!(
+            !function_ir.contains("br i1"),
+            "{} still branches on the condition:\n{}",
+            ident,
+            function_ir
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn when_on_constant_is_folded() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        pick : {} -> I64
+        pick = \{} ->
+            x = 1 + 2
+
+            when x is
+                3 -> 30
+                4 -> 40
+                _ -> 0
+
+        main = pick {}
+        "#
+    );
+
+    let ir = crate::helpers::llvm::llvm_ir(src);
+    let function_ir = crate::helpers::llvm::llvm_function_ir(&ir, "pick");
+
+    // `x` is known, so the add and the switch on it are gone, and only the `3` branch is left
+    assert!(
+        !function_ir.contains("with.overflow"),
+        "the add was not folded:\n{}",
+        function_ir
+    );
+    assert!(
+        !function_ir.contains("switch ") && !function_ir.contains("br i1"),
+        "the switch was not folded:\n{}",
+        function_ir
+    );
+    assert!(
+        function_ir.contains("ret i64 30"),
+        "pick does not return the `3` branch:\n{}",
+        function_ir
+    );
+
+    assert_evals_to!(src, 30, i64);
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn many_bindings() {
+    // x0 = 0, x1 = x0 + 1, ..., x199 = x198 + 199
+    let mut src = String::from("x0 = 0\n");
+
+    for i in 1..200 {
+        src.push_str(&format!("x{} = x{} + {}\n", i, i - 1, i));
+    }
+
+    src.push_str("x199");
+
+    assert_evals_to!(&src, 19900, i64);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
 fn branch_store_variable() {
@@ -4067,3 +4197,342 @@ fn int_let_generalization() {
         RocStr
     );
 }
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn llvm_values_named_after_bindings() {
+    let ir = crate::helpers::llvm::llvm_ir(indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        f : { a : I64, b : I64 } -> I64
+        f = \rec ->
+            first = rec.a
+
+            first + rec.b
+
+        main = f { a: 1, b: 2 }
+        "#
+    ));
+
+    assert!(
+        ir.contains("%first"),
+        "no value named after `first` in:\n{}",
+        ir
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn global_bytes_are_deduplicated() {
+    crate::helpers::llvm::with_empty_env(roc_target::TargetInfo::default_x86_64(), |env| {
+        let first = env.build_global_bytes(b"hello", "first");
+        let second = env.build_global_bytes(&b"hello world"[..5], "second");
+        let other = env.build_global_bytes(b"world", "other");
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    });
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn declare_external_host_function() {
+    use inkwell::module::Linkage;
+    use roc_gen_llvm::llvm::build::declare_foreign_symbol;
+    use roc_mono::layout::{Builtin, Layout};
+
+    crate::helpers::llvm::with_empty_env(roc_target::TargetInfo::default_x86_64(), |env| {
+        // Stdout.line : Str -> {}
+        let str_layout = Layout::Builtin(Builtin::Str);
+        let fn_val = declare_foreign_symbol(
+            env,
+            "roc_fx_stdoutLine".into(),
+            &[str_layout],
+            &Layout::UNIT,
+        );
+
+        assert_eq!(fn_val.get_linkage(), Linkage::External);
+        assert_eq!(fn_val.count_basic_blocks(), 0);
+        assert_eq!(env.module.get_function("roc_fx_stdoutLine"), Some(fn_val));
+
+        // declaring again, or calling it later, reuses the declaration
+        let again = declare_foreign_symbol(
+            env,
+            "roc_fx_stdoutLine".into(),
+            &[str_layout],
+            &Layout::UNIT,
+        );
+        assert_eq!(again, fn_val);
+    });
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn call_external_host_function() {
+    use roc_gen_llvm::llvm::build::{build_foreign_symbol_wrapper, verify_module};
+    use roc_mono::layout::{Builtin, Layout};
+
+    crate::helpers::llvm::with_empty_env(roc_target::TargetInfo::default_x86_64(), |env| {
+        // the wrapper is built while some roc function is being built
+        let fn_type = env.context.void_type().fn_type(&[], false);
+        let caller = env.module.add_function("caller", fn_type, None);
+        env.builder
+            .position_at_end(env.context.append_basic_block(caller, "entry"));
+
+        // Stdout.line : Str -> {}
+        let wrapper = build_foreign_symbol_wrapper(
+            env,
+            &"roc_fx_stdoutLine".into(),
+            &[Layout::Builtin(Builtin::Str)],
+            &Layout::UNIT,
+        );
+        env.builder.build_return(None);
+
+        let wrapper_ir = wrapper.print_to_string().to_string();
+        assert!(
+            wrapper_ir
+                .lines()
+                .any(|line| line.contains("call ") && line.contains("@roc_fx_stdoutLine(")),
+            "the wrapper does not call the host function:\n{}",
+            wrapper_ir
+        );
+
+        // the host function is only declared, the linker provides its definition
+        let host_function = env.module.get_function("roc_fx_stdoutLine").unwrap();
+        assert_eq!(host_function.count_basic_blocks(), 0);
+        assert_eq!(verify_module(env.module), Ok(()));
+    });
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn export_main_proc() {
+    use inkwell::module::Linkage;
+    use roc_gen_llvm::llvm::build::{export_proc, exported_functions};
+
+    crate::helpers::llvm::with_empty_env(roc_target::TargetInfo::default_x86_64(), |env| {
+        let fn_type = env.context.void_type().fn_type(&[], false);
+        let define = |name| {
+            let fn_val = env
+                .module
+                .add_function(name, fn_type, Some(Linkage::Internal));
+            env.builder
+                .position_at_end(env.context.append_basic_block(fn_val, "entry"));
+            env.builder.build_return(None);
+            fn_val
+        };
+
+        let main = define("main");
+        let helper = define("helper");
+        assert!(exported_functions(env.module).is_empty());
+
+        export_proc(main);
+
+        assert_eq!(main.get_linkage(), Linkage::External);
+        assert_eq!(helper.get_linkage(), Linkage::Internal);
+        assert_eq!(exported_functions(env.module), vec![main]);
+
+        // a declaration without a body is an import, not an export
+        env.module
+            .add_function("roc_alloc", fn_type, Some(Linkage::External));
+        assert_eq!(exported_functions(env.module), vec![main]);
+    });
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn module_with_missing_definition_fails_verification() {
+    use inkwell::module::Linkage;
+    use roc_gen_llvm::llvm::build::verify_module;
+
+    crate::helpers::llvm::with_empty_env(roc_target::TargetInfo::default_x86_64(), |env| {
+        let fn_type = env.context.void_type().fn_type(&[], false);
+
+        // an external declaration is fine, the linker will find the definition
+        env.module
+            .add_function("roc_alloc", fn_type, Some(Linkage::External));
+        assert_eq!(verify_module(env.module), Ok(()));
+
+        // an internal function must be defined in this module
+        env.module
+            .add_function("missing_helper", fn_type, Some(Linkage::Internal));
+
+        let errors = verify_module(env.module).unwrap_err();
+        assert!(
+            errors.contains("doesn't have external or weak linkage"),
+            "{}",
+            errors
+        );
+        assert!(errors.contains("@missing_helper"), "{}", errors);
+    });
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn index_into_non_struct_is_an_error() {
+    use roc_builtins::bitcode::IntWidth;
+    use roc_gen_llvm::llvm::build::{build_struct_at_index, CodegenError, Scope};
+    use roc_module::symbol::Symbol;
+    use roc_mono::layout::{Builtin, Layout};
+
+    crate::helpers::llvm::with_empty_env(roc_target::TargetInfo::default_x86_64(), |env| {
+        let mut scope = Scope::default();
+        let int_value = env.context.i64_type().const_int(42, false);
+        scope.insert(
+            Symbol::ARG_1,
+            (
+                Layout::Builtin(Builtin::Int(IntWidth::I64)),
+                int_value.into(),
+            ),
+        );
+
+        assert_eq!(
+            build_struct_at_index(env, &scope, Symbol::ARG_1, 0),
+            Err(CodegenError::IndexIntoNonStruct {
+                structure: Symbol::ARG_1,
+                index: 0
+            })
+        );
+    });
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn pointer_width_follows_target() {
+    use roc_target::TargetInfo;
+
+    let wasm32_width = crate::helpers::llvm::with_empty_env(TargetInfo::default_wasm32(), |env| {
+        env.ptr_int().get_bit_width()
+    });
+    let x86_64_width = crate::helpers::llvm::with_empty_env(TargetInfo::default_x86_64(), |env| {
+        env.ptr_int().get_bit_width()
+    });
+
+    assert_eq!(wasm32_width / 8, 4);
+    assert_eq!(x86_64_width / 8, 8);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn user_function_named_like_a_builtin() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            add : I64, I64 -> I64
+            add = \a, b -> a * b
+
+            main = add 2 3
+            "#
+        ),
+        6,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn closure_reads_capture_when_invoked_elsewhere() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            makeAdder : I64 -> (I64 -> I64)
+            makeAdder = \n -> \x -> x + n
+
+            apply : (I64 -> I64), I64 -> I64
+            apply = \f, x -> f x
+
+            main = apply (makeAdder 5) 10
+            "#
+        ),
+        15,
+        i64
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn passing_expect_is_a_no_op() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            x : I64
+            x = 41
+
+            expect x == 41
+
+            x + 1
+            "#
+        ),
+        42,
+        i64
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+#[should_panic(expected = r#"Roc failed with message: "Expect failed at @"#)]
+fn failing_expect_traps() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            x : I64
+            x = 41
+
+            expect x == 0
+
+            x + 1
+            "#
+        ),
+        42,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn discarded_values_in_sequence() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            double : I64 -> I64
+            double = \x -> x * 2
+
+            main =
+                _ = double 1
+                _ = double 2
+
+                double 21
+            "#
+        ),
+        42,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[should_panic(expected = "Roc failed with message: ")]
+fn discarded_value_is_still_evaluated() {
+    // the overflow must happen even though nothing uses the sum
+    assert_evals_to!(
+        indoc!(
+            r#"
+            n : I64
+            n = 9_223_372_036_854_775_807
+
+            _ = n + 1
+
+            42
+            "#
+        ),
+        42,
+        i64
+    );
+}
@@ -849,6 +849,24 @@ fn update_record() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn update_record_keeps_other_fields() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                r = { x: 1, y: 2 }
+
+                updated = { r & x: 5 }
+
+                { updatedX: updated.x, unchangedY: updated.y }
+                "#
+        ),
+        (2, 5),
+        (i64, i64)
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
 fn update_single_element_record() {
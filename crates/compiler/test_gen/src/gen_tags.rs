@@ -334,6 +334,25 @@ fn when_on_result() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn when_on_ok_extracts_payload() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+                x : Result I64 I64
+                x = Ok 5
+
+                when x is
+                    Ok v -> v * 10
+                    Err v -> v
+                "#
+        ),
+        50,
+        i64
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn when_on_these() {
@@ -2004,3 +2023,78 @@ fn match_on_result_with_uninhabited_error_branch() {
         RocStr
     );
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn switch_on_u8_tag_id() {
+    // the tag id of this union is a u8, so the switch on it must use u8 case constants
+    assert_evals_to!(
+        indoc!(
+            r#"
+            Shape : [Circle F64, Rect F64 F64, Tri F64 F64 F64]
+
+            area : Shape -> F64
+            area = \shape ->
+                when shape is
+                    Circle r -> r
+                    Rect w h -> w * h
+                    Tri a b c -> a + b + c
+
+            area (Rect 2 3) + area (Tri 1 1 1) + area (Circle 1)
+            "#
+        ),
+        10.0,
+        f64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn exhaustive_switch_on_tag_id() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            Color : [Red I64, Green I64, Blue I64]
+
+            value : Color -> I64
+            value = \color ->
+                when color is
+                    Red x -> x
+                    Green x -> x * 10
+                    Blue x -> x * 100
+
+            value (Red 1) + value (Green 2) + value (Blue 3)
+            "#
+        ),
+        321,
+        i64
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn exhaustive_switch_on_tag_id_has_no_reachable_default() {
+    // every tag id has its own case, so the default of the switch is unreachable
+    let ir = crate::helpers::llvm::llvm_ir(indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        Color : [Red I64, Green I64, Blue I64]
+
+        value : Color -> I64
+        value = \color ->
+            when color is
+                Red x -> x
+                Green x -> x * 10
+                Blue x -> x * 100
+
+        main = value (Red 1) + value (Green 2) + value (Blue 3)
+        "#
+    ));
+
+    assert!(
+        ir.contains("unreachable_default"),
+        "switch on the tag id still has a reachable default in:\n{}",
+        ir
+    );
+}
@@ -7,7 +7,10 @@ use roc_build::link::llvm_module_to_dylib;
 use roc_build::program::FunctionIterator;
 use roc_collections::all::MutSet;
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
-use roc_gen_llvm::{llvm::build::LlvmBackendMode, run_roc::RocCallResult};
+use roc_gen_llvm::{
+    llvm::build::{verify_module, LlvmBackendMode},
+    run_roc::RocCallResult,
+};
 use roc_load::{EntryPoint, ExecutionMode, LoadConfig, Threading};
 use roc_mono::ir::OptLevel;
 use roc_region::all::LineInfo;
@@ -175,6 +178,7 @@ fn create_llvm_module<'a>(
     }
 
     let builder = context.create_builder();
+    let alloca_builder = context.create_builder();
     let module = roc_gen_llvm::llvm::build::module_from_builtins(target, context, "app");
 
     let module = arena.alloc(module);
@@ -215,6 +219,7 @@ fn create_llvm_module<'a>(
         arena,
         layout_interner: &layout_interner,
         builder: &builder,
+        alloca_builder: &alloca_builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
         context,
@@ -224,6 +229,7 @@ fn create_llvm_module<'a>(
         mode: config.mode,
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        global_constants: Default::default(),
     };
 
     // strip Zig debug stuff
@@ -275,13 +281,12 @@ fn create_llvm_module<'a>(
     module_pass.run_on(env.module);
 
     // Verify the module
-    if let Err(errors) = env.module.verify() {
+    if let Err(errors) = verify_module(env.module) {
         let path = std::env::temp_dir().join("test.ll");
         env.module.print_to_file(&path).unwrap();
         panic!(
             "Errors defining module:\n\n{}\n\nI have written the full module to `{:?}`",
-            errors.to_string(),
-            path
+            errors, path
         );
     }
 
@@ -329,6 +334,75 @@ pub fn helper<'a>(
     (main_fn_name, delayed_errors, lib)
 }
 
+/// The unoptimized LLVM IR of a test program, for tests that check the generated code itself
+#[allow(dead_code)]
+pub fn llvm_ir(src: &str) -> String {
+    let arena = bumpalo::Bump::new();
+    let context = inkwell::context::Context::create();
+    let target = target_lexicon::Triple::host();
+    let config = HelperConfig {
+        mode: LlvmBackendMode::GenTest,
+        add_debug_info: false,
+        ignore_problems: false,
+        opt_level: OptLevel::Normal,
+    };
+
+    let (_, _, module) = create_llvm_module(&arena, src, config, &context, &target);
+
+    module.print_to_string().to_string()
+}
+
+/// The definition of the (first) roc function named `ident` in the module IR from llvm_ir
+#[allow(dead_code)]
+pub fn llvm_function_ir<'a>(ir: &'a str, ident: &str) -> &'a str {
+    let name = format!("_{}_", ident);
+
+    let start = ir
+        .match_indices("define ")
+        .map(|(index, _)| index)
+        .find(|index| ir[*index..].lines().next().unwrap().contains(&name))
+        .unwrap_or_else(|| panic!("no function named {} in:\n{}", ident, ir));
+
+    let end = ir[start..]
+        .find("\n}\n")
+        .map_or(ir.len(), |body_end| start + body_end + 2);
+
+    &ir[start..end]
+}
+
+/// Runs `f` with an Env around an empty module, for tests of the Env helpers themselves
+#[allow(dead_code)]
+pub fn with_empty_env<T, F>(target_info: roc_target::TargetInfo, f: F) -> T
+where
+    F: FnOnce(&roc_gen_llvm::llvm::build::Env<'_, '_, '_>) -> T,
+{
+    let arena = bumpalo::Bump::new();
+    let context = inkwell::context::Context::create();
+    let module = context.create_module("app");
+    let builder = context.create_builder();
+    let alloca_builder = context.create_builder();
+    let (dibuilder, compile_unit) = roc_gen_llvm::llvm::build::Env::new_debug_info(&module);
+    let layout_interner = roc_mono::layout::STLayoutInterner::with_capacity(4);
+
+    let env = roc_gen_llvm::llvm::build::Env {
+        arena: &arena,
+        layout_interner: &layout_interner,
+        builder: &builder,
+        alloca_builder: &alloca_builder,
+        dibuilder: &dibuilder,
+        compile_unit: &compile_unit,
+        context: &context,
+        interns: Default::default(),
+        module: &module,
+        target_info,
+        mode: LlvmBackendMode::GenTest,
+        exposed_to_host: MutSet::default(),
+        global_constants: Default::default(),
+    };
+
+    f(&env)
+}
+
 fn annotate_with_debug_info<'ctx>(
     module: &Module<'ctx>,
     context: &'ctx inkwell::context::Context,
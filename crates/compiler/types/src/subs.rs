@@ -1406,6 +1406,10 @@ impl VarId {
     pub const fn from_u32(n: u32) -> Self {
         VarId(n)
     }
+
+    pub const fn to_u32(self) -> u32 {
+        self.0
+    }
 }
 
 impl fmt::Debug for VarId {
@@ -1,4 +1,7 @@
-use roc_code_markup::{markup::nodes::MarkupNode, slow_pool::SlowPool};
+use roc_code_markup::{
+    markup::nodes::MarkupNode, slow_pool::SlowPool, syntax_highlight::HighlightStyle,
+};
+use roc_highlight::tokenizer::{Token, TokenTable};
 
 // determine appropriate css class for MarkupNode
 pub fn mark_node_to_html(mark_node: &MarkupNode, mark_node_pool: &SlowPool, buf: &mut String) {
@@ -22,28 +25,7 @@ pub fn mark_node_to_html(mark_node: &MarkupNode, mark_node_pool: &SlowPool, buf:
             newlines_at_end,
             ..
         } => {
-            use roc_code_markup::syntax_highlight::HighlightStyle::*;
-
-            let css_class = match syn_high_style {
-                Operator => "operator",
-                String => "string",
-                FunctionName => "function-name",
-                FunctionArgName => "function-arg-name",
-                Type => "type",
-                Bracket => "bracket",
-                Number => "number",
-                PackageRelated => "package-related",
-                Value => "value",
-                RecordField => "recordfield",
-                Import => "import",
-                Provides => "provides",
-                Blank => "blank",
-                Comment => "comment",
-                DocsComment => "docs-comment",
-                UppercaseIdent => "uppercase-ident",
-                LowercaseIdent => "lowercase-ident",
-                Keyword => "keyword-ident",
-            };
+            let css_class = highlight_style_to_css_class(syn_high_style);
 
             write_html_to_buf(content, css_class, buf);
 
@@ -74,12 +56,168 @@ pub fn mark_node_to_html(mark_node: &MarkupNode, mark_node_pool: &SlowPool, buf:
     }
 }
 
+// Wraps every line of highlighted html in a span with its line number, so a stylesheet can render a gutter.
+// The highlighter only emits newlines between spans, so this never splits a span.
+pub fn wrap_lines_w_numbers(highlighted_html: &str) -> String {
+    let mut buf = String::new();
+
+    for (index, line) in highlighted_html
+        .trim_end_matches('\n')
+        .split('\n')
+        .enumerate()
+    {
+        buf.push_str(&format!(
+            "<span class=\"syntax-line\" data-line=\"{}\">{}</span>\n",
+            index + 1,
+            line
+        ));
+    }
+
+    buf
+}
+
+// Wraps highlighted html in `<pre class="..."><code>...</code></pre>`, ready to be dropped into a page.
+pub fn wrap_in_code_block(highlighted_html: &str, language_class: &str) -> String {
+    let mut buf = String::with_capacity(highlighted_html.len() + 32);
+
+    buf.push_str("<pre class=\"");
+    push_escaped_html(language_class, &mut buf);
+    buf.push_str("\"><code>");
+    buf.push_str(highlighted_html);
+    buf.push_str("</code></pre>");
+
+    buf
+}
+
+fn highlight_style_to_css_class(style: &HighlightStyle) -> &'static str {
+    use HighlightStyle::*;
+
+    match style {
+        Operator => "operator",
+        String => "string",
+        FunctionName => "function-name",
+        FunctionArgName => "function-arg-name",
+        Type => "type",
+        Bracket => "bracket",
+        Number => "number",
+        PackageRelated => "package-related",
+        Value => "value",
+        RecordField => "recordfield",
+        Import => "import",
+        Provides => "provides",
+        Blank => "blank",
+        Comment => "comment",
+        DocsComment => "docs-comment",
+        UppercaseIdent => "uppercase-ident",
+        LowercaseIdent => "lowercase-ident",
+        Keyword => "keyword-ident",
+        Builtin => "builtin",
+    }
+}
+
+// Highlighting based on tokens alone, without parsing. Text between tokens (whitespace, comments)
+// is copied over as is, so the output always covers the whole input, even if it would not parse.
+pub fn token_table_to_html(code_str: &str, token_table: &TokenTable, buf: &mut String) {
+    let mut copied_up_to = 0;
+
+    for (index, &token) in token_table.tokens.iter().enumerate() {
+        let offset = token_table.offsets[index];
+        let length = token_table.lengths[index];
+
+        // indentation tokens are zero-width
+        if length == 0 || offset < copied_up_to {
+            continue;
+        }
+
+        push_escaped_html(&code_str[copied_up_to..offset], buf);
+
+        let content = token_table.extract_str(index, code_str);
+
+        match token_highlight_style(token) {
+            Some(style) => write_html_to_buf(content, highlight_style_to_css_class(&style), buf),
+            None => push_escaped_html(content, buf),
+        }
+
+        copied_up_to = offset + length;
+    }
+
+    push_escaped_html(&code_str[copied_up_to..], buf);
+}
+
+fn token_highlight_style(token: Token) -> Option<HighlightStyle> {
+    use Token::*;
+
+    let style = match token {
+        LowercaseIdent => HighlightStyle::LowercaseIdent,
+        UppercaseIdent => HighlightStyle::UppercaseIdent,
+        String => HighlightStyle::String,
+        Number | NumberBase => HighlightStyle::Number,
+
+        KeywordIf | KeywordThen | KeywordElse | KeywordWhen | KeywordAs | KeywordIs
+        | KeywordExpect => HighlightStyle::Keyword,
+
+        KeywordApp | KeywordInterface | KeywordPackages | KeywordImports | KeywordProvides
+        | KeywordTo | KeywordExposes | KeywordEffects | KeywordPlatform | KeywordRequires => {
+            HighlightStyle::PackageRelated
+        }
+
+        OpenParen | CloseParen | OpenCurly | CloseCurly | OpenSquare | CloseSquare => {
+            HighlightStyle::Bracket
+        }
+
+        Comma | Colon | OpPlus | OpMinus | OpSlash | OpPercent | OpCaret | OpGreaterThan
+        | OpLessThan | OpAssignment | OpPizza | OpEquals | OpNotEquals | OpGreaterThanOrEq
+        | OpLessThanOrEq | OpAnd | OpOr | OpDoubleSlash | OpBackpassing | QuestionMark
+        | Underscore | Ampersand | Pipe | Dot | SpaceDot | Bang | LambdaStart | Arrow
+        | FatArrow | Asterisk => HighlightStyle::Operator,
+
+        MalformedIdent | OpenIndent | CloseIndent | SameIndent | TodoNextThing | Malformed
+        | MalformedOperator => return None,
+    };
+
+    Some(style)
+}
+
 fn write_html_to_buf(content: &str, css_class: &'static str, buf: &mut String) {
     let opening_tag: String = ["<span class=\"syntax-", css_class, "\">"].concat();
 
     buf.push_str(opening_tag.as_str());
 
-    buf.push_str(content);
+    push_escaped_html(content, buf);
 
     buf.push_str("</span>");
 }
+
+// Source text can contain characters that have a meaning in html, e.g. `"a < b"`.
+fn push_escaped_html(content: &str, buf: &mut String) {
+    for ch in content.chars() {
+        match ch {
+            '&' => buf.push_str("&amp;"),
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            '"' => buf.push_str("&quot;"),
+            _ => buf.push(ch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_html {
+    use roc_code_markup::{markup::common_nodes::new_comments_mn, slow_pool::SlowPool};
+
+    use super::mark_node_to_html;
+
+    #[test]
+    fn comment_is_escaped() {
+        let mark_node_pool = SlowPool::default();
+        let mut buf = String::new();
+
+        mark_node_to_html(
+            &new_comments_mn("# x > y".to_owned(), 0),
+            &mark_node_pool,
+            &mut buf,
+        );
+
+        assert_eq!(buf, r#"<span class="syntax-comment"># x &gt; y</span>"#);
+    }
+}
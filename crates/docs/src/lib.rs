@@ -2,11 +2,14 @@ extern crate pulldown_cmark;
 extern crate roc_load;
 use bumpalo::Bump;
 use docs_error::{DocsError, DocsResult};
-use html::mark_node_to_html;
+use html::{mark_node_to_html, token_table_to_html, wrap_in_code_block, wrap_lines_w_numbers};
 use roc_can::scope::Scope;
-use roc_code_markup::markup::nodes::MarkupNode;
-use roc_code_markup::slow_pool::SlowPool;
+use roc_code_markup::markup::nodes::{node_to_string_w_children, MarkupNode, DOT};
+use roc_code_markup::slow_pool::{MarkNodeId, SlowPool};
+use roc_code_markup::syntax_highlight::HighlightStyle;
+use roc_collections::all::{MutMap, MutSet};
 use roc_highlight::highlight_parser::{highlight_defs, highlight_expr};
+use roc_highlight::tokenizer::{full_tokenize, Token, TokenTable};
 use roc_load::docs::DocEntry::DocDef;
 use roc_load::docs::{DocEntry, TypeAnnotation};
 use roc_load::docs::{ModuleDocumentation, RecordField};
@@ -103,8 +106,14 @@ pub fn generate_docs_html(filenames: Vec<PathBuf>) {
             render_sidebar(module_pairs).as_str(),
         );
 
+    // the builtins are the same for every loaded module, so their names are only collected once
+    let mut highlighter_opt = None;
+
     // Write each package's module docs html file
     for loaded_module in package.modules.iter_mut() {
+        let highlighter =
+            highlighter_opt.get_or_insert_with(|| Highlighter::new(&loaded_module.interns));
+
         for (module_id, module_docs) in loaded_module.documentation.iter() {
             if *module_id == loaded_module.module_id {
                 let module_dir = build_dir.join(module_docs.name.replace('.', "/").as_str());
@@ -120,7 +129,8 @@ pub fn generate_docs_html(filenames: Vec<PathBuf>) {
                     )
                     .replace(
                         "<!-- Module Docs -->",
-                        render_module_documentation(module_docs, loaded_module).as_str(),
+                        render_module_documentation(module_docs, loaded_module, highlighter)
+                            .as_str(),
                     );
 
                 fs::write(module_dir.join("index.html"), rendered_module).expect(
@@ -142,6 +152,133 @@ fn sidebar_link_url(module: &ModuleDocumentation) -> String {
 
 // converts plain-text code to highlighted html
 pub fn syntax_highlight_expr(code_str: &str) -> DocsResult<String> {
+    highlight_expr_to_html(code_str, None)
+}
+
+// converts plain-text code to highlighted html, references to builtins (e.g. `List.map`)
+// are resolved through interns and get the builtin css class.
+// To highlight many snippets, create one Highlighter and reuse it instead.
+pub fn syntax_highlight_expr_w_builtins(code_str: &str, interns: &Interns) -> DocsResult<String> {
+    Highlighter::new(interns).highlight_expr(code_str)
+}
+
+/// Highlights many snippets that refer to the same interns, like all the code blocks in the docs of a module.
+/// The names exposed by the builtin modules are collected once, instead of being looked up for every snippet.
+pub struct Highlighter {
+    // builtin module name -> the names of the values it exposes
+    builtin_idents: MutMap<String, MutSet<String>>,
+}
+
+impl Highlighter {
+    pub fn new(interns: &Interns) -> Self {
+        let mut builtin_idents = MutMap::default();
+
+        for module_id in interns.all_ident_ids.keys() {
+            if !module_id.is_builtin() {
+                continue;
+            }
+
+            if let (Some(module_name), Some(ident_ids)) = (
+                interns.module_ids.get_name(*module_id),
+                interns.all_ident_ids.get(module_id),
+            ) {
+                let ident_strs = ident_ids
+                    .ident_strs()
+                    .map(|(_, ident_str)| ident_str.to_string())
+                    .collect();
+
+                builtin_idents.insert(module_name.as_str().to_string(), ident_strs);
+            }
+        }
+
+        Highlighter { builtin_idents }
+    }
+
+    // like syntax_highlight_expr_w_builtins
+    pub fn highlight_expr(&self, code_str: &str) -> DocsResult<String> {
+        highlight_expr_to_html(code_str, Some(self))
+    }
+
+    fn is_builtin(&self, module_name: &str, ident_str: &str) -> bool {
+        self.builtin_idents
+            .get(module_name)
+            .map_or(false, |ident_strs| ident_strs.contains(ident_str))
+    }
+}
+
+// like syntax_highlight_expr, wrapped in a `<pre class="language_class"><code>` block
+pub fn syntax_highlight_block(code_str: &str, language_class: &str) -> DocsResult<String> {
+    syntax_highlight_expr(code_str).map(|html| wrap_in_code_block(&html, language_class))
+}
+
+// like syntax_highlight_top_level_defs, with every line wrapped in a span that has its line number
+pub fn syntax_highlight_top_level_defs_w_line_numbers(code_str: &str) -> DocsResult<String> {
+    syntax_highlight_top_level_defs(code_str).map(|html| wrap_lines_w_numbers(&html))
+}
+
+/// A bracket that does not match up, found while highlighting.
+/// `expected` is the closer that was due (None for a stray closer), `found` is the bracket
+/// at `offset` (None when the input ended with brackets still open).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BracketWarning {
+    pub offset: usize,
+    pub expected: Option<char>,
+    pub found: Option<char>,
+}
+
+// converts plain-text code to highlighted html using only the tokenizer, so it also works on
+// snippets that don't parse as a whole. Mismatched brackets are returned as warnings.
+pub fn syntax_highlight_lexical(code_str: &str, buf: &mut String) -> Vec<BracketWarning> {
+    let token_table = full_tokenize(code_str);
+
+    token_table_to_html(code_str, &token_table, buf);
+
+    find_bracket_mismatches(code_str, &token_table)
+}
+
+fn find_bracket_mismatches(code_str: &str, token_table: &TokenTable) -> Vec<BracketWarning> {
+    let mut warnings = Vec::new();
+    // closers we are waiting for, innermost last
+    let mut expected_closers: Vec<char> = Vec::new();
+
+    for (index, token) in token_table.tokens.iter().enumerate() {
+        let offset = token_table.offsets[index];
+
+        match token {
+            Token::OpenParen => expected_closers.push(')'),
+            Token::OpenSquare => expected_closers.push(']'),
+            Token::OpenCurly => expected_closers.push('}'),
+            Token::CloseParen | Token::CloseSquare | Token::CloseCurly => {
+                let found = code_str[offset..].chars().next();
+                let expected = expected_closers.pop();
+
+                if expected != found {
+                    warnings.push(BracketWarning {
+                        offset,
+                        expected,
+                        found,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for expected in expected_closers.into_iter().rev() {
+        warnings.push(BracketWarning {
+            offset: code_str.len(),
+            expected: Some(expected),
+            found: None,
+        });
+    }
+
+    warnings
+}
+
+fn highlight_expr_to_html(
+    code_str: &str,
+    highlighter_opt: Option<&Highlighter>,
+) -> DocsResult<String> {
     let trimmed_code_str = code_str.trim_end().trim();
     let mut mark_node_pool = SlowPool::default();
 
@@ -149,6 +286,10 @@ pub fn syntax_highlight_expr(code_str: &str) -> DocsResult<String> {
 
     match highlight_expr(trimmed_code_str, &mut mark_node_pool) {
         Ok(root_mark_node_id) => {
+            if let Some(highlighter) = highlighter_opt {
+                mark_builtins(root_mark_node_id, &mut mark_node_pool, highlighter);
+            }
+
             let root_mark_node = mark_node_pool.get(root_mark_node_id);
             mark_node_to_html(root_mark_node, &mark_node_pool, &mut highlighted_html_str);
 
@@ -183,9 +324,67 @@ pub fn syntax_highlight_top_level_defs(code_str: &str) -> DocsResult<String> {
     }
 }
 
+// gives every module var that refers to a builtin the Builtin style
+fn mark_builtins(
+    mark_node_id: MarkNodeId,
+    mark_node_pool: &mut SlowPool,
+    highlighter: &Highlighter,
+) {
+    if is_builtin_module_var(mark_node_id, mark_node_pool, highlighter) {
+        set_style_w_children(mark_node_id, mark_node_pool, HighlightStyle::Builtin);
+    } else {
+        for child_id in mark_node_pool.get(mark_node_id).get_children_ids() {
+            mark_builtins(child_id, mark_node_pool, highlighter);
+        }
+    }
+}
+
+// a module var like `List.map` is a nested node of the module name, a dot and a lowercase ident
+fn is_builtin_module_var(
+    mark_node_id: MarkNodeId,
+    mark_node_pool: &SlowPool,
+    highlighter: &Highlighter,
+) -> bool {
+    let children_ids = mark_node_pool.get(mark_node_id).get_children_ids();
+
+    if let [module_name_id, dot_id, ident_id] = children_ids[..] {
+        let is_dot = mark_node_pool.get(dot_id).get_content() == DOT;
+        let ident_str = match mark_node_pool.get(ident_id) {
+            MarkupNode::Text {
+                content,
+                syn_high_style: HighlightStyle::LowercaseIdent,
+                ..
+            } => content.as_str(),
+            _ => return false,
+        };
+
+        let mut module_name = String::new();
+        node_to_string_w_children(module_name_id, &mut module_name, mark_node_pool);
+
+        is_dot && highlighter.is_builtin(&module_name, ident_str)
+    } else {
+        false
+    }
+}
+
+fn set_style_w_children(
+    mark_node_id: MarkNodeId,
+    mark_node_pool: &mut SlowPool,
+    style: HighlightStyle,
+) {
+    for child_id in mark_node_pool.get(mark_node_id).get_children_ids() {
+        set_style_w_children(child_id, mark_node_pool, style);
+    }
+
+    if let MarkupNode::Text { syn_high_style, .. } = mark_node_pool.get_mut(mark_node_id) {
+        *syn_high_style = style;
+    }
+}
+
 fn render_module_documentation(
     module: &ModuleDocumentation,
     loaded_module: &LoadedModule,
+    highlighter: &Highlighter,
 ) -> String {
     let mut buf = String::new();
 
@@ -257,6 +456,7 @@ fn render_module_documentation(
                                 &module.scope,
                                 docs.to_string(),
                                 loaded_module,
+                                highlighter,
                             )
                             .as_str(),
                         );
@@ -270,6 +470,7 @@ fn render_module_documentation(
                         &module.scope,
                         docs.to_string(),
                         loaded_module,
+                        highlighter,
                     );
                     buf.push_str(markdown.as_str());
                 }
@@ -843,6 +1044,7 @@ fn markdown_to_html(
     scope: &Scope,
     markdown: String,
     loaded_module: &LoadedModule,
+    highlighter: &Highlighter,
 ) -> String {
     use pulldown_cmark::{BrokenLink, CodeBlockKind, CowStr, Event, LinkType, Tag::*};
 
@@ -999,10 +1201,7 @@ fn markdown_to_html(
             }
             Event::Text(CowStr::Borrowed(code_str)) if expecting_code_block => {
 
-                match syntax_highlight_expr(
-                    code_str
-                )
-                {
+                match highlighter.highlight_expr(code_str) {
                     Ok(highlighted_code_str) => {
                         docs_parser.push(Event::Html(CowStr::from(highlighted_code_str)));
                     }
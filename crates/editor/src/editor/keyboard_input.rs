@@ -1,7 +1,7 @@
 use crate::editor::ed_error::EdResult;
 use crate::editor::mvc::app_model::AppModel;
 use crate::editor::mvc::app_update::{
-    handle_copy, handle_cut, handle_paste, pass_keydown_to_focused,
+    handle_copy, handle_cut, handle_paste, pass_keydown_to_focused, InputOutcome,
 };
 use crate::window::keyboard_input::from_winit;
 use winit::event::VirtualKeyCode::*;
@@ -12,41 +12,26 @@ pub fn handle_keydown(
     virtual_keycode: VirtualKeyCode,
     modifiers_winit: ModifiersState,
     app_model: &mut AppModel,
-) -> EdResult<()> {
+) -> EdResult<InputOutcome> {
     if let ElementState::Released = elem_state {
-        return Ok(());
+        return Ok(InputOutcome::SilentIgnored);
     }
 
     let modifiers = from_winit(&modifiers_winit);
 
     match virtual_keycode {
-        Left | Up | Right | Down => {
-            pass_keydown_to_focused(&modifiers, virtual_keycode, app_model)?
-        }
+        Left | Up | Right | Down => pass_keydown_to_focused(&modifiers, virtual_keycode, app_model),
 
-        Copy => handle_copy(app_model)?,
-        Paste => handle_paste(app_model)?,
-        Cut => handle_cut(app_model)?,
-        C => {
-            if modifiers.cmd_or_ctrl() {
-                handle_copy(app_model)?
-            }
-        }
-        V => {
-            if modifiers.cmd_or_ctrl() {
-                handle_paste(app_model)?
-            }
-        }
-        X => {
-            if modifiers.cmd_or_ctrl() {
-                handle_cut(app_model)?
-            }
-        }
+        Copy => handle_copy(app_model),
+        Paste => handle_paste(app_model),
+        Cut => handle_cut(app_model),
+        C if modifiers.cmd_or_ctrl() => handle_copy(app_model),
+        V if modifiers.cmd_or_ctrl() => handle_paste(app_model),
+        X if modifiers.cmd_or_ctrl() => handle_cut(app_model),
+        C | V | X => Ok(InputOutcome::SilentIgnored),
 
-        _ => pass_keydown_to_focused(&modifiers, virtual_keycode, app_model)?,
+        _ => pass_keydown_to_focused(&modifiers, virtual_keycode, app_model),
     }
-
-    Ok(())
 }
 
 // pub fn handle_text_input(
@@ -163,6 +163,11 @@ fn run_event_loop(project_dir_path_opt: Option<&Path>) -> Result<(), Box<dyn Err
                     &mut glyph_brush,
                     config.code_font_size,
                 ));
+                ed_model.resize_viewport(
+                    size.width as f32,
+                    size.height as f32,
+                    config.make_code_txt_xy(),
+                );
 
                 Some(ed_model)
             }
@@ -223,6 +228,14 @@ fn run_event_loop(project_dir_path_opt: Option<&Path>) -> Result<(), Box<dyn Err
                     &rect_resources.ortho.buffer,
                     &cmd_queue,
                 );
+
+                if let Some(ref mut ed_model) = app_model.ed_model_opt {
+                    ed_model.resize_viewport(
+                        size.width as f32,
+                        size.height as f32,
+                        config.make_code_txt_xy(),
+                    );
+                }
             }
             //Received Character
             Event::WindowEvent {
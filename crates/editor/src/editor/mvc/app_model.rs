@@ -25,6 +25,7 @@ impl<'a> AppModel<'a> {
         }
     }
 
+    #[cfg(not(test))]
     pub fn init_clipboard_opt() -> Option<Clipboard> {
         let clipboard_res = Clipboard::init();
 
@@ -36,10 +37,18 @@ impl<'a> AppModel<'a> {
             }
         }
     }
+
+    // tests should not depend on (or mess with) the OS clipboard
+    #[cfg(test)]
+    pub fn init_clipboard_opt() -> Option<Clipboard> {
+        Some(Clipboard::in_memory())
+    }
 }
 
-pub struct Clipboard {
-    context: ClipboardContext,
+pub enum Clipboard {
+    System(ClipboardContext),
+    // does not touch the OS clipboard, used for tests and as a headless fallback
+    InMemory(String),
 }
 
 impl Clipboard {
@@ -47,33 +56,43 @@ impl Clipboard {
         let context_res = ClipboardContext::new();
 
         match context_res {
-            Ok(context) => Ok(Clipboard { context }),
+            Ok(context) => Ok(Clipboard::System(context)),
             Err(e) => Err(ClipboardInitFailed {
                 err_msg: e.to_string(),
             }),
         }
     }
 
+    pub fn in_memory() -> Clipboard {
+        Clipboard::InMemory(String::new())
+    }
+
     // clipboard crate needs this to be mutable
     pub fn get_content(&mut self) -> EdResult<String> {
-        let content_res = self.context.get_contents();
-
-        match content_res {
-            Ok(content_str) => Ok(content_str),
-            Err(e) => Err(ClipboardReadFailed {
-                err_msg: e.to_string(),
-            }),
+        match self {
+            Clipboard::System(context) => match context.get_contents() {
+                Ok(content_str) => Ok(content_str),
+                Err(e) => Err(ClipboardReadFailed {
+                    err_msg: e.to_string(),
+                }),
+            },
+            Clipboard::InMemory(content) => Ok(content.clone()),
         }
     }
 
     pub fn set_content(&mut self, copy_str: String) -> EdResult<()> {
-        let content_set_res = self.context.set_contents(copy_str);
-
-        match content_set_res {
-            Ok(_) => Ok(()),
-            Err(e) => Err(ClipboardWriteFailed {
-                err_msg: e.to_string(),
-            }),
+        match self {
+            Clipboard::System(context) => match context.set_contents(copy_str) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClipboardWriteFailed {
+                    err_msg: e.to_string(),
+                }),
+            },
+            Clipboard::InMemory(content) => {
+                *content = copy_str;
+
+                Ok(())
+            }
         }
     }
 }
@@ -106,3 +125,31 @@ impl fmt::Debug for Clipboard {
         f.debug_struct("Clipboard (can't show)").finish()
     }
 }
+
+#[cfg(test)]
+pub mod test_app_model {
+    use crate::editor::mvc::app_model::{get_clipboard_txt, set_clipboard_txt, Clipboard};
+
+    #[test]
+    fn in_memory_clipboard() -> Result<(), String> {
+        let mut clipboard_opt = Some(Clipboard::in_memory());
+
+        assert_eq!(get_clipboard_txt(&mut clipboard_opt)?, "");
+
+        set_clipboard_txt(&mut clipboard_opt, "abc")?;
+        assert_eq!(get_clipboard_txt(&mut clipboard_opt)?, "abc");
+
+        set_clipboard_txt(&mut clipboard_opt, "a\n b\n")?;
+        assert_eq!(get_clipboard_txt(&mut clipboard_opt)?, "a\n b\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn uninitialized_clipboard() {
+        let mut clipboard_opt: Option<Clipboard> = None;
+
+        assert!(set_clipboard_txt(&mut clipboard_opt, "abc").is_err());
+        assert!(get_clipboard_txt(&mut clipboard_opt).is_err());
+    }
+}
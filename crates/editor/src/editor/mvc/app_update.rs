@@ -1,55 +1,251 @@
-use super::app_model::AppModel;
+use super::app_model::{self, AppModel};
 use super::ed_update;
+use crate::ui::text::caret_w_select::CaretWSelect;
+use crate::ui::text::lines::{Lines, SelectableLines};
+use crate::ui::text::selection::validate_selection;
+use crate::ui::text::text_pos::TextPos;
 use crate::window::keyboard_input::Modifiers;
 use crate::{editor::ed_error::EdResult, window::keyboard_input::from_winit};
 use winit::event::{ModifiersState, VirtualKeyCode};
 
-pub fn handle_copy(app_model: &mut AppModel) -> EdResult<()> {
+// The handlers below return SilentIgnored when there is no focused editor to handle the event,
+// so the host can fall through to its own shortcuts.
+
+pub fn handle_copy(app_model: &mut AppModel) -> EdResult<InputOutcome> {
+    if let Some(ref mut ed_model) = app_model.ed_model_opt {
+        if ed_model.has_focus {
+            if let Some(selected_str) = ed_model.get_selected_str()? {
+                app_model::set_clipboard_txt(&mut app_model.clipboard_opt, &selected_str)?;
+            }
+
+            return Ok(InputOutcome::Accepted);
+        }
+    }
+
+    Ok(InputOutcome::SilentIgnored)
+}
+
+// The code is edited through its AST, so pasting types the clipboard text char by char,
+// like the user would. Chars that can't be typed at that point are skipped.
+pub fn handle_paste(app_model: &mut AppModel) -> EdResult<InputOutcome> {
+    if let Some(ref mut ed_model) = app_model.ed_model_opt {
+        if ed_model.has_focus {
+            let clipboard_txt = app_model::get_clipboard_txt(&mut app_model.clipboard_opt)?;
+            let mut pasted = false;
+
+            for received_char in clipboard_txt.chars() {
+                // pasted text inserts, it never deletes
+                let new_char_opt = filter_control_char(received_char)
+                    .filter(|new_char| !matches!(new_char, '\u{8}' | '\u{7f}'));
+
+                if let Some(new_char) = new_char_opt {
+                    let outcome = ed_update::handle_new_char(&new_char, ed_model)?;
+                    pasted |= matches!(outcome, InputOutcome::Accepted);
+                }
+            }
+
+            ed_model.ensure_caret_visible();
+
+            return if pasted {
+                Ok(InputOutcome::Accepted)
+            } else {
+                Ok(InputOutcome::Ignored)
+            };
+        }
+    }
+
+    Ok(InputOutcome::SilentIgnored)
+}
+
+// copy the selected expression to the clipboard and replace it by a blank, like backspace does
+pub fn handle_cut(app_model: &mut AppModel) -> EdResult<InputOutcome> {
+    if let Some(ref mut ed_model) = app_model.ed_model_opt {
+        if ed_model.has_focus {
+            return match ed_model.get_selected_str()? {
+                Some(selected_str) => {
+                    app_model::set_clipboard_txt(&mut app_model.clipboard_opt, &selected_str)?;
+
+                    let outcome = ed_update::handle_new_char(&'\u{8}', ed_model)?;
+                    ed_model.ensure_caret_visible();
+
+                    Ok(outcome)
+                }
+                None => Ok(InputOutcome::Ignored),
+            };
+        }
+    }
+
+    Ok(InputOutcome::SilentIgnored)
+}
+
+// select the next match of needle after the caret or active selection, wraps around at the end of the file
+pub fn handle_find(app_model: &mut AppModel, needle: &str) -> EdResult<InputOutcome> {
     if let Some(ref mut ed_model) = app_model.ed_model_opt {
         if ed_model.has_focus {
-            unimplemented!("TODO");
+            let search_start = match ed_model.get_selection() {
+                Some(selection) => selection.end_pos,
+                None => ed_model.get_caret(),
+            };
+
+            if let Some((start_pos, end_pos)) =
+                ed_model.code_lines.find_next(search_start, needle, true)
+            {
+                let selection = validate_selection(start_pos, end_pos)?;
+
+                ed_model.set_caret_w_sel(CaretWSelect::new(end_pos, Some(selection)));
+                ed_model.dirty = true;
+                ed_model.ensure_caret_visible();
+            }
+
+            return Ok(InputOutcome::Accepted);
         }
     }
 
-    Ok(())
+    Ok(InputOutcome::SilentIgnored)
 }
 
-pub fn handle_paste(app_model: &mut AppModel) -> EdResult<()> {
+// replace the next match of needle after the caret, or every match if all is true, by typing replacement over it.
+// The code is edited through its AST, so only a match that is a whole expression can be replaced, other matches are skipped.
+pub fn handle_replace(
+    app_model: &mut AppModel,
+    needle: &str,
+    replacement: &str,
+    all: bool,
+) -> EdResult<InputOutcome> {
     if let Some(ref mut ed_model) = app_model.ed_model_opt {
         if ed_model.has_focus {
-            unimplemented!("TODO");
+            let old_caret_pos = ed_model.get_caret();
+            let mut search_start = if all {
+                TextPos { line: 0, column: 0 }
+            } else {
+                // start at the selection so a selected match gets replaced
+                match ed_model.get_selection() {
+                    Some(selection) => selection.start_pos,
+                    None => old_caret_pos,
+                }
+            };
+            let mut replaced = false;
+
+            while let Some((start_pos, end_pos)) =
+                ed_model.code_lines.find_next(search_start, needle, !all)
+            {
+                ed_model.set_sel_none();
+                ed_model.set_caret(start_pos);
+                ed_model.select_expr()?;
+
+                let match_is_expr = ed_model.get_selection().map_or(false, |selection| {
+                    selection.start_pos == start_pos && selection.end_pos == end_pos
+                });
+
+                let mut match_replaced = false;
+
+                if match_is_expr {
+                    // typing over the selected expression replaces it, backspace replaces it with a blank
+                    let input_chars: Vec<char> = if replacement.is_empty() {
+                        vec!['\u{8}']
+                    } else {
+                        replacement.chars().collect()
+                    };
+
+                    for input_char in input_chars {
+                        match ed_update::handle_new_char(&input_char, ed_model)? {
+                            InputOutcome::Accepted => match_replaced = true,
+                            _ => break,
+                        }
+                    }
+                }
+
+                // continue after the replacement, or after the match if it was left as is,
+                // so every match is visited at most once
+                search_start = match ed_model.get_caret() {
+                    caret_pos if match_replaced && caret_pos > start_pos => caret_pos,
+                    _ => end_pos,
+                };
+                ed_model.set_sel_none();
+                replaced |= match_replaced;
+
+                if !all {
+                    break;
+                }
+            }
+
+            let outcome = if replaced {
+                ed_model.dirty = true;
+
+                InputOutcome::Accepted
+            } else {
+                ed_model.set_caret(old_caret_pos);
+
+                InputOutcome::Ignored
+            };
+
+            ed_model.ensure_caret_visible();
+
+            return Ok(outcome);
         }
     }
 
-    Ok(())
+    Ok(InputOutcome::SilentIgnored)
 }
 
-pub fn handle_cut(app_model: &mut AppModel) -> EdResult<()> {
+// move the caret to the bracket that matches the one at the caret, or select both brackets and everything in between
+pub fn handle_match_bracket(app_model: &mut AppModel, select: bool) -> EdResult<InputOutcome> {
     if let Some(ref mut ed_model) = app_model.ed_model_opt {
         if ed_model.has_focus {
-            unimplemented!("TODO");
+            let caret_pos = ed_model.get_caret();
+
+            if let Some(match_pos) = ed_model.code_lines.match_bracket(caret_pos) {
+                let caret_w_select = if select {
+                    let (open_pos, close_pos) = if match_pos > caret_pos {
+                        (caret_pos, match_pos)
+                    } else {
+                        (match_pos, caret_pos)
+                    };
+                    // brackets are a single byte, so this is right after the closing one
+                    let after_close_pos = TextPos {
+                        line: close_pos.line,
+                        column: close_pos.column + 1,
+                    };
+
+                    CaretWSelect::new(
+                        after_close_pos,
+                        Some(validate_selection(open_pos, after_close_pos)?),
+                    )
+                } else {
+                    CaretWSelect::new(match_pos, None)
+                };
+
+                ed_model.set_caret_w_sel(caret_w_select);
+                ed_model.dirty = true;
+                ed_model.ensure_caret_visible();
+            }
+
+            return Ok(InputOutcome::Accepted);
         }
     }
 
-    Ok(())
+    Ok(InputOutcome::SilentIgnored)
 }
 
 pub fn pass_keydown_to_focused(
     modifiers: &Modifiers,
     virtual_keycode: VirtualKeyCode,
     app_model: &mut AppModel,
-) -> EdResult<()> {
+) -> EdResult<InputOutcome> {
     if let Some(ref mut ed_model) = app_model.ed_model_opt {
         if ed_model.has_focus {
-            ed_model.ed_handle_key_down(
+            let outcome = ed_model.ed_handle_key_down(
                 modifiers,
                 virtual_keycode,
                 &mut app_model.sound_thread_pool,
             )?;
+            ed_model.ensure_caret_visible();
+
+            return Ok(outcome);
         }
     }
 
-    Ok(())
+    Ok(InputOutcome::SilentIgnored)
 }
 
 #[derive(Debug)]
@@ -70,7 +266,15 @@ pub fn handle_new_char(
 
             if modifiers.new_char_modifiers() {
                 // shortcuts with modifiers are handled by ed_handle_key_down
-                return ed_update::handle_new_char(received_char, ed_model);
+                return match filter_control_char(*received_char) {
+                    Some(new_char) => {
+                        let outcome = ed_update::handle_new_char(&new_char, ed_model)?;
+                        ed_model.ensure_caret_visible();
+
+                        Ok(outcome)
+                    }
+                    None => Ok(InputOutcome::Ignored),
+                };
             }
         }
     }
@@ -78,42 +282,67 @@ pub fn handle_new_char(
     Ok(InputOutcome::SilentIgnored)
 }
 
-/*
+// Depending on the platform, winit also delivers control characters as received chars.
+// Returns the char that ed_update should handle, or None if it should not be inserted.
+fn filter_control_char(received_char: char) -> Option<char> {
+    match received_char {
+        // backspace on Linux and macOS respectively, ed_update handles both
+        '\u{8}' | '\u{7f}' => Some(received_char),
+        // ed_update expects '\r' for a newline
+        '\r' | '\n' => Some('\r'),
+        ch if ch.is_control() => None,
+        ch => Some(ch),
+    }
+}
+
 #[cfg(test)]
 pub mod test_app_update {
-    use crate::editor::mvc::app_model;
-    use crate::editor::mvc::app_model::{AppModel, Clipboard};
-    use crate::editor::mvc::app_update::{handle_copy, handle_cut, handle_paste};
-    use crate::editor::mvc::ed_model::EdModel;
-    use crate::ui::text::{
-        big_selectable_text::test_big_sel_text::{
-            all_lines_vec, convert_selection_to_dsl, gen_big_text,
-        },
-        big_selectable_text::BigSelectableText,
+    use crate::editor::mvc::app_model::{self, AppModel, Clipboard};
+    use crate::editor::mvc::app_update::{
+        handle_copy, handle_cut, handle_find, handle_new_char, handle_paste, handle_replace,
+        pass_keydown_to_focused, InputOutcome,
     };
+    use crate::editor::mvc::ed_model::test_ed_model::{
+        ed_model_from_dsl, ed_model_to_dsl, init_model_refs,
+    };
+    use crate::editor::mvc::ed_model::Viewport;
+    use crate::editor::resources::strings::nr_hello_world_lines;
+    use crate::graphics::primitives::rect::Rect;
+    use crate::ui::text::lines::SelectableLines;
+    use crate::ui::text::text_pos::TextPos;
+    use crate::window::keyboard_input::no_mods;
+    use crate::window::keyboard_input::test_modifiers::ctrl_cmd_shift;
+    use bumpalo::Bump;
+    use roc_code_markup::markup::common_nodes::NEW_LINES_AFTER_DEF;
+    use roc_module::symbol::ModuleIds;
+    use threadpool::ThreadPool;
+    use winit::event::ModifiersState;
+    use winit::event::VirtualKeyCode::*;
+
+    // select the expression at the caret with ctrl+shift+up, copy it and check the clipboard content
+    fn assert_copy_expr(pre_line: &str, expected_clipboard_content: &str) -> Result<(), String> {
+        let pre_lines = vec![pre_line.to_owned()];
+        let mut code_str = pre_line.replace('┃', "");
+
+        let mut model_refs = init_model_refs();
+        let code_arena = Bump::new();
+        let module_ids = ModuleIds::default();
+
+        let mut ed_model = ed_model_from_dsl(
+            &mut code_str,
+            pre_lines,
+            &mut model_refs,
+            &module_ids,
+            &code_arena,
+        )?;
 
-    pub fn mock_app_model(
-        big_sel_text: BigSelectableText,
-        clipboard_opt: Option<Clipboard>,
-    ) -> AppModel {
-        AppModel {
-            ed_model_opt: Some(EdModel {
-                text: big_sel_text,
-                glyph_dim_rect_opt: None,
-                has_focus: true,
-            }),
-            clipboard_opt,
-        }
-    }
-
-    fn assert_copy(
-        pre_lines_str: &[&str],
-        expected_clipboard_content: &str,
-        clipboard_opt: Option<Clipboard>,
-    ) -> Result<Option<Clipboard>, String> {
-        let pre_text_buf = gen_big_text(pre_lines_str)?;
+        ed_model.ed_handle_key_down(&ctrl_cmd_shift(), Up, &mut ThreadPool::new(1))?;
 
-        let mut app_model = mock_app_model(pre_text_buf, clipboard_opt);
+        let mut app_model = AppModel {
+            ed_model_opt: Some(ed_model),
+            clipboard_opt: Some(Clipboard::in_memory()),
+            sound_thread_pool: ThreadPool::new(1),
+        };
 
         handle_copy(&mut app_model)?;
 
@@ -121,109 +350,587 @@ pub mod test_app_update {
 
         assert_eq!(clipboard_content, expected_clipboard_content);
 
-        Ok(app_model.clipboard_opt)
+        Ok(())
     }
 
-    fn assert_paste(
-        pre_lines_str: &[&str],
-        clipboard_content: &str,
-        expected_post_lines_str: &[&str],
-        clipboard_opt: Option<Clipboard>,
-    ) -> Result<Option<Clipboard>, String> {
-        let pre_big_text = gen_big_text(pre_lines_str)?;
+    #[test]
+    fn copy() -> Result<(), String> {
+        assert_copy_expr("val = 5┃", "5")?;
+        assert_copy_expr("val = 1┃2345", "12345")?;
+        assert_copy_expr("val = \"┃abc\"", "\"abc\"")?;
 
-        let mut app_model = mock_app_model(pre_big_text, clipboard_opt);
+        Ok(())
+    }
 
-        app_model::set_clipboard_txt(&mut app_model.clipboard_opt, clipboard_content)?;
+    // the code of the ed_model as DSL, without the header
+    fn code_dsl(app_model: &AppModel) -> Result<Vec<String>, String> {
+        // safe unwrap, the tests set the ed_model
+        let mut post_lines = ed_model_to_dsl(app_model.ed_model_opt.as_ref().unwrap())?;
+        post_lines.drain(0..nr_hello_world_lines()); // remove header for clean tests
+        post_lines.truncate(post_lines.len() - NEW_LINES_AFTER_DEF);
 
-        handle_paste(&mut app_model)?;
+        Ok(post_lines)
+    }
 
-        let ed_model = app_model.ed_model_opt.unwrap();
-        let mut text_lines = all_lines_vec(&ed_model.text);
-        let post_lines_str =
-            convert_selection_to_dsl(ed_model.text.caret_w_select, &mut text_lines)?;
+    fn select_expr(app_model: &mut AppModel) -> Result<(), String> {
+        if let Some(ed_model) = app_model.ed_model_opt.as_mut() {
+            ed_model.ed_handle_key_down(&ctrl_cmd_shift(), Up, &mut ThreadPool::new(1))?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn copy_paste_cut() -> Result<(), String> {
+        let pre_line = "val = 95┃21";
+        let mut code_str = pre_line.replace('┃', "");
+
+        let mut model_refs = init_model_refs();
+        let code_arena = Bump::new();
+        let module_ids = ModuleIds::default();
+
+        let ed_model = ed_model_from_dsl(
+            &mut code_str,
+            vec![pre_line.to_owned()],
+            &mut model_refs,
+            &module_ids,
+            &code_arena,
+        )?;
 
-        assert_eq!(post_lines_str, expected_post_lines_str);
+        let mut app_model = AppModel {
+            ed_model_opt: Some(ed_model),
+            clipboard_opt: Some(Clipboard::in_memory()),
+            sound_thread_pool: ThreadPool::new(1),
+        };
+
+        // nothing is selected, so there is nothing to cut
+        assert!(matches!(handle_cut(&mut app_model)?, InputOutcome::Ignored));
+        assert_eq!(code_dsl(&app_model)?, vec!["val = 95┃21".to_owned()]);
+
+        // cut leaves a blank where the expression was
+        select_expr(&mut app_model)?;
+        assert!(matches!(
+            handle_cut(&mut app_model)?,
+            InputOutcome::Accepted
+        ));
+        assert_eq!(
+            app_model::get_clipboard_txt(&mut app_model.clipboard_opt)?,
+            "9521"
+        );
+        assert_eq!(code_dsl(&app_model)?, vec!["val = ┃ ".to_owned()]);
+
+        // paste fills the blank
+        assert!(matches!(
+            handle_paste(&mut app_model)?,
+            InputOutcome::Accepted
+        ));
+        assert_eq!(code_dsl(&app_model)?, vec!["val = 9521┃".to_owned()]);
+
+        // pasting over a selection replaces it
+        app_model::set_clipboard_txt(&mut app_model.clipboard_opt, "34")?;
+        select_expr(&mut app_model)?;
+        assert!(matches!(
+            handle_paste(&mut app_model)?,
+            InputOutcome::Accepted
+        ));
+        assert_eq!(code_dsl(&app_model)?, vec!["val = 34┃".to_owned()]);
+
+        // text that can't be typed there is not pasted
+        app_model::set_clipboard_txt(&mut app_model.clipboard_opt, ")")?;
+        assert!(matches!(
+            handle_paste(&mut app_model)?,
+            InputOutcome::Ignored
+        ));
+        assert_eq!(code_dsl(&app_model)?, vec!["val = 34┃".to_owned()]);
+
+        // copy leaves the code as is
+        select_expr(&mut app_model)?;
+        assert!(matches!(
+            handle_copy(&mut app_model)?,
+            InputOutcome::Accepted
+        ));
+        assert_eq!(
+            app_model::get_clipboard_txt(&mut app_model.clipboard_opt)?,
+            "34"
+        );
 
-        Ok(app_model.clipboard_opt)
+        Ok(())
     }
 
-    fn assert_cut(
-        pre_lines_str: &[&str],
-        expected_clipboard_content: &str,
-        expected_post_lines_str: &[&str],
-        clipboard_opt: Option<Clipboard>,
-    ) -> Result<Option<Clipboard>, String> {
-        let pre_big_text = gen_big_text(pre_lines_str)?;
+    fn is_silent_ignored(outcome: InputOutcome) -> bool {
+        matches!(outcome, InputOutcome::SilentIgnored)
+    }
 
-        let mut app_model = mock_app_model(pre_big_text, clipboard_opt);
+    #[test]
+    fn no_model_does_not_consume() -> Result<(), String> {
+        let mut app_model = AppModel {
+            ed_model_opt: None,
+            clipboard_opt: Some(Clipboard::in_memory()),
+            sound_thread_pool: ThreadPool::new(1),
+        };
+
+        assert!(is_silent_ignored(handle_copy(&mut app_model)?));
+        assert!(is_silent_ignored(handle_paste(&mut app_model)?));
+        assert!(is_silent_ignored(handle_cut(&mut app_model)?));
+        assert!(is_silent_ignored(handle_find(&mut app_model, "a")?));
+        assert!(is_silent_ignored(handle_replace(
+            &mut app_model,
+            "a",
+            "b",
+            false
+        )?));
+        assert!(is_silent_ignored(pass_keydown_to_focused(
+            &no_mods(),
+            Left,
+            &mut app_model
+        )?));
+        assert!(is_silent_ignored(handle_new_char(
+            &'a',
+            &mut app_model,
+            ModifiersState::empty()
+        )?));
 
-        handle_cut(&mut app_model)?;
+        Ok(())
+    }
 
-        let clipboard_content = app_model::get_clipboard_txt(&mut app_model.clipboard_opt)?;
+    // Create ed_model from a single DSL line with a viewport of the given size at top_line,
+    // then move the caret one position to the right.
+    fn viewport_after_move_right(
+        pre_line: &str,
+        top_line: usize,
+        height_lines: usize,
+    ) -> Result<(TextPos, Viewport), String> {
+        let mut code_str = pre_line.replace('┃', "");
+
+        let mut model_refs = init_model_refs();
+        let code_arena = Bump::new();
+        let module_ids = ModuleIds::default();
+
+        let mut ed_model = ed_model_from_dsl(
+            &mut code_str,
+            vec![pre_line.to_owned()],
+            &mut model_refs,
+            &module_ids,
+            &code_arena,
+        )?;
+        ed_model.viewport = Viewport {
+            top_line,
+            left_col: 0,
+            height_lines,
+            width_cols: 80,
+        };
 
-        assert_eq!(clipboard_content, expected_clipboard_content);
+        let mut app_model = AppModel {
+            ed_model_opt: Some(ed_model),
+            clipboard_opt: Some(Clipboard::in_memory()),
+            sound_thread_pool: ThreadPool::new(1),
+        };
 
-        let ed_model = app_model.ed_model_opt.unwrap();
-        let mut text_lines = all_lines_vec(&ed_model.text);
-        let post_lines_str =
-            convert_selection_to_dsl(ed_model.text.caret_w_select, &mut text_lines)?;
+        pass_keydown_to_focused(&no_mods(), Right, &mut app_model)?;
 
-        assert_eq!(post_lines_str, expected_post_lines_str);
+        // safe unwrap, the ed_model was set above
+        let ed_model = app_model.ed_model_opt.as_ref().unwrap();
 
-        Ok(app_model.clipboard_opt)
+        Ok((ed_model.get_caret(), ed_model.viewport))
     }
 
     #[test]
-    #[ignore] // ignored because of clipboard problems on ci
-    fn copy_paste_cut() -> Result<(), String> {
-        // can only init clipboard once
-        let mut clipboard_opt = AppModel::init_clipboard_opt();
-
-        // copy
-        clipboard_opt = assert_copy(&["[a]|"], "a", clipboard_opt)?;
-        clipboard_opt = assert_copy(&["|[b]"], "b", clipboard_opt)?;
-        clipboard_opt = assert_copy(&["a[ ]|"], " ", clipboard_opt)?;
-        clipboard_opt = assert_copy(&["[ ]|b"], " ", clipboard_opt)?;
-        clipboard_opt = assert_copy(&["a\n", "[b\n", "]|"], "b\n", clipboard_opt)?;
-        clipboard_opt = assert_copy(&["[a\n", " b\n", "]|"], "a\n b\n", clipboard_opt)?;
-        clipboard_opt = assert_copy(
-            &["abc\n", "d[ef\n", "ghi]|\n", "jkl"],
-            "ef\nghi",
-            clipboard_opt,
-        )?;
-
-        // paste
-
-        clipboard_opt = assert_paste(&["|"], "", &["|"], clipboard_opt)?;
-        clipboard_opt = assert_paste(&["|"], "a", &["a|"], clipboard_opt)?;
-        clipboard_opt = assert_paste(&["a|"], "b", &["ab|"], clipboard_opt)?;
-        clipboard_opt = assert_paste(&["|a"], "b", &["b|a"], clipboard_opt)?;
-        clipboard_opt = assert_paste(&["[a]|"], "c", &["c|"], clipboard_opt)?;
-        clipboard_opt = assert_paste(&["[ab]|"], "d", &["d|"], clipboard_opt)?;
-        clipboard_opt = assert_paste(&["a[b]|c"], "e", &["ae|c"], clipboard_opt)?;
-        clipboard_opt = assert_paste(&["a\n", "[b\n", "]|"], "f", &["a\n", "f|"], clipboard_opt)?;
-        clipboard_opt = assert_paste(
-            &["abc\n", "d[ef\n", "ghi]|\n", "jkl"],
-            "ef\nghi",
-            &["abc\n", "def\n", "ghi|\n", "jkl"],
-            clipboard_opt,
-        )?;
-
-        // cut
-        clipboard_opt = assert_cut(&["[a]|"], "a", &["|"], clipboard_opt)?;
-        clipboard_opt = assert_cut(&["|[b]"], "b", &["|"], clipboard_opt)?;
-        clipboard_opt = assert_cut(&["a[ ]|"], " ", &["a|"], clipboard_opt)?;
-        clipboard_opt = assert_cut(&["[ ]|b"], " ", &["|b"], clipboard_opt)?;
-        clipboard_opt = assert_cut(&["a\n", "[b\n", "]|"], "b\n", &["a\n", "|"], clipboard_opt)?;
-        clipboard_opt = assert_cut(&["[a\n", " b\n", "]|"], "a\n b\n", &["|"], clipboard_opt)?;
-        assert_cut(
-            &["abc\n", "d[ef\n", "ghi]|\n", "jkl"],
-            "ef\nghi",
-            &["abc\n", "d|\n", "jkl"],
-            clipboard_opt,
-        )?;
-
-        Ok(())
-    }
-}*/
+    fn caret_below_viewport_scrolls_down() -> Result<(), String> {
+        let (caret_pos, viewport) = viewport_after_move_right("val = ┃5", 0, 2)?;
+
+        assert_eq!(caret_pos.line, nr_hello_world_lines());
+        // the caret ends up on the last visible line
+        assert_eq!(viewport.top_line, caret_pos.line - 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn caret_above_viewport_scrolls_up() -> Result<(), String> {
+        let (caret_pos, viewport) = viewport_after_move_right("val = ┃5", 100, 2)?;
+
+        assert_eq!(viewport.top_line, caret_pos.line);
+
+        Ok(())
+    }
+
+    #[test]
+    fn caret_inside_viewport_does_not_scroll() -> Result<(), String> {
+        let (_, viewport) = viewport_after_move_right("val = ┃5", 0, 100)?;
+
+        assert_eq!(viewport.top_line, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn viewport_fits_window() {
+        let glyph_dim_rect = Rect {
+            color: (0.0, 0.0, 0.0, 0.0),
+            height: 20.0,
+            top_left_coords: (0.0, 0.0).into(),
+            width: 10.0,
+        };
+        let mut viewport = Viewport::default();
+
+        // code starts 20 pixels from the left and 100 from the top
+        viewport.resize(1000.0, 500.0, (20.0, 100.0), &glyph_dim_rect);
+        assert_eq!(viewport.height_lines, 20);
+        assert_eq!(viewport.width_cols, 98);
+
+        // a window too small for any code still shows the caret
+        viewport.resize(10.0, 50.0, (20.0, 100.0), &glyph_dim_rect);
+        assert_eq!(viewport.height_lines, 1);
+        assert_eq!(viewport.width_cols, 1);
+    }
+
+    #[test]
+    fn unfocused_does_not_consume() -> Result<(), String> {
+        let pre_line = "val = 5┃";
+        let mut code_str = pre_line.replace('┃', "");
+
+        let mut model_refs = init_model_refs();
+        let code_arena = Bump::new();
+        let module_ids = ModuleIds::default();
+
+        let mut ed_model = ed_model_from_dsl(
+            &mut code_str,
+            vec![pre_line.to_owned()],
+            &mut model_refs,
+            &module_ids,
+            &code_arena,
+        )?;
+        ed_model.has_focus = false;
+
+        let mut app_model = AppModel {
+            ed_model_opt: Some(ed_model),
+            clipboard_opt: Some(Clipboard::in_memory()),
+            sound_thread_pool: ThreadPool::new(1),
+        };
+
+        assert!(is_silent_ignored(handle_copy(&mut app_model)?));
+        assert!(is_silent_ignored(pass_keydown_to_focused(
+            &no_mods(),
+            Left,
+            &mut app_model
+        )?));
+
+        // once focused, the same events are consumed
+        if let Some(ed_model) = app_model.ed_model_opt.as_mut() {
+            ed_model.has_focus = true;
+        }
+
+        assert!(matches!(
+            handle_copy(&mut app_model)?,
+            InputOutcome::Accepted
+        ));
+        assert!(matches!(
+            pass_keydown_to_focused(&no_mods(), Left, &mut app_model)?,
+            InputOutcome::Accepted
+        ));
+
+        // keys the editor has no use for are left to the host, even when focused
+        assert!(is_silent_ignored(pass_keydown_to_focused(
+            &no_mods(),
+            F1,
+            &mut app_model
+        )?));
+        assert!(is_silent_ignored(pass_keydown_to_focused(
+            &no_mods(),
+            A,
+            &mut app_model
+        )?));
+
+        Ok(())
+    }
+
+    // Create ed_model from pre_lines DSL, optionally select the expression at the caret with ctrl+shift+up,
+    // then pass every char of input_seq to app_update::handle_new_char like winit would.
+    // 🡲 moves the caret to the right instead.
+    fn assert_received_chars(
+        pre_lines: Vec<String>,
+        expected_post_lines: Vec<String>,
+        select_expr: bool,
+        input_seq: &str,
+    ) -> Result<(), String> {
+        let mut code_str = pre_lines.join("\n").replace('┃', "");
+
+        let mut model_refs = init_model_refs();
+        let code_arena = Bump::new();
+        let module_ids = ModuleIds::default();
+
+        let mut ed_model = ed_model_from_dsl(
+            &mut code_str,
+            pre_lines,
+            &mut model_refs,
+            &module_ids,
+            &code_arena,
+        )?;
+
+        if select_expr {
+            ed_model.ed_handle_key_down(&ctrl_cmd_shift(), Up, &mut ThreadPool::new(1))?;
+        }
+
+        let mut app_model = AppModel {
+            ed_model_opt: Some(ed_model),
+            clipboard_opt: Some(Clipboard::in_memory()),
+            sound_thread_pool: ThreadPool::new(1),
+        };
+
+        for input_char in input_seq.chars() {
+            if input_char == '🡲' {
+                if let Some(ed_model) = app_model.ed_model_opt.as_mut() {
+                    ed_model.simple_move_carets_right(1);
+                }
+            } else {
+                handle_new_char(&input_char, &mut app_model, ModifiersState::empty())?;
+            }
+        }
+
+        // safe unwrap, the ed_model was set above
+        let mut post_lines = ed_model_to_dsl(app_model.ed_model_opt.as_ref().unwrap())?;
+        post_lines.drain(0..nr_hello_world_lines()); // remove header for clean tests
+
+        let mut expected_post_lines = expected_post_lines;
+        expected_post_lines.extend(std::iter::repeat(String::new()).take(NEW_LINES_AFTER_DEF));
+
+        assert_eq!(post_lines, expected_post_lines);
+
+        Ok(())
+    }
+
+    #[test]
+    fn newline_chars() -> Result<(), String> {
+        let expected = vec!["ab = 5".to_owned(), "".to_owned(), "cd = 6┃".to_owned()];
+
+        assert_received_chars(
+            vec!["┃".to_owned()],
+            expected.clone(),
+            false,
+            "ab🡲🡲🡲5\rcd🡲🡲🡲6",
+        )?;
+        assert_received_chars(vec!["┃".to_owned()], expected, false, "ab🡲🡲🡲5\ncd🡲🡲🡲6")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn ignore_control_chars() -> Result<(), String> {
+        assert_received_chars(
+            vec!["┃".to_owned()],
+            vec!["ab = 5┃".to_owned()],
+            false,
+            "a\u{1b}b\t🡲🡲🡲\u{0}5\u{7}",
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn delete_chars_are_backspace() -> Result<(), String> {
+        // selected expressions are replaced by a blank, instead of inserting the char
+        assert_received_chars(
+            vec!["val = 95┃21".to_owned()],
+            vec!["val = ┃ ".to_owned()],
+            true,
+            "\u{7f}",
+        )?;
+        assert_received_chars(
+            vec!["val = 95┃21".to_owned()],
+            vec!["val = ┃ ".to_owned()],
+            true,
+            "\u{8}",
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn typing_replaces_selection() -> Result<(), String> {
+        assert_received_chars(
+            vec!["val = 95┃21".to_owned()],
+            vec!["val = 7┃".to_owned()],
+            true,
+            "7",
+        )?;
+        assert_received_chars(
+            vec!["val = { a: ┃12 }".to_owned()],
+            vec!["val = { a: 34┃ }".to_owned()],
+            true,
+            "34",
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn bracket_wraps_selection() -> Result<(), String> {
+        assert_received_chars(
+            vec!["val = 95┃21".to_owned()],
+            vec!["val = [ 9521┃ ]".to_owned()],
+            true,
+            "[",
+        )?;
+        assert_received_chars(
+            vec!["val = { a: ┃12 }".to_owned()],
+            vec!["val = { a: [ 12┃ ] }".to_owned()],
+            true,
+            "[",
+        )?;
+        // the caret is after the wrapped expression, so more elements can be added right away
+        assert_received_chars(
+            vec!["val = 95┃21".to_owned()],
+            vec!["val = [ 9521, 3┃ ]".to_owned()],
+            true,
+            "[,3",
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn closer_is_typed_over() -> Result<(), String> {
+        assert_received_chars(
+            vec!["val = [ 1┃ ]".to_owned()],
+            vec!["val = [ 1 ]┃".to_owned()],
+            false,
+            "]",
+        )?;
+        assert_received_chars(
+            vec!["val = { a: \"b┃\" }".to_owned()],
+            vec!["val = { a: \"b\" }┃".to_owned()],
+            false,
+            "\"}",
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn ignored_char_keeps_selection() -> Result<(), String> {
+        assert_received_chars(
+            vec!["val = 95┃21".to_owned()],
+            vec!["val = ┃❮9521❯".to_owned()],
+            true,
+            ")",
+        )?;
+        assert_received_chars(
+            vec!["val = { a: ┃12 }".to_owned()],
+            vec!["val = { a: ┃❮12❯ }".to_owned()],
+            true,
+            "=",
+        )?;
+
+        Ok(())
+    }
+
+    // Create ed_model from pre_lines DSL, then replace needle like app_update::handle_replace would.
+    fn assert_replace(
+        pre_lines: Vec<String>,
+        expected_post_lines: Vec<String>,
+        needle: &str,
+        replacement: &str,
+        all: bool,
+    ) -> Result<InputOutcome, String> {
+        let mut code_str = pre_lines.join("\n").replace('┃', "");
+
+        let mut model_refs = init_model_refs();
+        let code_arena = Bump::new();
+        let module_ids = ModuleIds::default();
+
+        let ed_model = ed_model_from_dsl(
+            &mut code_str,
+            pre_lines,
+            &mut model_refs,
+            &module_ids,
+            &code_arena,
+        )?;
+
+        let mut app_model = AppModel {
+            ed_model_opt: Some(ed_model),
+            clipboard_opt: Some(Clipboard::in_memory()),
+            sound_thread_pool: ThreadPool::new(1),
+        };
+
+        let outcome = handle_replace(&mut app_model, needle, replacement, all)?;
+
+        // safe unwrap, the ed_model was set above
+        let mut post_lines = ed_model_to_dsl(app_model.ed_model_opt.as_ref().unwrap())?;
+        post_lines.drain(0..nr_hello_world_lines()); // remove header for clean tests
+
+        let mut expected_post_lines = expected_post_lines;
+        expected_post_lines.extend(std::iter::repeat(String::new()).take(NEW_LINES_AFTER_DEF));
+
+        assert_eq!(post_lines, expected_post_lines);
+
+        Ok(outcome)
+    }
+
+    #[test]
+    fn replace_expr() -> Result<(), String> {
+        assert_replace(
+            vec!["val = ┃12".to_owned()],
+            vec!["val = 34┃".to_owned()],
+            "12",
+            "34",
+            false,
+        )?;
+        assert_replace(
+            vec!["val = { a: 12 }┃".to_owned()],
+            vec!["val = { a: 34┃ }".to_owned()],
+            "12",
+            "34",
+            false,
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn replace_skips_part_of_expr() -> Result<(), String> {
+        // 23 is only part of the int, it can't be replaced on its own
+        let outcome = assert_replace(
+            vec!["val = ┃1234".to_owned()],
+            vec!["val = ┃1234".to_owned()],
+            "23",
+            "5",
+            false,
+        )?;
+        assert!(matches!(outcome, InputOutcome::Ignored));
+
+        Ok(())
+    }
+
+    #[test]
+    fn replace_all_exprs() -> Result<(), String> {
+        let pre_lines = vec![
+            "a = 12┃".to_owned(),
+            "".to_owned(),
+            "b = 12".to_owned(),
+            "".to_owned(),
+            "c = 123".to_owned(),
+        ];
+
+        // 12 in 123 is only part of the int, so it is skipped
+        let outcome = assert_replace(
+            pre_lines.clone(),
+            vec![
+                "a = 34".to_owned(),
+                "".to_owned(),
+                "b = 34┃".to_owned(),
+                "".to_owned(),
+                "c = 123".to_owned(),
+            ],
+            "12",
+            "34",
+            true,
+        )?;
+        assert!(matches!(outcome, InputOutcome::Accepted));
+
+        // an int can't be replaced by a ')', every match is rejected and the caret stays put
+        let outcome = assert_replace(pre_lines.clone(), pre_lines, "12", ")", true)?;
+        assert!(matches!(outcome, InputOutcome::Ignored));
+
+        Ok(())
+    }
+}
@@ -15,6 +15,20 @@ pub fn break_line(ed_model: &mut EdModel) -> EdResult<InputOutcome> {
     for caret_pos in carets.iter() {
         let caret_line_nr = caret_pos.line;
 
+        // the definition continues on the next line, e.g. the body of a closure after `->`, move to its indentation
+        if let Some(indent_len) = continuation_indent(ed_model, caret_line_nr + 1)? {
+            ed_model.simple_move_caret_down(*caret_pos, 1);
+            ed_model.simple_move_caret_right(
+                TextPos {
+                    line: caret_line_nr + 1,
+                    column: 0,
+                },
+                indent_len,
+            );
+
+            continue;
+        }
+
         // don't allow adding new lines on empty line
         if caret_pos.column > 0
             && ed_model.grid_node_map.node_exists_at_pos(TextPos {
@@ -35,13 +49,30 @@ pub fn break_line(ed_model: &mut EdModel) -> EdResult<InputOutcome> {
                 insert_new_blank(ed_model, caret_pos.line + NEW_LINES_AFTER_DEF + 1)?;
             }
         }
-    }
 
-    ed_model.simple_move_carets_down(NEW_LINES_AFTER_DEF); // one blank lines between top level definitions
+        ed_model.simple_move_caret_down(*caret_pos, NEW_LINES_AFTER_DEF); // one blank lines between top level definitions
+    }
 
     Ok(InputOutcome::Accepted)
 }
 
+// nr of leading spaces of line_nr if it is an indented continuation of the definition above it
+fn continuation_indent(ed_model: &EdModel, line_nr: usize) -> EdResult<Option<usize>> {
+    if line_nr >= ed_model.code_lines.nr_of_lines() {
+        return Ok(None);
+    }
+
+    let line = ed_model.code_lines.get_line_ref(line_nr)?;
+    let indent_len = line.len() - line.trim_start_matches(' ').len();
+
+    // a line with only the blank placeholder is a new definition, not a continuation
+    if indent_len > 0 && indent_len < line.len() {
+        Ok(Some(indent_len))
+    } else {
+        Ok(None)
+    }
+}
+
 pub fn insert_new_blank(ed_model: &mut EdModel, insert_on_line_nr: usize) -> EdResult<()> {
     // find position of the previous ASTNode to figure out where to add this new Blank ASTNode
     let def_mark_node_id = ed_model.grid_node_map.get_def_mark_node_id_before_line(
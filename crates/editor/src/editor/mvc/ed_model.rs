@@ -21,6 +21,7 @@ use roc_code_markup::markup::nodes;
 use roc_code_markup::slow_pool::{MarkNodeId, SlowPool};
 use roc_load::LoadedModule;
 use roc_module::symbol::Interns;
+use std::cmp::max;
 use std::path::Path;
 
 /// Contains nearly all state related to a single roc file in the editor.
@@ -39,9 +40,66 @@ pub struct EdModel<'a> {
     pub selected_block_opt: Option<SelectedBlock>, // a selected AST node, the roc type of this node is shown in the editor on ctrl+shift+"up arrow"
     pub loaded_module: LoadedModule, // contains all roc symbols, exposed values, exposed aliases, solved types... in the file(=module)
     pub show_debug_view: bool,       // see render_debug.rs for the debug view
+    pub viewport: Viewport,          // the part of code_lines that is visible in the window
     pub dirty: bool, // EdModel is dirty if it has changed since the previous render.
 }
 
+/// The lines and columns of the file that fit in the editor window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Viewport {
+    pub top_line: usize,
+    pub left_col: usize,
+    pub height_lines: usize,
+    pub width_cols: usize,
+}
+
+impl Viewport {
+    // scroll as little as possible so txt_pos is inside the viewport
+    pub fn scroll_to(&mut self, txt_pos: TextPos) {
+        self.top_line = scroll_range(self.top_line, self.height_lines, txt_pos.line);
+        self.left_col = scroll_range(self.left_col, self.width_cols, txt_pos.column);
+    }
+
+    // fit the viewport to a window of the given size, code is drawn from code_txt_xy with glyphs the size of glyph_dim_rect
+    pub fn resize(
+        &mut self,
+        window_width: f32,
+        window_height: f32,
+        code_txt_xy: (f32, f32),
+        glyph_dim_rect: &Rect,
+    ) {
+        self.height_lines = fit_count(window_height - code_txt_xy.1, glyph_dim_rect.height);
+        self.width_cols = fit_count(window_width - code_txt_xy.0, glyph_dim_rect.width);
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport {
+            top_line: 0,
+            left_col: 0,
+            height_lines: 40,
+            width_cols: 120,
+        }
+    }
+}
+
+// how many items of item_size fit in available, at least 1 so the caret can always be shown
+fn fit_count(available: f32, item_size: f32) -> usize {
+    max((available / item_size) as usize, 1)
+}
+
+// returns the new start of a range of size len, so that it contains index
+fn scroll_range(start: usize, len: usize, index: usize) -> usize {
+    if index < start {
+        index
+    } else if index >= start + len {
+        (index + 1).saturating_sub(len)
+    } else {
+        start
+    }
+}
+
 // a selected AST node, the roc type of this node is shown in the editor on ctrl+shift+"up arrow"
 #[derive(Debug, Copy, Clone)]
 pub struct SelectedBlock {
@@ -116,6 +174,7 @@ pub fn init_model<'a>(
         selected_block_opt: None,
         loaded_module: owned_loaded_module,
         show_debug_view: false,
+        viewport: Viewport::default(),
         dirty: true,
     })
 }
@@ -151,6 +210,44 @@ impl<'a> EdModel<'a> {
         Ok(prev_id_opt)
     }
 
+    // scroll the viewport so the caret stays visible after a movement or edit
+    pub fn ensure_caret_visible(&mut self) {
+        let caret_pos = self.get_caret();
+
+        if !self.viewport_contains(caret_pos) {
+            self.viewport.scroll_to(caret_pos);
+            self.dirty = true;
+        }
+    }
+
+    // fit the viewport to a window of the given size, the caret stays visible
+    pub fn resize_viewport(
+        &mut self,
+        window_width: f32,
+        window_height: f32,
+        code_txt_xy: (f32, f32),
+    ) {
+        if let Some(glyph_dim_rect) = &self.glyph_dim_rect_opt {
+            self.viewport
+                .resize(window_width, window_height, code_txt_xy, glyph_dim_rect);
+            self.dirty = true;
+        }
+
+        self.ensure_caret_visible();
+    }
+
+    fn viewport_contains(&self, txt_pos: TextPos) -> bool {
+        let Viewport {
+            top_line,
+            left_col,
+            height_lines,
+            width_cols,
+        } = self.viewport;
+
+        (top_line..top_line + height_lines).contains(&txt_pos.line)
+            && (left_col..left_col + width_cols).contains(&txt_pos.column)
+    }
+
     pub fn node_exists_at_caret(&self) -> bool {
         self.grid_node_map.node_exists_at_pos(self.get_caret())
     }
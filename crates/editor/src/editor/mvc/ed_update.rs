@@ -12,7 +12,7 @@ use crate::editor::mvc::ed_model::EdModel;
 use crate::editor::mvc::ed_model::SelectedBlock;
 use crate::editor::mvc::int_update::start_new_int;
 use crate::editor::mvc::int_update::update_int;
-use crate::editor::mvc::list_update::{add_blank_child, start_new_list};
+use crate::editor::mvc::list_update::{add_blank_child, start_new_list, wrap_in_list};
 use crate::editor::mvc::lookup_update::update_invalid_lookup;
 use crate::editor::mvc::record_update::start_new_record;
 use crate::editor::mvc::record_update::update_empty_record;
@@ -122,16 +122,6 @@ impl<'a> EdModel<'a> {
         }
     }
 
-    // disregards EdModel.code_lines because the caller knows the resulting caret position will be valid.
-    // allows us to prevent multiple updates to EdModel.code_lines
-    pub fn simple_move_carets_down(&mut self, repeat: usize) {
-        for caret_tup in self.caret_w_select_vec.iter_mut() {
-            caret_tup.0.caret_pos.column = 0;
-            caret_tup.0.caret_pos.line += repeat;
-            caret_tup.1 = None;
-        }
-    }
-
     // disregards EdModel.code_lines because the caller knows the resulting caret position will be valid.
     // allows us to prevent multiple updates to EdModel.code_lines
     // TODO error if no match was found for old_caret_pos
@@ -504,12 +494,13 @@ impl<'a> EdModel<'a> {
         (solved_subs, solved_env, problems)
     }
 
+    // Returns SilentIgnored for keys that the editor has no use for, so the host can handle them.
     pub fn ed_handle_key_down(
         &mut self,
         modifiers: &Modifiers,
         virtual_keycode: VirtualKeyCode,
         _sound_thread_pool: &mut ThreadPool,
-    ) -> EdResult<()> {
+    ) -> EdResult<InputOutcome> {
         match virtual_keycode {
             Left => self.move_caret_left(modifiers)?,
             Up => {
@@ -522,21 +513,11 @@ impl<'a> EdModel<'a> {
             Right => self.move_caret_right(modifiers)?,
             Down => self.move_caret_down(modifiers)?,
 
-            A => {
-                if modifiers.cmd_or_ctrl() {
-                    self.select_all()?
-                }
-            }
-            S => {
-                if modifiers.cmd_or_ctrl() {
-                    self.save_file()?
-                }
-            }
-            R => {
-                if modifiers.cmd_or_ctrl() {
-                    self.check_file()?;
-                    self.run_file()?
-                }
+            A if modifiers.cmd_or_ctrl() => self.select_all()?,
+            S if modifiers.cmd_or_ctrl() => self.save_file()?,
+            R if modifiers.cmd_or_ctrl() => {
+                self.check_file()?;
+                self.run_file()?
             }
 
             Home => self.move_caret_home(modifiers)?,
@@ -552,10 +533,10 @@ impl<'a> EdModel<'a> {
                     play_sound("./editor/src/editor/resources/sounds/bell_sound.mp3");
                 });
             }
-            _ => (),
+            _ => return Ok(InputOutcome::SilentIgnored),
         }
 
-        Ok(())
+        Ok(InputOutcome::Accepted)
     }
 
     // Replaces selected expression with blank.
@@ -1183,11 +1164,70 @@ pub fn handle_new_char_diff_mark_nodes_prev_is_expr(
     Ok(outcome)
 }
 
+// whether a blank that replaced the node with ast_node_id would accept ch, see the Blank arms of
+// handle_new_char_def and handle_new_char_expr
+fn blank_accepts_char(ast_node_id: ASTNodeId, ch: &char) -> bool {
+    match ast_node_id {
+        ASTNodeId::ADefId(_) => matches!(ch, 'a'..='z'),
+        ASTNodeId::AExprId(_) => matches!(ch, 'a'..='z' | '"' | '{' | '0'..='9' | '['),
+    }
+}
+
+// Closing brackets and quotes are created together with their opening one, so typing one that is already
+// right after the caret moves over it. Returns how far the caret moves, None if ch is not such a closer.
+fn type_over_len(ch: &char, ed_model: &EdModel) -> EdResult<Option<usize>> {
+    if ed_model.selected_block_opt.is_some() || !ed_model.node_exists_at_caret() {
+        return Ok(None);
+    }
+
+    let curr_mark_node_id = ed_model.get_curr_mark_node_id()?;
+    let content = ed_model.mark_node_pool.get(curr_mark_node_id).get_content();
+    let node_caret_offset = ed_model
+        .grid_node_map
+        .get_offset_to_node_id(ed_model.get_caret(), curr_mark_node_id)?;
+
+    let type_over_len = match ch {
+        ']' if content == nodes::RIGHT_SQUARE_BR => Some(content.len() - node_caret_offset),
+        '}' if content == nodes::RIGHT_ACCOLADE => Some(content.len() - node_caret_offset),
+        '"' if node_caret_offset > 0 && node_caret_offset + 1 == content.len() => {
+            // the whole string, quotes included, is a single node
+            match ed_model.mark_id_ast_id_map.get(curr_mark_node_id)? {
+                ASTNodeId::AExprId(expr_id) => match ed_model.module.env.pool.get(expr_id) {
+                    Expr2::SmallStr(_) | Expr2::Str(_) => Some(1),
+                    _ => None,
+                },
+                ASTNodeId::ADefId(_) => None,
+            }
+        }
+        _ => None,
+    };
+
+    Ok(type_over_len)
+}
+
 // updates the ed_model based on the char the user just typed if the result would be syntactically correct.
 pub fn handle_new_char(received_char: &char, ed_model: &mut EdModel) -> EdResult<InputOutcome> {
     //dbg!("{}", ed_model.module.ast.ast_to_string(ed_model.module.env.pool));
 
-    let input_outcome = match received_char {
+    let selected_expr_id_opt = match &ed_model.selected_block_opt {
+        Some(SelectedBlock {
+            ast_node_id: ASTNodeId::AExprId(expr_id),
+            ..
+        }) => Some(*expr_id),
+        _ => None,
+    };
+
+    if let Some(caret_move_len) = type_over_len(received_char, ed_model)? {
+        ed_model.simple_move_carets_right(caret_move_len);
+        ed_model.dirty = true;
+
+        return Ok(InputOutcome::Accepted);
+    }
+
+    let input_outcome = match (*received_char, selected_expr_id_opt) {
+        // a list can hold any expression, so '[' puts the selected expression in a list
+        ('[', Some(selected_expr_id)) => wrap_in_list(selected_expr_id, ed_model)?,
+        _ => match received_char {
             '\u{e000}'..='\u{f8ff}' // http://www.unicode.org/faq/private_use.html
             | '\u{f0000}'..='\u{ffffd}' // ^
             | '\u{100000}'..='\u{10fffd}' // ^
@@ -1203,6 +1243,20 @@ pub fn handle_new_char(received_char: &char, ed_model: &mut EdModel) -> EdResult
                 InputOutcome::Accepted
             }
             ch => {
+                // Typing over a selected expression replaces it, just like backspace followed by the char.
+                // A char that the blank would not accept is ignored and keeps the selection,
+                // a newline keeps the selection and is handled as usual.
+                let replaces_selection = ed_model
+                    .selected_block_opt
+                    .as_ref()
+                    .map_or(false, |sel_block| blank_accepts_char(sel_block.ast_node_id, ch));
+
+                if replaces_selection {
+                    ed_model.backspace()?;
+                } else if ed_model.selected_block_opt.is_some() && *ch != '\r' {
+                    return Ok(InputOutcome::Ignored);
+                }
+
                 let outcome =
                     if ed_model.node_exists_at_caret() {
                         let curr_mark_node_id = ed_model.get_curr_mark_node_id()?;
@@ -1262,7 +1316,8 @@ pub fn handle_new_char(received_char: &char, ed_model: &mut EdModel) -> EdResult
 
                     outcome
             }
-        };
+        },
+    };
 
     if let InputOutcome::Accepted = input_outcome {
         ed_model.post_process_ast_update()?;
@@ -1531,6 +1586,9 @@ pub mod test_ed_update {
 
     const IGNORE_CHARS: &str = "{}()[]-><-_\"azAZ:@09";
     const IGNORE_CHARS_NO_NUM: &str = ",{}()[]-><-_\"azAZ:@";
+    // a closer right after the caret is typed over
+    const IGNORE_CHARS_NO_SQ_BR: &str = "{}()[-><-_\"azAZ:@09";
+    const IGNORE_CHARS_NO_ACC: &str = "{()[]-><-_\"azAZ:@09";
     const IGNORE_NO_LTR: &str = "{\"5";
     const IGNORE_NO_NUM: &str = "a{\"";
 
@@ -2055,7 +2113,7 @@ pub mod test_ed_update {
         assert_insert_seq_ignore_nls(ovec!["val = ┃{  }"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = {  }┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = {┃  }"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = {  ┃}"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = {  ┃}"], IGNORE_CHARS_NO_ACC)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = { ┃ }"], IGNORE_NO_LTR)?;
         assert_insert_seq_ignore_nls(ovec!["val = { ┃a }"], IGNORE_NO_LTR)?;
@@ -2093,7 +2151,7 @@ pub mod test_ed_update {
         assert_insert_seq_ignore_nls(ovec!["val = ┃{ a: \"\" }"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = {┃ a: \"\" }"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = { a: ┃\"\" }"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = { a: \"\"┃ }"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = { a: \"\"┃ }"], IGNORE_CHARS_NO_ACC)?;
         assert_insert_seq_ignore_nls(ovec!["val = { a: \"\" }┃"], IGNORE_CHARS)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃{ a: 1 }"], IGNORE_CHARS)?;
@@ -2111,13 +2169,13 @@ pub mod test_ed_update {
         assert_insert_seq_ignore_nls(ovec!["val = ┃{ camelCase: \"\" }"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = {┃ camelCase: \"\" }"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = { camelCase: ┃\"\" }"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = { camelCase: \"\"┃ }"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = { camelCase: \"\"┃ }"], IGNORE_CHARS_NO_ACC)?;
         assert_insert_seq_ignore_nls(ovec!["val = { camelCase: \"\" }┃"], IGNORE_CHARS)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃{ a: \"z\" }"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = {┃ a: \"z\" }"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = { a: ┃\"z\" }"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = { a: \"z\"┃ }"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = { a: \"z\"┃ }"], IGNORE_CHARS_NO_ACC)?;
         assert_insert_seq_ignore_nls(ovec!["val = { a: \"z\" }┃"], IGNORE_CHARS)?;
 
         assert_insert_seq_ignore_nls(
@@ -2391,45 +2449,45 @@ pub mod test_ed_update {
         assert_insert_seq_ignore_nls(ovec!["val = ┃[  ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [  ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃  ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [  ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [  ┃]"], IGNORE_CHARS_NO_SQ_BR)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃[ 0 ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ 0 ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃ 0 ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ 0 ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ 0 ┃]"], IGNORE_CHARS_NO_SQ_BR)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃[ 137 ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ 137 ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃ 137 ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ 137 ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ 137 ┃]"], IGNORE_CHARS_NO_SQ_BR)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ ┃137 ]"], IGNORE_NO_NUM)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ 137┃ ]"], IGNORE_NO_NUM)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃[ \"teststring\" ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ \"teststring\" ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃ \"teststring\" ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ \"teststring\" ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ \"teststring\" ┃]"], IGNORE_CHARS_NO_SQ_BR)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ ┃\"teststring\" ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ \"teststring\"┃ ]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ \"teststring\"┃ ]"], IGNORE_CHARS_NO_SQ_BR)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃[ { a: 1 } ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ { a: 1 } ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃ { a: 1 } ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ { a: 1 } ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ { a: 1 } ┃]"], IGNORE_CHARS_NO_SQ_BR)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ ┃{ a: 1 } ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ {┃ a: 1 } ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ { a:┃ 1 } ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ { a: 1 ┃} ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ { a: 1 }┃ ]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ { a: 1 ┃} ]"], IGNORE_CHARS_NO_ACC)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ { a: 1 }┃ ]"], IGNORE_CHARS_NO_SQ_BR)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃[ [  ] ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ [  ] ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃ [  ] ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ [  ] ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ [  ] ┃]"], IGNORE_CHARS_NO_SQ_BR)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ ┃[  ] ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ [  ]┃ ]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ [  ]┃ ]"], IGNORE_CHARS_NO_SQ_BR)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ [┃  ] ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ [  ┃] ]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ [  ┃] ]"], IGNORE_CHARS_NO_SQ_BR)?;
 
         Ok(())
     }
@@ -2504,42 +2562,79 @@ pub mod test_ed_update {
         assert_insert_seq_ignore_nls(ovec!["val = ┃[ 0, 1 ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ 0, 1 ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃ 0, 1 ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ 0, 1 ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ 0, 1 ┃]"], IGNORE_CHARS_NO_SQ_BR)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ 0,┃ 1 ]"], IGNORE_CHARS)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃[ 123, 56, 7 ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ 123, 56, 7 ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃ 123, 56, 7 ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ 123, 56, 7 ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ 123, 56, 7 ┃]"], IGNORE_CHARS_NO_SQ_BR)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ 123,┃ 56, 7 ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ 123, 56,┃ 7 ]"], IGNORE_CHARS)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃[ \"123\", \"56\", \"7\" ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ \"123\", \"56\", \"7\" ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃ \"123\", \"56\", \"7\" ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ \"123\", \"56\", \"7\" ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(
+            ovec!["val = [ \"123\", \"56\", \"7\" ┃]"],
+            IGNORE_CHARS_NO_SQ_BR,
+        )?;
         assert_insert_seq_ignore_nls(ovec!["val = [ \"123\",┃ \"56\", \"7\" ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ \"123\", \"56\",┃ \"7\" ]"], IGNORE_CHARS)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃[ { a: 0 }, { a: 1 } ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ { a: 0 }, { a: 1 } ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃ { a: 0 }, { a: 1 } ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ { a: 0 }, { a: 1 } ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(
+            ovec!["val = [ { a: 0 }, { a: 1 } ┃]"],
+            IGNORE_CHARS_NO_SQ_BR,
+        )?;
         assert_insert_seq_ignore_nls(ovec!["val = [ { a: 0 },┃ { a: 1 } ]"], IGNORE_CHARS)?;
 
         assert_insert_seq_ignore_nls(ovec!["val = ┃[ [ 0 ], [ 1 ] ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ], [ 1 ] ]┃"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [┃ [ 0 ], [ 1 ] ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ], [ 1 ] ┃]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ], [ 1 ] ┃]"], IGNORE_CHARS_NO_SQ_BR)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ],┃ [ 1 ] ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ ┃[ 0 ], [ 1 ] ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ]┃, [ 1 ] ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ [┃ 0 ], [ 1 ] ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ┃], [ 1 ] ]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ┃], [ 1 ] ]"], IGNORE_CHARS_NO_SQ_BR)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ], ┃[ 1 ] ]"], IGNORE_CHARS)?;
         assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ], [┃ 1 ] ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ], [ 1 ]┃ ]"], IGNORE_CHARS)?;
-        assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ], [ 1 ┃] ]"], IGNORE_CHARS)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ], [ 1 ]┃ ]"], IGNORE_CHARS_NO_SQ_BR)?;
+        assert_insert_seq_ignore_nls(ovec!["val = [ [ 0 ], [ 1 ┃] ]"], IGNORE_CHARS_NO_SQ_BR)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_over() -> Result<(), String> {
+        assert_insert_nls(ovec!["val = [ ┃ ]"], ovec!["val = [  ]┃"], ']')?;
+        assert_insert_nls(ovec!["val = [  ┃]"], ovec!["val = [  ]┃"], ']')?;
+        assert_insert_nls(ovec!["val = [ 0┃ ]"], ovec!["val = [ 0 ]┃"], ']')?;
+        assert_insert_nls(ovec!["val = [ 0, 1 ┃]"], ovec!["val = [ 0, 1 ]┃"], ']')?;
+        assert_insert_nls(
+            ovec!["val = [ [ 0 ┃], [ 1 ] ]"],
+            ovec!["val = [ [ 0 ]┃, [ 1 ] ]"],
+            ']',
+        )?;
+
+        assert_insert_nls(ovec!["val = { ┃ }"], ovec!["val = {  }┃"], '}')?;
+        assert_insert_nls(ovec!["val = { a: 1┃ }"], ovec!["val = { a: 1 }┃"], '}')?;
+        assert_insert_nls(
+            ovec!["val = [ { a: 1 ┃} ]"],
+            ovec!["val = [ { a: 1 }┃ ]"],
+            '}',
+        )?;
+
+        assert_insert_nls(ovec!["val = \"┃\""], ovec!["val = \"\"┃"], '"')?;
+        assert_insert_nls(ovec!["val = \"ab┃\""], ovec!["val = \"ab\"┃"], '"')?;
+
+        // typing the closers like in a text editor gives the same code
+        assert_insert_seq_in_def(add_nls(ovec!["[ 0 ]┃"]), "[0]")?;
+        assert_insert_seq_in_def(add_nls(ovec!["{ a: 1 }┃"]), "{a:1}")?;
+        assert_insert_seq_in_def(add_nls(ovec!["\"ab\"┃"]), "\"ab\"")?;
 
         Ok(())
     }
@@ -2584,6 +2679,13 @@ pub mod test_ed_update {
             "ab🡲🡲🡲5\rcd🡲🡲🡲\"good",
         )?;
 
+        // enter at the end of a line that continues on an indented line moves to that indentation
+        assert_insert_seq(
+            ovec!["f = \\x -> ┃", "    5"],
+            add_nls(ovec!["f = \\x -> ", "        ┃5"]),
+            "\r",
+        )?;
+
         Ok(())
     }
 
@@ -5,11 +5,14 @@ use roc_code_markup::markup::nodes::{self};
 use roc_code_markup::slow_pool::MarkNodeId;
 
 use crate::editor::ed_error::EdResult;
-use crate::editor::ed_error::{MissingParentSnafu, UnexpectedASTNodeSnafu};
+use crate::editor::ed_error::{MissingParentSnafu, MissingSelectionSnafu, UnexpectedASTNodeSnafu};
 use crate::editor::mvc::app_update::InputOutcome;
 use crate::editor::mvc::ed_model::EdModel;
 use crate::editor::mvc::ed_update::get_node_context;
 use crate::editor::mvc::ed_update::NodeContext;
+use crate::ui::text::lines::SelectableLines;
+use crate::ui::text::text_pos::TextPos;
+use snafu::OptionExt;
 
 pub fn start_new_list(ed_model: &mut EdModel) -> EdResult<InputOutcome> {
     let NodeContext {
@@ -121,3 +124,37 @@ pub fn add_blank_child(
 
     Ok(InputOutcome::Accepted)
 }
+
+// replace the selected expression by a list that has it as only element, the caret ends up after the element
+pub fn wrap_in_list(selected_expr_id: ExprId, ed_model: &mut EdModel) -> EdResult<InputOutcome> {
+    let selection = ed_model.get_selection().context(MissingSelectionSnafu {})?;
+
+    let elem_var = ed_model.module.env.var_store.fresh();
+    let pool = &mut *ed_model.module.env.pool;
+
+    // the element keeps its children, only the node that refers to it moves
+    let elem_expr = std::mem::replace(pool.get_mut(selected_expr_id), Expr2::Blank);
+    let elem_id = pool.add(elem_expr);
+
+    let list_node = Expr2::List {
+        elem_var,
+        elems: PoolVec::new(std::iter::once(elem_id), pool),
+    };
+
+    pool.set(selected_expr_id, list_node);
+
+    let end_pos = selection.end_pos;
+    let new_caret_pos = if selection.start_pos.line == end_pos.line {
+        TextPos {
+            line: end_pos.line,
+            column: end_pos.column + nodes::LEFT_SQUARE_BR.len(),
+        }
+    } else {
+        end_pos
+    };
+
+    ed_model.set_sel_none();
+    ed_model.set_caret(new_caret_pos);
+
+    Ok(InputOutcome::Accepted)
+}
@@ -13,6 +13,7 @@ use crate::ui::ui_error::{OutOfBoundsSnafu, UIResult};
 use crate::ui::util::is_newline;
 use crate::window::keyboard_input::{no_mods, Modifiers};
 use bumpalo::Bump;
+use roc_code_markup::markup::nodes::SINGLE_INDENT;
 use snafu::ensure;
 use std::{fmt, path::Path};
 use winit::event::{VirtualKeyCode, VirtualKeyCode::*};
@@ -23,6 +24,8 @@ pub struct BigTextArea {
     pub caret_w_select: CaretWSelect,
     text_buffer: TextBuffer,
     pub path_str: String,
+    // Tab inserts spaces up to the next multiple of this column
+    pub indent_width: usize,
     arena: Bump,
 }
 
@@ -39,6 +42,120 @@ impl BigTextArea {
 
         Ok(())
     }
+
+    // byte offset of pos in all_lines_as_string, to link carets to compiler Regions
+    pub fn pos_to_offset(&self, pos: TextPos) -> UIResult<usize> {
+        self.text_buffer.pos_to_offset(pos)
+    }
+
+    pub fn offset_to_pos(&self, offset: usize) -> UIResult<TextPos> {
+        self.text_buffer.offset_to_pos(offset)
+    }
+
+    // leading whitespace of the caret line, plus one indent level if the text before the caret opens a block
+    fn newline_indent(&self) -> UIResult<String> {
+        let caret_pos = self.caret_w_select.caret_pos;
+        let line_before_caret = &self.get_line_ref(caret_pos.line)?[..caret_pos.column];
+
+        let mut indent_str: String = line_before_caret
+            .chars()
+            .take_while(|ch| *ch == ' ' || *ch == '\t')
+            .collect();
+
+        if let Some(last_token) = line_before_caret.split_whitespace().last() {
+            if BLOCK_OPENERS.contains(&last_token) {
+                indent_str.push_str(SINGLE_INDENT);
+            }
+        }
+
+        Ok(indent_str)
+    }
+
+    // spaces up to the next tab stop, so the caret ends up at a multiple of indent_width
+    fn insert_tab(&mut self) -> UIResult<()> {
+        let caret_pos = self.caret_w_select.caret_pos;
+        let nr_of_spaces = self.indent_width - caret_pos.column % self.indent_width;
+
+        self.insert_str(&" ".repeat(nr_of_spaces))?;
+        self.set_caret(TextPos {
+            line: caret_pos.line,
+            column: caret_pos.column + nr_of_spaces,
+        });
+
+        Ok(())
+    }
+
+    fn char_after_caret(&self) -> UIResult<Option<char>> {
+        let caret_pos = self.caret_w_select.caret_pos;
+        let line_ref = self.get_line_ref(caret_pos.line)?;
+
+        Ok(line_ref
+            .get(caret_pos.column..)
+            .and_then(|rest| rest.chars().next()))
+    }
+
+    // inserts the opening and closing char, caret ends up in between
+    fn insert_pair(&mut self, opening_char: char, closing_char: char) -> UIResult<()> {
+        self.insert_str(&[opening_char, closing_char].iter().collect::<String>())?;
+        self.move_caret_right(&no_mods())?;
+
+        Ok(())
+    }
+
+    // puts the opening and closing char around the selection, selection stays on the wrapped text
+    fn wrap_selection(
+        &mut self,
+        selection: Selection,
+        opening_char: char,
+        closing_char: char,
+    ) -> UIResult<()> {
+        let start_pos = selection.start_pos;
+        let end_pos = selection.end_pos;
+
+        // closing char first so start_pos stays valid
+        self.text_buffer
+            .insert_str(end_pos, &closing_char.to_string())?;
+        self.text_buffer
+            .insert_str(start_pos, &opening_char.to_string())?;
+
+        let shift_pos = |pos: TextPos| {
+            if pos.line == start_pos.line {
+                TextPos {
+                    line: pos.line,
+                    column: pos.column + opening_char.len_utf8(),
+                }
+            } else {
+                pos
+            }
+        };
+
+        let new_caret_pos = shift_pos(self.caret_w_select.caret_pos);
+
+        self.set_raw_sel(RawSelection {
+            start_pos: shift_pos(start_pos),
+            end_pos: shift_pos(end_pos),
+        })?;
+        self.set_caret(new_caret_pos);
+
+        Ok(())
+    }
+}
+
+// a newline after one of these gets an extra indent level
+const BLOCK_OPENERS: [&str; 3] = ["=", "->", "is"];
+
+fn closing_char(opening_char: &char) -> Option<char> {
+    match opening_char {
+        '[' => Some(']'),
+        '(' => Some(')'),
+        '{' => Some('}'),
+        '"' => Some('"'),
+        _ => None,
+    }
+}
+
+fn is_closing_char(ch: &char) -> bool {
+    matches!(ch, ']' | ')' | '}' | '"')
 }
 
 impl Lines for BigTextArea {
@@ -189,6 +306,20 @@ impl SelectableLines for BigTextArea {
                     Ok(())
                 }
             }
+            K => {
+                if modifiers.cmd_or_ctrl() && modifiers.shift {
+                    self.delete_lines()
+                } else {
+                    Ok(())
+                }
+            }
+            T => {
+                if modifiers.cmd_or_ctrl() {
+                    self.transpose_chars()
+                } else {
+                    Ok(())
+                }
+            }
             Home => self.move_caret_home(modifiers),
             End => self.move_caret_end(modifiers),
             _ => Ok(()),
@@ -202,14 +333,18 @@ impl MutSelectableLines for BigTextArea {
             self.del_selection()?;
         }
 
-        self.insert_str(&new_char.to_string())?;
-
         if is_newline(new_char) {
+            let indent_str = self.newline_indent()?;
+
+            self.text_buffer
+                .insert_newline(self.caret_w_select.caret_pos, &indent_str)?;
+
             self.set_caret(TextPos {
                 line: self.caret_w_select.caret_pos.line + 1,
-                column: 0,
+                column: indent_str.len(),
             });
         } else {
+            self.insert_str(&new_char.to_string())?;
             self.move_caret_right(&no_mods())?;
         }
 
@@ -229,6 +364,8 @@ impl MutSelectableLines for BigTextArea {
 
             '\u{1}' // Ctrl + A
             | '\u{3}' // Ctrl + C
+            | '\u{b}' // Ctrl + K
+            | '\u{14}' // Ctrl + T
             | '\u{16}' // Ctrl + V
             | '\u{18}' // Ctrl + X
             | '\u{e000}'..='\u{f8ff}' // http://www.unicode.org/faq/private_use.html
@@ -238,6 +375,30 @@ impl MutSelectableLines for BigTextArea {
                 // chars that can be ignored
             }
 
+            // indenting a selection is not supported yet, Tab replaces it like any other char
+            '\t' if !self.is_selection_active() => {
+                self.insert_tab()?;
+            }
+
+            // type over the closing char if it's already next to the caret
+            closing if is_closing_char(closing)
+                && !self.is_selection_active()
+                && self.char_after_caret()? == Some(*closing) =>
+            {
+                self.move_caret_right(&no_mods())?;
+            }
+
+            opening if closing_char(opening).is_some() => {
+                // safe unwrap because of the match guard
+                let closing = closing_char(opening).unwrap();
+
+                if let Some(selection) = self.get_selection() {
+                    self.wrap_selection(selection, *opening, closing)?;
+                } else {
+                    self.insert_pair(*opening, closing)?;
+                }
+            }
+
             _ => {
                 self.insert_char(received_char)?;
             }
@@ -279,6 +440,116 @@ impl MutSelectableLines for BigTextArea {
 
         Ok(())
     }
+
+    fn delete_lines(&mut self) -> UIResult<()> {
+        let (first_line_nr, last_line_nr) = match self.get_selection() {
+            Some(selection) => (selection.start_pos.line, selection.end_pos.line),
+            None => (self.get_caret().line, self.get_caret().line),
+        };
+
+        self.text_buffer.del_lines(first_line_nr, last_line_nr)?;
+
+        // the caret goes to the start of the line that came after the deleted ones,
+        // or to the new last line if the deleted lines were at the end
+        self.set_caret(TextPos {
+            line: first_line_nr.min(self.nr_of_lines() - 1),
+            column: 0,
+        });
+        self.set_sel_none();
+
+        Ok(())
+    }
+
+    fn transpose_chars(&mut self) -> UIResult<()> {
+        let caret_pos = self.get_caret();
+        let line = self.get_line_ref(caret_pos.line)?;
+
+        // at the end of a line, the last two chars are swapped
+        let swap_end = if caret_pos.column < line.len() {
+            line[caret_pos.column..]
+                .chars()
+                .next()
+                .map(|ch| caret_pos.column + ch.len_utf8())
+        } else {
+            Some(caret_pos.column)
+        };
+
+        let swap_range_opt = swap_end.and_then(|end_col| {
+            let mut before_end = line[..end_col].chars().rev();
+            let second = before_end.next()?;
+            let first = before_end.next()?;
+            let start_col = end_col - second.len_utf8() - first.len_utf8();
+
+            Some((start_col, end_col, format!("{}{}", second, first)))
+        });
+
+        // a caret at the start of the line has no char before it, nothing to do there
+        if let (true, Some((start_col, end_col, swapped))) = (caret_pos.column > 0, swap_range_opt)
+        {
+            self.set_raw_sel(RawSelection {
+                start_pos: TextPos {
+                    line: caret_pos.line,
+                    column: start_col,
+                },
+                end_pos: TextPos {
+                    line: caret_pos.line,
+                    column: end_col,
+                },
+            })?;
+            self.del_selection()?;
+            self.insert_str(&swapped)?;
+
+            self.set_caret(TextPos {
+                line: caret_pos.line,
+                column: end_col,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn replace(&mut self, needle: &str, replacement: &str, all: bool) -> UIResult<()> {
+        let matches: Vec<(TextPos, TextPos)> = if all {
+            self.find_all(needle)
+        } else {
+            // start at the selection so a selected match gets replaced
+            let search_start = match self.get_selection() {
+                Some(selection) => selection.start_pos,
+                None => self.get_caret(),
+            };
+
+            self.find_next(search_start, needle, true)
+                .into_iter()
+                .collect()
+        };
+
+        // replace back to front so the positions of the remaining matches stay valid
+        for (start_pos, end_pos) in matches.into_iter().rev() {
+            self.set_raw_sel(RawSelection { start_pos, end_pos })?;
+            self.del_selection()?;
+
+            // a replacement that spans multiple lines is inserted line by line
+            let mut insert_pos = start_pos;
+
+            for (index, replacement_line) in replacement.split('\n').enumerate() {
+                if index > 0 {
+                    self.text_buffer.insert_newline(insert_pos, "")?;
+
+                    insert_pos = TextPos {
+                        line: insert_pos.line + 1,
+                        column: 0,
+                    };
+                }
+
+                self.text_buffer.insert_str(insert_pos, replacement_line)?;
+                insert_pos.column += replacement_line.len();
+            }
+
+            self.set_caret(insert_pos);
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for BigTextArea {
@@ -292,6 +563,7 @@ impl Default for BigTextArea {
             caret_w_select,
             text_buffer,
             path_str,
+            indent_width: SINGLE_INDENT.len(),
             arena,
         }
     }
@@ -331,6 +603,7 @@ impl fmt::Debug for BigTextArea {
             .field("caret_w_select", &self.caret_w_select)
             .field("text_buffer", &self.text_buffer)
             .field("path_str", &self.path_str)
+            .field("indent_width", &self.indent_width)
             .finish()
     }
 }
@@ -467,6 +740,19 @@ pub mod test_big_sel_text {
         Ok(())
     }
 
+    #[test]
+    fn backspace_joins_lines() -> Result<(), String> {
+        assert_insert(&["ab", "┃cd"], &["ab┃cd"], '\u{8}')?;
+        assert_insert(&["ab", "┃cd"], &["ab┃cd"], '\u{7f}')?;
+        assert_insert(&["", "┃cd"], &["┃cd"], '\u{8}')?;
+        assert_insert(&["ab", "", "┃cd"], &["ab", "┃cd"], '\u{8}')?;
+        assert_insert(&["ab", "┃cd", "ef"], &["ab┃cd", "ef"], '\u{8}')?;
+        // only a no-op at the very start of the buffer
+        assert_insert(&["┃ab", "cd"], &["┃ab", "cd"], '\u{8}')?;
+
+        Ok(())
+    }
+
     #[test]
     fn selection_backspace() -> Result<(), String> {
         assert_insert(&["❮a❯┃"], &["┃"], '\u{8}')?;
@@ -527,6 +813,99 @@ pub mod test_big_sel_text {
         Ok(())
     }
 
+    #[test]
+    fn auto_close() -> Result<(), String> {
+        assert_insert(&["┃"], &["[┃]"], '[')?;
+        assert_insert(&["┃"], &["(┃)"], '(')?;
+        assert_insert(&["┃"], &["{┃}"], '{')?;
+        assert_insert(&["┃"], &["\"┃\""], '"')?;
+        assert_insert(&["a┃"], &["a[┃]"], '[')?;
+        assert_insert(&["a┃b"], &["a(┃)b"], '(')?;
+        assert_insert(&["abc", "┃"], &["abc", "{┃}"], '{')?;
+        assert_insert(&["[┃]"], &["[[┃]]"], '[')?;
+        assert_insert(&["{┃}"], &["{\"┃\"}"], '"')?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn type_over() -> Result<(), String> {
+        assert_insert(&["[┃]"], &["[]┃"], ']')?;
+        assert_insert(&["(┃)"], &["()┃"], ')')?;
+        assert_insert(&["{┃}"], &["{}┃"], '}')?;
+        assert_insert(&["\"┃\""], &["\"\"┃"], '"')?;
+        assert_insert(&["[a┃]b"], &["[a]┃b"], ']')?;
+        assert_insert(&["[[┃]]"], &["[[]┃]"], ']')?;
+        // no type over if the next char is a different closer
+        assert_insert(&["(┃]"], &["()┃]"], ')')?;
+        assert_insert(&["a┃"], &["a]┃"], ']')?;
+        assert_insert(&["┃"], &["}┃"], '}')?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn wrap_selection() -> Result<(), String> {
+        assert_insert(&["❮a❯┃"], &["[❮a❯┃]"], '[')?;
+        assert_insert(&["┃❮a❯"], &["[┃❮a❯]"], '[')?;
+        assert_insert(&["x❮abc❯┃y"], &["x(❮abc❯┃)y"], '(')?;
+        assert_insert(&["❮abc❯┃", ""], &["{❮abc❯┃}", ""], '{')?;
+        assert_insert(&["a", "❮b c❯┃"], &["a", "\"❮b c❯┃\""], '"')?;
+        assert_insert(&["a❮b", "cd❯┃e"], &["a[❮b", "cd❯┃]e"], '[')?;
+        assert_insert(&["a┃❮b", "cd❯e"], &["a[┃❮b", "cd❯]e"], '[')?;
+        // closers replace the selection like any other char
+        assert_insert(&["❮ab❯┃"], &["]┃"], ']')?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn insert_newline() -> Result<(), String> {
+        assert_insert(&["┃"], &["", "┃"], '\n')?;
+        assert_insert(&["a┃"], &["a", "┃"], '\n')?;
+        assert_insert(&["a┃b"], &["a", "┃b"], '\n')?;
+        assert_insert(&["┃a"], &["", "┃a"], '\n')?;
+        assert_insert(&["a┃", "b"], &["a", "┃", "b"], '\n')?;
+        assert_insert(&["a", "b┃"], &["a", "b", "┃"], '\n')?;
+        assert_insert(&["❮ab❯┃c"], &["", "┃c"], '\n')?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn tab_inserts_spaces() -> Result<(), String> {
+        assert_insert(&["┃"], &["    ┃"], '\t')?;
+        assert_insert(&["ab┃"], &["ab  ┃"], '\t')?;
+        assert_insert(&["abcd┃"], &["abcd    ┃"], '\t')?;
+        assert_insert(&["a┃b"], &["a   ┃b"], '\t')?;
+        assert_insert(&["a", "     ┃"], &["a", "        ┃"], '\t')?;
+
+        let mut big_text = gen_big_text(&["abc┃"])?;
+        big_text.indent_width = 2;
+        big_text.handle_new_char(&'\t').unwrap();
+        assert_eq!(all_lines_vec(&big_text), vec!["abc ".to_owned()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn auto_indent() -> Result<(), String> {
+        assert_insert(&["    foo┃"], &["    foo", "    ┃"], '\n')?;
+        assert_insert(&["  foo┃"], &["  foo", "  ┃"], '\n')?;
+        assert_insert(&["    foo┃ bar"], &["    foo", "    ┃ bar"], '\n')?;
+        assert_insert(&["x =┃"], &["x =", "    ┃"], '\n')?;
+        assert_insert(&["x = ┃"], &["x = ", "    ┃"], '\n')?;
+        assert_insert(&["    x =┃"], &["    x =", "        ┃"], '\n')?;
+        assert_insert(&["\\x ->┃"], &["\\x ->", "    ┃"], '\n')?;
+        assert_insert(&["when x is┃"], &["when x is", "    ┃"], '\n')?;
+        assert_insert(&["    when x is┃"], &["    when x is", "        ┃"], '\n')?;
+        // only whole tokens open a block
+        assert_insert(&["this┃"], &["this", "┃"], '\n')?;
+        assert_insert(&["x ==┃"], &["x ==", "┃"], '\n')?;
+
+        Ok(())
+    }
+
     fn assert_select_all(
         pre_lines_str: &[&str],
         expected_post_lines_str: &[&str],
@@ -3408,4 +3787,270 @@ pub mod test_big_sel_text {
 
         Ok(())
     }
+
+    fn txt_pos(line: usize, column: usize) -> TextPos {
+        TextPos { line, column }
+    }
+
+    #[test]
+    fn find_all() -> Result<(), String> {
+        let big_text = gen_big_text(&["┃abc ab", "cab ab"])?;
+
+        assert_eq!(
+            big_text.find_all("ab"),
+            vec![
+                (txt_pos(0, 0), txt_pos(0, 2)),
+                (txt_pos(0, 4), txt_pos(0, 6)),
+                (txt_pos(1, 1), txt_pos(1, 3)),
+                (txt_pos(1, 4), txt_pos(1, 6)),
+            ]
+        );
+        assert_eq!(
+            big_text.find_all("ab\nc"),
+            vec![(txt_pos(0, 4), txt_pos(1, 1))]
+        );
+        assert!(big_text.find_all("xyz").is_empty());
+        assert!(big_text.find_all("").is_empty());
+
+        // matches don't overlap
+        let big_text = gen_big_text(&["┃aaaa"])?;
+
+        assert_eq!(
+            big_text.find_all("aa"),
+            vec![
+                (txt_pos(0, 0), txt_pos(0, 2)),
+                (txt_pos(0, 2), txt_pos(0, 4))
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn find_next() -> Result<(), String> {
+        let big_text = gen_big_text(&["┃abc ab", "cab ab"])?;
+
+        assert_eq!(
+            big_text.find_next(txt_pos(0, 0), "ab", false),
+            Some((txt_pos(0, 0), txt_pos(0, 2)))
+        );
+        assert_eq!(
+            big_text.find_next(txt_pos(0, 1), "ab", false),
+            Some((txt_pos(0, 4), txt_pos(0, 6)))
+        );
+        assert_eq!(
+            big_text.find_next(txt_pos(0, 5), "ab", false),
+            Some((txt_pos(1, 1), txt_pos(1, 3)))
+        );
+        assert_eq!(
+            big_text.find_next(txt_pos(0, 1), "ab\nc", false),
+            Some((txt_pos(0, 4), txt_pos(1, 1)))
+        );
+        assert_eq!(big_text.find_next(txt_pos(1, 5), "ab", false), None);
+        assert_eq!(
+            big_text.find_next(txt_pos(1, 5), "ab", true),
+            Some((txt_pos(0, 0), txt_pos(0, 2)))
+        );
+        assert_eq!(big_text.find_next(txt_pos(0, 0), "xyz", true), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn match_bracket() -> Result<(), String> {
+        let big_text = gen_big_text(&["┃[ a [ b ] c ]"])?;
+
+        // outer pair, in both directions
+        assert_eq!(big_text.match_bracket(txt_pos(0, 0)), Some(txt_pos(0, 12)));
+        assert_eq!(big_text.match_bracket(txt_pos(0, 12)), Some(txt_pos(0, 0)));
+        // inner pair
+        assert_eq!(big_text.match_bracket(txt_pos(0, 4)), Some(txt_pos(0, 8)));
+        assert_eq!(big_text.match_bracket(txt_pos(0, 8)), Some(txt_pos(0, 4)));
+        // not a bracket
+        assert_eq!(big_text.match_bracket(txt_pos(0, 2)), None);
+
+        // unbalanced
+        let big_text = gen_big_text(&["┃[ a ( b ]"])?;
+        assert_eq!(big_text.match_bracket(txt_pos(0, 0)), None);
+        assert_eq!(big_text.match_bracket(txt_pos(0, 4)), None);
+
+        // brackets in strings and comments don't count
+        let big_text = gen_big_text(&["┃{ \"}\" # }", "}"])?;
+        assert_eq!(big_text.match_bracket(txt_pos(0, 0)), Some(txt_pos(1, 0)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn wrap_line() -> Result<(), String> {
+        let big_text = from_str_vec(vec![
+            "short".to_string(),
+            "hello world".to_string(),
+            "abcdefghij".to_string(),
+            "".to_string(),
+            "日本語".to_string(),
+        ]);
+
+        // fits on one row
+        assert_eq!(big_text.wrap_line(0, 10)?, vec![(0, 5)]);
+        // broken after the space
+        assert_eq!(big_text.wrap_line(1, 8)?, vec![(0, 6), (6, 11)]);
+        // a token wider than the row is hard-wrapped
+        assert_eq!(big_text.wrap_line(2, 4)?, vec![(0, 4), (4, 8), (8, 10)]);
+        // an empty line still takes up a row
+        assert_eq!(big_text.wrap_line(3, 4)?, vec![(0, 0)]);
+        // each of these takes 2 columns and 3 bytes
+        assert_eq!(big_text.wrap_line(4, 4)?, vec![(0, 6), (6, 9)]);
+
+        assert!(big_text.wrap_line(5, 4).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn pos_offset_round_trip() -> Result<(), String> {
+        // 'é' takes 2 bytes and '€' takes 3
+        let big_text = from_str_vec(vec!["aéb".to_string(), "".to_string(), "€x".to_string()]);
+        let all_text = big_text.all_lines_as_string();
+
+        let positions = [
+            (txt_pos(0, 0), 0),
+            (txt_pos(0, 3), 3),
+            (txt_pos(0, 4), 4),
+            (txt_pos(1, 0), 5),
+            (txt_pos(2, 0), 6),
+            (txt_pos(2, 3), 9),
+            (txt_pos(2, 4), 10),
+        ];
+
+        for (pos, offset) in positions {
+            assert_eq!(big_text.pos_to_offset(pos)?, offset);
+            assert_eq!(big_text.offset_to_pos(offset)?, pos);
+            assert!(all_text.is_char_boundary(offset));
+        }
+
+        // inside a multi-byte char
+        assert!(big_text.pos_to_offset(txt_pos(0, 2)).is_err());
+        assert!(big_text.offset_to_pos(7).is_err());
+        // past the end
+        assert!(big_text.offset_to_pos(11).is_err());
+        assert!(big_text.pos_to_offset(txt_pos(3, 0)).is_err());
+
+        Ok(())
+    }
+
+    fn assert_delete_lines(
+        pre_lines_str: &[&str],
+        expected_post_lines_str: &[&str],
+    ) -> Result<(), String> {
+        let mut big_text = gen_big_text(pre_lines_str)?;
+
+        big_text.delete_lines()?;
+
+        let actual_lines = all_lines_vec(&big_text);
+        let dsl_slice = convert_selection_to_dsl(big_text.caret_w_select, actual_lines)?;
+        assert_eq!(dsl_slice, expected_post_lines_str);
+
+        Ok(())
+    }
+
+    #[test]
+    fn delete_lines() -> Result<(), String> {
+        // middle line
+        assert_delete_lines(&["abc", "d┃ef", "ghi"], &["abc", "┃ghi"])?;
+        // last line
+        assert_delete_lines(&["abc", "def", "gh┃i"], &["abc", "┃def"])?;
+        // every line touched by the selection
+        assert_delete_lines(&["abc", "d❮ef", "g❯┃hi"], &["┃abc"])?;
+        // only line
+        assert_delete_lines(&["ab┃c"], &["┃"])?;
+
+        Ok(())
+    }
+
+    fn assert_transpose_chars(
+        pre_lines_str: &[&str],
+        expected_post_lines_str: &[&str],
+    ) -> Result<(), String> {
+        let mut big_text = gen_big_text(pre_lines_str)?;
+
+        big_text.transpose_chars()?;
+
+        let actual_lines = all_lines_vec(&big_text);
+        let dsl_slice = convert_selection_to_dsl(big_text.caret_w_select, actual_lines)?;
+        assert_eq!(dsl_slice, expected_post_lines_str);
+
+        Ok(())
+    }
+
+    #[test]
+    fn transpose_chars() -> Result<(), String> {
+        assert_transpose_chars(&["ab┃c"], &["acb┃"])?;
+        assert_transpose_chars(&["a┃bcd"], &["ba┃cd"])?;
+        // at the end of the line, the last two chars are swapped
+        assert_transpose_chars(&["abc┃"], &["acb┃"])?;
+        assert_transpose_chars(&["abc", "d┃e"], &["abc", "ed┃"])?;
+        // no-ops
+        assert_transpose_chars(&["┃ab"], &["┃ab"])?;
+        assert_transpose_chars(&["a┃"], &["a┃"])?;
+        assert_transpose_chars(&["abc", "┃de"], &["abc", "┃de"])?;
+
+        Ok(())
+    }
+
+    fn assert_replace(
+        pre_lines_str: &[&str],
+        expected_post_lines_str: &[&str],
+        needle: &str,
+        replacement: &str,
+        all: bool,
+    ) -> Result<(), String> {
+        let mut big_text = gen_big_text(pre_lines_str)?;
+
+        big_text.replace(needle, replacement, all)?;
+
+        let actual_lines = all_lines_vec(&big_text);
+        let dsl_slice = convert_selection_to_dsl(big_text.caret_w_select, actual_lines)?;
+        assert_eq!(dsl_slice, expected_post_lines_str);
+
+        Ok(())
+    }
+
+    #[test]
+    fn replace_one() -> Result<(), String> {
+        assert_replace(&["┃aa aa aa"], &["b┃ aa aa"], "aa", "b", false)?;
+        assert_replace(&["aa ┃aa aa"], &["aa b┃ aa"], "aa", "b", false)?;
+        assert_replace(&["aa ❮aa❯┃ aa"], &["aa b┃ aa"], "aa", "b", false)?;
+        assert_replace(&["aa aa ┃aa"], &["aa aa ccc┃"], "aa", "ccc", false)?;
+        assert_replace(&["aa aa aa┃"], &["b┃ aa aa"], "aa", "b", false)?;
+        assert_replace(&["┃abc"], &["┃abc"], "x", "y", false)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn replace_all() -> Result<(), String> {
+        assert_replace(&["┃aa aa aa"], &["b┃ b b"], "aa", "b", true)?;
+        assert_replace(&["aa aa aa┃"], &["ccc┃ ccc ccc"], "aa", "ccc", true)?;
+        assert_replace(&["a┃b", "ab"], &["xyz┃", "xyz"], "ab", "xyz", true)?;
+        assert_replace(&["┃abc"], &["┃abc"], "x", "y", true)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn replace_multi_line() -> Result<(), String> {
+        assert_replace(&["┃a b c"], &["a x", "y┃ c"], "b", "x\ny", false)?;
+        assert_replace(&["┃ab ab"], &["x", "y┃ x", "y"], "ab", "x\ny", true)?;
+        assert_replace(&["┃a b", "c d"], &["a x┃ d"], "b\nc", "x", false)?;
+        assert_replace(
+            &["┃a b", "c d"],
+            &["a x", "", "y┃ d"],
+            "b\nc",
+            "x\n\ny",
+            false,
+        )?;
+
+        Ok(())
+    }
 }
@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+use super::lines::Lines;
 use super::selection::validate_selection;
 use super::selection::Selection;
 use super::text_pos::TextPos;
@@ -18,6 +19,8 @@ pub enum CaretPos {
     End,
 }
 
+// An empty selection is no selection, so equal positions give None instead of an error.
+// Relies on TextPos equality agreeing with its ordering.
 fn mk_some_sel(start_pos: TextPos, end_pos: TextPos) -> UIResult<Option<Selection>> {
     if start_pos == end_pos {
         Ok(None)
@@ -43,6 +46,34 @@ impl CaretWSelect {
         }
     }
 
+    /// The selection as `(line, start_column, end_column)` spans, one per line, for drawing its highlight.
+    /// Lines in the middle of the selection are covered up to their end.
+    pub fn selection_line_spans(&self, text: &impl Lines) -> UIResult<Vec<(usize, usize, usize)>> {
+        let mut spans = Vec::new();
+
+        if let Some(selection) = self.selection_opt {
+            let start_pos = selection.start_pos;
+            let end_pos = selection.end_pos;
+
+            for line_nr in start_pos.line..=end_pos.line {
+                let start_col = if line_nr == start_pos.line {
+                    start_pos.column
+                } else {
+                    0
+                };
+                let end_col = if line_nr == end_pos.line {
+                    end_pos.column
+                } else {
+                    text.line_len(line_nr)?
+                };
+
+                spans.push((line_nr, start_col, end_col));
+            }
+        }
+
+        Ok(spans)
+    }
+
     pub fn move_caret_w_mods(&self, new_pos: TextPos, mods: &Modifiers) -> UIResult<CaretWSelect> {
         let old_caret_pos = self.caret_pos;
 
@@ -83,6 +114,25 @@ impl CaretWSelect {
 
         Ok(CaretWSelect::new(new_pos, valid_sel_opt))
     }
+
+    /// Move the caret to `pos` and select everything between it and the anchor, like a shift+click.
+    /// The anchor is the end of the selection that the caret is not at, or the caret if nothing is selected.
+    pub fn extend_selection_to(&mut self, pos: TextPos) -> UIResult<()> {
+        let anchor = match self.selection_opt {
+            Some(sel) if self.caret_pos == sel.start_pos => sel.end_pos,
+            Some(sel) => sel.start_pos,
+            None => self.caret_pos,
+        };
+
+        self.selection_opt = if pos < anchor {
+            mk_some_sel(pos, anchor)?
+        } else {
+            mk_some_sel(anchor, pos)?
+        };
+        self.caret_pos = pos;
+
+        Ok(())
+    }
 }
 
 // VIEW
@@ -135,6 +185,7 @@ pub fn make_selection_rect(
 
 #[cfg(test)]
 pub mod test_caret_w_select {
+    use crate::ui::text::big_text_area::from_str_vec;
     use crate::ui::text::caret_w_select::CaretWSelect;
     use crate::ui::text::selection::validate_selection;
     use crate::ui::text::text_pos::TextPos;
@@ -322,4 +373,128 @@ pub mod test_caret_w_select {
 
         Ok(elt_ref)
     }
+
+    #[test]
+    fn selection_line_spans_single_line() -> Result<(), String> {
+        let big_text = from_str_vec(vec!["abcdef".to_owned()]);
+        let caret_w_select = CaretWSelect::new(
+            TextPos { line: 0, column: 4 },
+            Some(validate_selection(
+                TextPos { line: 0, column: 1 },
+                TextPos { line: 0, column: 4 },
+            )?),
+        );
+
+        assert_eq!(
+            caret_w_select.selection_line_spans(&big_text)?,
+            vec![(0, 1, 4)]
+        );
+        assert!(CaretWSelect::default()
+            .selection_line_spans(&big_text)?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn selection_line_spans_three_lines() -> Result<(), String> {
+        let big_text = from_str_vec(vec![
+            "first line".to_owned(),
+            "mid".to_owned(),
+            "last line".to_owned(),
+        ]);
+        let caret_w_select = CaretWSelect::new(
+            TextPos { line: 2, column: 4 },
+            Some(validate_selection(
+                TextPos { line: 0, column: 6 },
+                TextPos { line: 2, column: 4 },
+            )?),
+        );
+
+        assert_eq!(
+            caret_w_select.selection_line_spans(&big_text)?,
+            vec![(0, 6, 10), (1, 0, 3), (2, 0, 4)]
+        );
+
+        Ok(())
+    }
+
+    fn line_pos(column: usize) -> TextPos {
+        TextPos { line: 0, column }
+    }
+
+    // caret at caret_col, with start_col..end_col selected
+    fn sel_on_line(caret_col: usize, start_col: usize, end_col: usize) -> UIResult<CaretWSelect> {
+        Ok(CaretWSelect::new(
+            line_pos(caret_col),
+            Some(validate_selection(line_pos(start_col), line_pos(end_col))?),
+        ))
+    }
+
+    fn assert_selected(caret_w_select: &CaretWSelect, start_col: usize, end_col: usize) {
+        let selection = caret_w_select.selection_opt.expect("Expected a selection");
+
+        assert_eq!(selection.start_pos, line_pos(start_col));
+        assert_eq!(selection.end_pos, line_pos(end_col));
+    }
+
+    #[test]
+    fn extend_selection_after() -> Result<(), String> {
+        // selected left to right, so the anchor is the start
+        let mut caret_w_select = sel_on_line(5, 2, 5)?;
+        caret_w_select.extend_selection_to(line_pos(8))?;
+
+        assert_eq!(caret_w_select.caret_pos, line_pos(8));
+        assert_selected(&caret_w_select, 2, 8);
+
+        // selected right to left, so the anchor is the end
+        let mut caret_w_select = sel_on_line(2, 2, 5)?;
+        caret_w_select.extend_selection_to(line_pos(8))?;
+
+        assert_selected(&caret_w_select, 5, 8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn extend_selection_before() -> Result<(), String> {
+        let mut caret_w_select = sel_on_line(2, 2, 5)?;
+        caret_w_select.extend_selection_to(line_pos(0))?;
+
+        assert_eq!(caret_w_select.caret_pos, line_pos(0));
+        assert_selected(&caret_w_select, 0, 5);
+
+        // clicking before the anchor flips the selection around it
+        let mut caret_w_select = sel_on_line(5, 2, 5)?;
+        caret_w_select.extend_selection_to(line_pos(0))?;
+
+        assert_selected(&caret_w_select, 0, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn extend_selection_without_selection() -> Result<(), String> {
+        let mut caret_w_select = CaretWSelect::new(line_pos(3), None);
+        caret_w_select.extend_selection_to(line_pos(1))?;
+
+        assert_eq!(caret_w_select.caret_pos, line_pos(1));
+        assert_selected(&caret_w_select, 1, 3);
+
+        // clicking on the anchor selects nothing
+        caret_w_select.extend_selection_to(line_pos(3))?;
+        assert!(caret_w_select.selection_opt.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn mk_some_sel_equal_positions() {
+        let pos = TextPos { line: 1, column: 3 };
+
+        assert!(super::mk_some_sel(pos, pos).unwrap().is_none());
+
+        let end_pos = TextPos { line: 2, column: 0 };
+        assert!(super::mk_some_sel(pos, end_pos).unwrap().is_some());
+    }
 }
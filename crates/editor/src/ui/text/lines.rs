@@ -15,6 +15,7 @@ use crate::ui::util::is_newline;
 use crate::window::keyboard_input::Modifiers;
 use std::cmp::max;
 use std::cmp::min;
+use std::iter;
 use winit::event::VirtualKeyCode;
 
 pub trait Lines {
@@ -31,6 +32,24 @@ pub trait Lines {
     fn is_last_line(&self, line_nr: usize) -> bool;
 
     fn last_char(&self, line_nr: usize) -> UIResult<Option<char>>;
+
+    // the methods below only need the ones above, so they are shared by every implementation
+
+    fn find_all(&self, needle: &str) -> Vec<(TextPos, TextPos)> {
+        find_all(self, needle)
+    }
+
+    fn find_next(&self, from: TextPos, needle: &str, wrap: bool) -> Option<(TextPos, TextPos)> {
+        find_next(self, from, needle, wrap)
+    }
+
+    fn match_bracket(&self, from: TextPos) -> Option<TextPos> {
+        match_bracket(self, from)
+    }
+
+    fn wrap_line(&self, line_nr: usize, width_cols: usize) -> UIResult<Vec<(usize, usize)>> {
+        wrap_line(self, line_nr, width_cols)
+    }
 }
 
 pub trait SelectableLines {
@@ -84,6 +103,15 @@ pub trait MutSelectableLines {
     fn backspace(&mut self) -> UIResult<()>;
 
     fn del_selection(&mut self) -> UIResult<()>;
+
+    // deletes every line that the selection touches, or the caret's line
+    fn delete_lines(&mut self) -> UIResult<()>;
+
+    // swaps the chars around the caret and moves the caret after them, emacs style
+    fn transpose_chars(&mut self) -> UIResult<()>;
+
+    // replaces the next match of needle (or every match if all is true), the caret is placed after the replacement
+    fn replace(&mut self, needle: &str, replacement: &str, all: bool) -> UIResult<()>;
 }
 
 // T: Lines
@@ -443,3 +471,203 @@ pub fn move_caret_end<T: Lines>(
 
     caret_w_select.move_caret_w_mods(new_pos, modifiers)
 }
+
+// byte offset at which every line starts in all_lines_as_string()
+pub fn line_start_offsets(text: &str) -> Vec<usize> {
+    iter::once(0)
+        .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+        .collect()
+}
+
+pub fn offset_to_text_pos(line_starts: &[usize], offset: usize) -> TextPos {
+    let line = match line_starts.binary_search(&offset) {
+        Ok(line_nr) => line_nr,
+        Err(line_nr) => line_nr - 1, // safe because line_starts[0] == 0
+    };
+
+    TextPos {
+        line,
+        column: offset - line_starts[line],
+    }
+}
+
+fn match_to_text_pos_range(
+    line_starts: &[usize],
+    match_start: usize,
+    needle: &str,
+) -> (TextPos, TextPos) {
+    (
+        offset_to_text_pos(line_starts, match_start),
+        offset_to_text_pos(line_starts, match_start + needle.len()),
+    )
+}
+
+// Returns the (start, end) positions of all matches of needle, needle may contain newlines.
+// Matches do not overlap, e.g. searching "aa" in "aaaa" yields 2 matches, not 3.
+pub fn find_all<T: Lines + ?Sized>(lines: &T, needle: &str) -> Vec<(TextPos, TextPos)> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let text = lines.all_lines_as_string();
+    let line_starts = line_start_offsets(&text);
+
+    text.match_indices(needle)
+        .map(|(match_start, _)| match_to_text_pos_range(&line_starts, match_start, needle))
+        .collect()
+}
+
+// Returns the first match of needle that starts at or after `from`.
+// If there is no such match and wrap is true, the search continues from the start of the text.
+pub fn find_next<T: Lines + ?Sized>(
+    lines: &T,
+    from: TextPos,
+    needle: &str,
+    wrap: bool,
+) -> Option<(TextPos, TextPos)> {
+    if needle.is_empty() {
+        return None;
+    }
+
+    let text = lines.all_lines_as_string();
+    let line_starts = line_start_offsets(&text);
+
+    let from_offset = line_starts
+        .get(from.line)
+        .map(|line_start| min(line_start + from.column, text.len()))
+        .unwrap_or(text.len());
+
+    let after_from_opt = text
+        .get(from_offset..)
+        .and_then(|rest| rest.find(needle))
+        .map(|rest_index| from_offset + rest_index);
+
+    let match_start_opt = if after_from_opt.is_none() && wrap {
+        text.find(needle)
+    } else {
+        after_from_opt
+    };
+
+    match_start_opt.map(|match_start| match_to_text_pos_range(&line_starts, match_start, needle))
+}
+
+// Splits a line into the rows it takes up on screen when it is wrapped at `width_cols` display columns.
+// Returns the start and (exclusive) end column of every row, as byte offsets like TextPos.column.
+// Rows are broken after a space where possible, spaces at a break stay at the end of the row.
+// A word that is wider than a row is broken wherever it reaches the width.
+pub fn wrap_line<T: Lines + ?Sized>(
+    lines: &T,
+    line_nr: usize,
+    width_cols: usize,
+) -> UIResult<Vec<(usize, usize)>> {
+    let line = lines.get_line_ref(line_nr)?;
+    let width_cols = max(width_cols, 1);
+
+    let mut rows = Vec::new();
+    let mut row_start = 0;
+    let mut row_width = 0;
+    // right after the last space of the current row
+    let mut break_opt: Option<usize> = None;
+
+    for (column, ch) in line.char_indices() {
+        let ch_width = char_display_width(ch);
+
+        if ch == ' ' {
+            row_width += ch_width;
+            break_opt = Some(column + 1);
+            continue;
+        }
+
+        if row_width + ch_width > width_cols && column > row_start {
+            let row_end = break_opt.unwrap_or(column);
+
+            rows.push((row_start, row_end));
+
+            row_start = row_end;
+            row_width = line[row_start..column]
+                .chars()
+                .map(char_display_width)
+                .sum();
+            break_opt = None;
+        }
+
+        row_width += ch_width;
+    }
+
+    rows.push((row_start, line.len()));
+
+    Ok(rows)
+}
+
+// Wide East Asian characters and most emoji take up two columns in a monospace font
+fn char_display_width(ch: char) -> usize {
+    match ch as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+// Returns the position of the bracket that matches the one at `from`, in either direction.
+// Brackets inside strings and comments are skipped. Returns None if there is no bracket at `from`,
+// or if it is not balanced.
+pub fn match_bracket<T: Lines + ?Sized>(lines: &T, from: TextPos) -> Option<TextPos> {
+    let mut open_brackets: Vec<(char, TextPos)> = Vec::new();
+
+    for line_nr in 0..lines.nr_of_lines() {
+        let line = lines.get_line_ref(line_nr).ok()?;
+        let mut in_string = false;
+
+        for (column, ch) in line.char_indices() {
+            let pos = TextPos {
+                line: line_nr,
+                column,
+            };
+
+            if in_string {
+                in_string = ch != '"';
+                continue;
+            }
+
+            match ch {
+                '"' => in_string = true,
+                '#' => break,
+                '(' | '[' | '{' => open_brackets.push((ch, pos)),
+                ')' | ']' | '}' => {
+                    let (open_ch, open_pos) = match open_brackets.pop() {
+                        Some(open_bracket) => open_bracket,
+                        None if pos == from => return None,
+                        None => continue,
+                    };
+
+                    if open_pos == from || pos == from {
+                        let is_pair = matches!((open_ch, ch), ('(', ')') | ('[', ']') | ('{', '}'));
+
+                        return if !is_pair {
+                            None
+                        } else if open_pos == from {
+                            Some(pos)
+                        } else {
+                            Some(open_pos)
+                        };
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    None
+}
@@ -6,6 +6,7 @@ use crate::ui::{
 };
 use snafu::ensure;
 
+use super::lines::{line_start_offsets, offset_to_text_pos};
 use super::{selection::Selection, text_pos::TextPos};
 use std::io::BufRead;
 
@@ -99,6 +100,51 @@ impl TextBuffer {
         Ok(())
     }
 
+    // Byte offset of txt_pos in the text with lines joined by '\n'.
+    // Like everywhere in the text model, the column is a byte index in its line.
+    pub fn pos_to_offset(&self, txt_pos: TextPos) -> UIResult<usize> {
+        self.ensure_bounds_txt_pos(txt_pos)?;
+        self.ensure_char_boundary(txt_pos)?;
+
+        let line_starts = line_start_offsets(&self.lines.join("\n"));
+
+        Ok(line_starts[txt_pos.line] + txt_pos.column)
+    }
+
+    // inverse of pos_to_offset, an offset right after a line's last char is the end of that line
+    pub fn offset_to_pos(&self, offset: usize) -> UIResult<TextPos> {
+        let text = self.lines.join("\n");
+
+        ensure!(
+            offset <= text.len(),
+            OutOfBoundsSnafu {
+                index: offset,
+                collection_name: "TextBuffer",
+                len: text.len(),
+            }
+        );
+
+        let txt_pos = offset_to_text_pos(&line_start_offsets(&text), offset);
+        self.ensure_char_boundary(txt_pos)?;
+
+        Ok(txt_pos)
+    }
+
+    fn ensure_char_boundary(&self, txt_pos: TextPos) -> UIResult<()> {
+        let line_ref = self.get_line_ref(txt_pos.line)?;
+
+        ensure!(
+            line_ref.is_char_boundary(txt_pos.column),
+            OutOfBoundsSnafu {
+                index: txt_pos.column,
+                collection_name: format!("char boundaries of line in TextBuffer: {}", line_ref),
+                len: line_ref.len(),
+            }
+        );
+
+        Ok(())
+    }
+
     pub fn all_lines_ref(&self) -> &[String] {
         &self.lines
     }
@@ -140,6 +186,19 @@ impl TextBuffer {
         Ok(())
     }
 
+    // splits the line at txt_pos, the new line starts with indent_str
+    pub fn insert_newline(&mut self, txt_pos: TextPos, indent_str: &str) -> UIResult<()> {
+        self.ensure_bounds_txt_pos(txt_pos)?;
+
+        let line_ref = self.lines.get_mut(txt_pos.line).unwrap(); // safe because of earlier bounds check
+        let rest_of_line = line_ref.split_off(txt_pos.column);
+
+        self.lines
+            .insert(txt_pos.line + 1, format!("{}{}", indent_str, rest_of_line));
+
+        Ok(())
+    }
+
     pub fn backspace_char(&mut self, txt_pos: TextPos) -> UIResult<()> {
         if txt_pos.column > 0 {
             let prev_col_pos = TextPos {
@@ -153,7 +212,25 @@ impl TextBuffer {
 
             line_ref.remove(prev_col_pos.column);
         } else if txt_pos.line > 0 {
-            self.lines.remove(txt_pos.line);
+            // at the start of a line, join it onto the end of the previous line
+            self.ensure_bounds(txt_pos.line)?;
+
+            let joined_line = self.lines.remove(txt_pos.line);
+
+            self.lines[txt_pos.line - 1].push_str(&joined_line);
+        }
+
+        Ok(())
+    }
+
+    // removes the lines first_line_nr..=last_line_nr, an empty buffer keeps one empty line
+    pub fn del_lines(&mut self, first_line_nr: usize, last_line_nr: usize) -> UIResult<()> {
+        self.ensure_bounds(last_line_nr)?;
+
+        self.lines.drain(first_line_nr..=last_line_nr);
+
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
         }
 
         Ok(())
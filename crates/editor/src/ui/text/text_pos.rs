@@ -1,5 +1,8 @@
 use std::cmp::Ordering;
 
+/// A position in the text, `column` is a byte offset within the line.
+/// Positions are ordered by line first and column second, so any position on an earlier line
+/// comes before every position on a later line, no matter how long those lines are.
 #[derive(Debug, Copy, Clone)]
 pub struct TextPos {
     pub line: usize,
@@ -47,3 +50,27 @@ impl PartialEq for TextPos {
 }
 
 impl Eq for TextPos {}
+
+#[cfg(test)]
+mod test_text_pos {
+    use crate::ui::text::text_pos::TextPos;
+
+    fn pos(line: usize, column: usize) -> TextPos {
+        TextPos { line, column }
+    }
+
+    #[test]
+    fn order_across_lines() {
+        assert!(pos(1, 0) < pos(2, 0));
+        assert!(pos(1, 5) < pos(2, 0));
+        assert!(pos(2, 0) > pos(1, 100));
+    }
+
+    #[test]
+    fn order_within_line() {
+        assert!(pos(3, 2) < pos(3, 4));
+        assert!(pos(3, 4) > pos(3, 2));
+        assert_eq!(pos(3, 4), pos(3, 4));
+        assert!(pos(3, 4) <= pos(3, 4));
+    }
+}
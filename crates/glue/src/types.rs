@@ -8,7 +8,7 @@ use roc_builtins::bitcode::{
 };
 use roc_collections::VecMap;
 use roc_module::{
-    ident::TagName,
+    ident::{sort_by_name, TagName},
     symbol::{Interns, Symbol},
 };
 use roc_mono::layout::{
@@ -1482,7 +1482,7 @@ fn union_tags_to_types<'a>(
         .collect();
 
     // Sort tags alphabetically by tag name
-    tags.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
+    sort_by_name(&mut tags);
 
     tags.into_iter()
         .map(|(tag_name, payload_vars)| {
@@ -1,10 +1,12 @@
 use peg::error::ParseError;
 use roc_code_markup::markup::attribute::Attributes;
 use roc_code_markup::markup::common_nodes::{
-    else_mn, if_mn, new_assign_mn, new_dot_mn, new_equals_mn, new_if_expr_mn,
-    new_module_name_mn_id, new_module_var_mn, then_mn,
+    else_mn, if_mn, new_assign_mn, new_bin_op_expr_mn_id, new_dot_mn, new_equals_mn,
+    new_equals_mn_w_nl, new_if_expr_mn, new_indent_mn, new_indented_assign_mn, new_left_paren_mn,
+    new_module_name_mn_id, new_module_var_mn, new_operator_mn, new_parens_around_mn,
+    new_right_paren_mn, then_mn,
 };
-use roc_code_markup::markup::nodes::MarkupNode;
+use roc_code_markup::markup::nodes::{MarkupNode, SINGLE_INDENT};
 use roc_code_markup::slow_pool::{MarkNodeId, SlowPool};
 use roc_code_markup::syntax_highlight::HighlightStyle;
 
@@ -41,7 +43,30 @@ peg::parser! {
     grammar highlightparser(t_table: &TokenTable, code_str: &str, mn_pool: &mut SlowPool) for [T] {
 
       pub rule full_expr() -> MarkNodeId =
-        common_expr()
+        op_expr()
+
+      // operator precedence does not matter for highlighting, so operands and operators are kept flat
+      rule op_expr() -> MarkNodeId =
+        first_id:common_expr() rest_ids:op_and_expr()* {
+          new_bin_op_expr_mn_id(
+            merge_ids(first_id, flatten_tups(rest_ids)),
+            mn_pool
+          )
+        }
+
+      rule op_and_expr() -> (MarkNodeId, MarkNodeId) =
+        op_id:bin_op() e_id:common_expr() { (op_id, e_id) }
+
+      rule bin_op() -> MarkNodeId =
+        p:position!() [T::OpPlus | T::OpMinus | T::Asterisk | T::OpSlash | T::OpDoubleSlash
+          | T::OpPercent | T::OpCaret | T::OpEquals | T::OpNotEquals | T::OpLessThan
+          | T::OpGreaterThan | T::OpLessThanOrEq | T::OpGreaterThanOrEq | T::OpAnd | T::OpOr
+          | T::OpPizza]
+        {
+          mn_pool.add(
+            new_operator_mn(format!(" {} ", t_table.extract_str(p, code_str)))
+          )
+        }
 
       pub rule full_exprs() -> Vec<MarkNodeId> =
         opt_same_indent_expr()*
@@ -54,7 +79,9 @@ peg::parser! {
 
       rule common_expr() -> MarkNodeId =
         if_expr()
+        / parens_around()
         / p:position!() [T::Number] { add_new_mn(t_table.extract_str(p, code_str), HighlightStyle::Number, mn_pool) }
+        / p:position!() [T::String] { add_new_mn(t_table.extract_str(p, code_str), HighlightStyle::String, mn_pool) }
         / module_var()
         / lowercase_ident()
 
@@ -66,6 +93,20 @@ peg::parser! {
           )
         }
 
+      rule parens_around() -> MarkNodeId =
+        left_id:left_paren() e_id:full_expr() right_id:right_paren()
+        {
+          mn_pool.add(
+            new_parens_around_mn(left_id, e_id, right_id)
+          )
+        }
+
+      rule left_paren() -> MarkNodeId =
+        [T::OpenParen] {mn_pool.add(new_left_paren_mn())}
+
+      rule right_paren() -> MarkNodeId =
+        [T::CloseParen] {mn_pool.add(new_right_paren_mn())}
+
       rule if() -> MarkNodeId =
         [T::KeywordIf] {mn_pool.add(if_mn())}
 
@@ -86,10 +127,10 @@ peg::parser! {
         opt_same_indent_def()+
 
       rule body() -> MarkNodeId =
-          ident_id:ident() as_id:assign() [T::OpenIndent] e_id:full_expr() /*TODO not sure when this is needed> es:full_exprs()*/ ([T::CloseIndent] / end_of_file())
+          ident_id:ident() as_id:assign_w_nl() indent_id:open_indent() e_id:full_expr() /*TODO not sure when this is needed> es:full_exprs()*/ ([T::CloseIndent] / end_of_file())
           {
             mn_pool.add(
-              new_assign_mn(ident_id, as_id, e_id)
+              new_indented_assign_mn(ident_id, as_id, indent_id, e_id)
             )
           }
           /
@@ -118,6 +159,13 @@ peg::parser! {
       rule assign() -> MarkNodeId =
         [T::OpAssignment] { mn_pool.add(new_equals_mn()) }
 
+      // keeps the line break after the `=` of a body that starts on the next line
+      rule assign_w_nl() -> MarkNodeId =
+        [T::OpAssignment] &[T::OpenIndent] { mn_pool.add(new_equals_mn_w_nl()) }
+
+      rule open_indent() -> MarkNodeId =
+        p:position!() [T::OpenIndent] { mn_pool.add(new_indent_mn(indent_level(t_table, p, code_str))) }
+
       rule dot() -> MarkNodeId =
         [T::Dot] { mn_pool.add(new_dot_mn()) }
 
@@ -155,6 +203,17 @@ fn flatten_tups(tup_vec: Vec<(MarkNodeId, MarkNodeId)>) -> Vec<MarkNodeId> {
     tup_vec.iter().flat_map(|(a, b)| vec![*a, *b]).collect()
 }
 
+// the indent level of the line that starts at the OpenIndent token at index
+fn indent_level(t_table: &TokenTable, index: usize, code_str: &str) -> usize {
+    let line_start = t_table.offsets[index];
+    let spaces = code_str.as_bytes()[line_start..]
+        .iter()
+        .take_while(|byte| **byte == b' ')
+        .count();
+
+    (spaces / SINGLE_INDENT.len()).max(1)
+}
+
 fn add_new_mn(
     text: &str,
     highlight_style: HighlightStyle,
@@ -211,6 +270,11 @@ pub mod highlight_tests {
         test_highlight_expr("0", "0");
     }
 
+    #[test]
+    fn test_highlight_string() {
+        test_highlight_expr("\"a < b & c\"", "\"a < b & c\"");
+    }
+
     #[test]
     fn test_highlight_module_var() {
         test_highlight_expr("Foo.Bar.var", "Foo.Bar.var");
@@ -224,6 +288,14 @@ pub mod highlight_tests {
         )
     }
 
+    #[test]
+    fn test_highlight_parens_around() {
+        test_highlight_expr("( 1 + 2 )", "( 1 + 2 )");
+        test_highlight_expr("(1+2)", "( 1 + 2 )");
+        test_highlight_expr("((x))", "( ( x ) )");
+        test_highlight_expr("(a + (b * 3)) - c", "( a + ( b * 3 ) ) - c");
+    }
+
     #[test]
     fn test_highlight_defs() {
         let mut mark_node_pool = SlowPool::default();
@@ -241,4 +313,40 @@ pub mod highlight_tests {
 
         assert_eq!(&str_buffer, "a = 0\n\n");
     }
+
+    #[test]
+    fn test_highlight_indented_def() {
+        let mut mark_node_pool = SlowPool::default();
+
+        let mut str_buffer = String::new();
+
+        node_to_string_w_children(
+            *highlight_defs("a =\n    0", &mut mark_node_pool)
+                .unwrap()
+                .get(0)
+                .unwrap(),
+            &mut str_buffer,
+            &mark_node_pool,
+        );
+
+        assert_eq!(&str_buffer, "a =\n    0\n\n");
+    }
+
+    #[test]
+    fn test_highlight_two_level_indented_def() {
+        let mut mark_node_pool = SlowPool::default();
+
+        let mut str_buffer = String::new();
+
+        node_to_string_w_children(
+            *highlight_defs("a =\n        0", &mut mark_node_pool)
+                .unwrap()
+                .get(0)
+                .unwrap(),
+            &mut str_buffer,
+            &mark_node_pool,
+        );
+
+        assert_eq!(&str_buffer, "a =\n        0\n\n");
+    }
 }
@@ -2,7 +2,7 @@ use bumpalo::Bump;
 use const_format::concatcp;
 use inkwell::context::Context;
 use libloading::Library;
-use roc_gen_llvm::llvm::build::LlvmBackendMode;
+use roc_gen_llvm::llvm::build::{verify_module, LlvmBackendMode};
 use roc_intern::SingleThreadedInterner;
 use roc_mono::layout::Layout;
 use roc_types::subs::Subs;
@@ -230,6 +230,7 @@ pub fn mono_module_to_dylib<'a>(
 
     let context = Context::create();
     let builder = context.create_builder();
+    let alloca_builder = context.create_builder();
     let module = arena.alloc(roc_gen_llvm::llvm::build::module_from_builtins(
         &target, &context, "",
     ));
@@ -245,6 +246,7 @@ pub fn mono_module_to_dylib<'a>(
         arena,
         layout_interner: &layout_interner,
         builder: &builder,
+        alloca_builder: &alloca_builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
         context: &context,
@@ -254,6 +256,7 @@ pub fn mono_module_to_dylib<'a>(
         mode: LlvmBackendMode::GenTest, // so roc_panic is generated
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        global_constants: Default::default(),
     };
 
     // Add roc_alloc, roc_realloc, and roc_dealloc, since the repl has no
@@ -296,10 +299,10 @@ pub fn mono_module_to_dylib<'a>(
     // env.module.print_to_stderr();
 
     // Verify the module
-    if let Err(errors) = env.module.verify() {
+    if let Err(errors) = verify_module(env.module) {
         panic!(
             "Errors defining module:\n{}\n\nUncomment things nearby to see more details.",
-            errors.to_string()
+            errors
         );
     }
 
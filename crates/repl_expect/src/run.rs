@@ -6,7 +6,10 @@ use inkwell::context::Context;
 use roc_build::link::llvm_module_to_dylib;
 use roc_collections::{MutSet, VecMap};
 use roc_gen_llvm::{
-    llvm::{build::LlvmBackendMode, externs::add_default_roc_externs},
+    llvm::{
+        build::{verify_module, LlvmBackendMode},
+        externs::add_default_roc_externs,
+    },
     run_roc::RocCallResult,
     run_roc_dylib,
 };
@@ -508,6 +511,7 @@ pub fn expect_mono_module_to_dylib<'a>(
 
     let context = Context::create();
     let builder = context.create_builder();
+    let alloca_builder = context.create_builder();
     let module = arena.alloc(roc_gen_llvm::llvm::build::module_from_builtins(
         &target, &context, "",
     ));
@@ -523,6 +527,7 @@ pub fn expect_mono_module_to_dylib<'a>(
         arena,
         layout_interner: &layout_interner,
         builder: &builder,
+        alloca_builder: &alloca_builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
         context: &context,
@@ -532,6 +537,7 @@ pub fn expect_mono_module_to_dylib<'a>(
         mode,
         // important! we don't want any procedures to get the C calling convention
         exposed_to_host: MutSet::default(),
+        global_constants: Default::default(),
     };
 
     // Add roc_alloc, roc_realloc, and roc_dealloc, since the repl has no
@@ -604,12 +610,12 @@ pub fn expect_mono_module_to_dylib<'a>(
     // env.module.print_to_stderr();
 
     // Verify the module
-    if let Err(errors) = env.module.verify() {
+    if let Err(errors) = verify_module(env.module) {
         let path = std::env::temp_dir().join("test.ll");
         env.module.print_to_file(&path).unwrap();
         panic!(
             "Errors defining module:\n{}\n\nUncomment things nearby to see more details. IR written to `{:?}`",
-            errors.to_string(), path,
+            errors, path,
         );
     }
 